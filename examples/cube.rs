@@ -1,6 +1,9 @@
 use bytemuck::{Pod, Zeroable};
 use imgui::*;
-use imgui_wgpu::{Renderer, RendererConfig, Texture, TextureConfig};
+use imgui_wgpu::{
+    util::{acquire_frame, FrameAcquire},
+    Renderer, RendererConfig, Texture, TextureConfig, TexturePool,
+};
 use imgui_winit_support::WinitPlatform;
 use pollster::block_on;
 use std::{sync::Arc, time::Instant};
@@ -341,6 +344,8 @@ struct ImguiState {
     example: Example,
     example_size: [f32; 2],
     example_texture_id: TextureId,
+    // Keeps the render targets of previous sizes around while the window is resized.
+    example_pool: TexturePool,
     last_frame: Instant,
     last_cursor: Option<MouseCursor>,
 }
@@ -455,7 +460,8 @@ impl AppWindow {
             ..Default::default()
         };
 
-        let mut renderer = Renderer::new(&mut context, &self.device, &self.queue, renderer_config);
+        let mut renderer = Renderer::new(&mut context, &self.device, &self.queue, renderer_config)
+            .expect("Failed to create the renderer");
 
         let last_frame = Instant::now();
         let last_cursor = None;
@@ -484,6 +490,7 @@ impl AppWindow {
             example,
             example_size,
             example_texture_id,
+            example_pool: TexturePool::new(4),
             last_frame,
             last_cursor,
         })
@@ -543,13 +550,17 @@ impl ApplicationHandler for App {
                     .update_delta_time(now - imgui.last_frame);
                 imgui.last_frame = now;
 
-                let frame = match window.surface.get_current_texture() {
-                    Ok(frame) => frame,
-                    Err(e) => {
-                        eprintln!("dropped frame: {e:?}");
-                        return;
-                    }
-                };
+                let frame =
+                    match acquire_frame(&window.surface, &window.device, &window.surface_desc)
+                        .map(FrameAcquire::into_frame)
+                    {
+                        Ok(Some(frame)) => frame,
+                        Ok(None) => return,
+                        Err(e) => {
+                            eprintln!("dropped frame: {e:?}");
+                            return;
+                        }
+                    };
                 imgui
                     .platform
                     .prepare_frame(imgui.context.io_mut(), &window.window)
@@ -583,19 +594,15 @@ impl ApplicationHandler for App {
                     if size != imgui.example_size && size[0] >= 1.0 && size[1] >= 1.0 {
                         imgui.example_size = size;
                         let scale = &ui.io().display_framebuffer_scale;
-                        let texture_config = TextureConfig {
-                            size: Extent3d {
+                        imgui.example_pool.resize(
+                            &window.device,
+                            &mut imgui.renderer,
+                            imgui.example_texture_id,
+                            Extent3d {
                                 width: (imgui.example_size[0] * scale[0]) as u32,
                                 height: (imgui.example_size[1] * scale[1]) as u32,
                                 ..Default::default()
                             },
-                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                                | wgpu::TextureUsages::TEXTURE_BINDING,
-                            ..Default::default()
-                        };
-                        imgui.renderer.textures.replace(
-                            imgui.example_texture_id,
-                            Texture::new(&window.device, &imgui.renderer, texture_config),
                         );
                     }
 
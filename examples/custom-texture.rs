@@ -1,6 +1,9 @@
 use image::ImageFormat;
 use imgui::*;
-use imgui_wgpu::{Renderer, RendererConfig, Texture, TextureConfig};
+use imgui_wgpu::{
+    util::{acquire_frame, FrameAcquire},
+    Renderer, RendererConfig, Texture, TextureConfig,
+};
 use imgui_winit_support::WinitPlatform;
 use pollster::block_on;
 use std::{sync::Arc, time::Instant};
@@ -136,7 +139,8 @@ impl AppWindow {
             ..Default::default()
         };
 
-        let mut renderer = Renderer::new(&mut context, &self.device, &self.queue, renderer_config);
+        let mut renderer = Renderer::new(&mut context, &self.device, &self.queue, renderer_config)
+            .expect("Failed to create the renderer");
 
         let last_frame = Instant::now();
         let last_cursor = None;
@@ -231,13 +235,17 @@ impl ApplicationHandler for App {
                     .update_delta_time(now - imgui.last_frame);
                 imgui.last_frame = now;
 
-                let frame = match window.surface.get_current_texture() {
-                    Ok(frame) => frame,
-                    Err(e) => {
-                        eprintln!("dropped frame: {e:?}");
-                        return;
-                    }
-                };
+                let frame =
+                    match acquire_frame(&window.surface, &window.device, &window.surface_desc)
+                        .map(FrameAcquire::into_frame)
+                    {
+                        Ok(Some(frame)) => frame,
+                        Ok(None) => return,
+                        Err(e) => {
+                            eprintln!("dropped frame: {e:?}");
+                            return;
+                        }
+                    };
                 imgui
                     .platform
                     .prepare_frame(imgui.context.io_mut(), &window.window)
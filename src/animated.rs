@@ -0,0 +1,113 @@
+//! Animated GIF and APNG images.
+
+use crate::{Renderer, Texture, TextureConfig};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::error::{ParameterError, ParameterErrorKind};
+use image::{AnimationDecoder, DynamicImage, Frame, ImageError, ImageFormat, ImageResult};
+use imgui::TextureId;
+use std::io::Cursor;
+use std::time::Duration;
+use wgpu::*;
+
+/// Frames shorter than this are shown for [`DEFAULT_FRAME_DELAY`] instead, like browsers do.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// The frames of an animated image, each registered as a texture of the renderer.
+///
+/// Call [`Self::advance`] once per frame with the frame time to get the texture to
+/// display.
+pub struct AnimatedTexture {
+    frames: Vec<(TextureId, Duration)>,
+    current: usize,
+    elapsed: Duration,
+}
+
+impl AnimatedTexture {
+    /// Decode all frames of a GIF or APNG file and upload them to textures inserted into
+    /// [`Renderer::textures`]. Other formats are decoded as a single frame.
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        renderer: &mut Renderer,
+        bytes: &[u8],
+    ) -> ImageResult<Self> {
+        let frames = match image::guess_format(bytes)? {
+            ImageFormat::Gif => GifDecoder::new(Cursor::new(bytes))?
+                .into_frames()
+                .collect_frames()?,
+            ImageFormat::Png if PngDecoder::new(Cursor::new(bytes))?.is_apng() => {
+                PngDecoder::new(Cursor::new(bytes))?
+                    .apng()
+                    .into_frames()
+                    .collect_frames()?
+            }
+            _ => vec![Frame::new(image::load_from_memory(bytes)?.to_rgba8())],
+        };
+        if frames.is_empty() {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::NoMoreData,
+            )));
+        }
+
+        let frames = frames
+            .into_iter()
+            .map(|frame| {
+                let delay = match Duration::from(frame.delay()) {
+                    delay if delay < MIN_FRAME_DELAY => DEFAULT_FRAME_DELAY,
+                    delay => delay,
+                };
+                let image = DynamicImage::ImageRgba8(frame.into_buffer());
+                let config = TextureConfig {
+                    label: Some("imgui-wgpu animation frame"),
+                    ..Default::default()
+                };
+                let texture = Texture::from_image(device, queue, renderer, &image, config);
+                (renderer.textures.insert(texture), delay)
+            })
+            .collect();
+
+        Ok(Self {
+            frames,
+            current: 0,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    /// Advance the animation by `delta_time` and return the texture of the current frame.
+    pub fn advance(&mut self, delta_time: Duration) -> TextureId {
+        self.elapsed += delta_time;
+        while let Some(&(_, delay)) = self.frames.get(self.current) {
+            if self.elapsed < delay {
+                break;
+            }
+            self.elapsed -= delay;
+            self.current = (self.current + 1) % self.frames.len();
+        }
+        self.texture_id()
+    }
+
+    /// The texture of the current frame.
+    pub fn texture_id(&self) -> TextureId {
+        self.frames[self.current].0
+    }
+
+    /// The number of frames of the animation.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Restart the animation from the first frame.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// Remove the textures of all frames from the renderer.
+    pub fn remove(self, renderer: &mut Renderer) {
+        for (id, _) in self.frames {
+            renderer.textures.remove(id);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Packing many small images into a single texture.
+
+use crate::{Renderer, Texture, TextureConfig, TextureRegion};
+use imgui::TextureId;
+use wgpu::*;
+
+/// Space left between images, so linear filtering doesn't blend neighboring images.
+const PADDING: u32 = 1;
+
+/// The place of an image in a [`TextureAtlas`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRect {
+    /// The position of the top left corner of the image in pixels.
+    pub position: [u32; 2],
+    /// The size of the image in pixels.
+    pub size: [u32; 2],
+    /// The UV coordinates of the top left corner, for `imgui::Image::uv0`.
+    pub uv0: [f32; 2],
+    /// The UV coordinates of the bottom right corner, for `imgui::Image::uv1`.
+    pub uv1: [f32; 2],
+}
+
+/// A row of images of at most `height` pixels.
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Where the next image of the row starts.
+    x: u32,
+}
+
+/// Many small images, like icons, packed into one RGBA8 texture.
+///
+/// All images of the atlas are drawn with the same [`TextureId`] and therefore the same
+/// bind group, so the renderer doesn't have to switch bind groups between them. Images
+/// are packed into rows of similar height and can't be removed individually.
+pub struct TextureAtlas {
+    texture_id: TextureId,
+    size: [u32; 2],
+    shelves: Vec<Shelf>,
+}
+
+impl TextureAtlas {
+    /// Create an empty `width` by `height` atlas and insert its texture into
+    /// [`Renderer::textures`], pinned so it isn't evicted.
+    pub fn new(device: &Device, renderer: &mut Renderer, width: u32, height: u32) -> Self {
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                label: Some("imgui-wgpu texture atlas"),
+                format: Some(TextureFormat::Rgba8Unorm),
+                ..Default::default()
+            },
+        );
+        let texture_id = renderer.textures.insert(texture);
+        renderer.pin_texture(texture_id);
+        Self {
+            texture_id,
+            size: [width, height],
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Pack a `width` by `height` image of tightly packed RGBA8 pixels into the atlas
+    /// and upload it.
+    ///
+    /// Returns `None` if the atlas is full.
+    pub fn insert(
+        &mut self,
+        queue: &Queue,
+        renderer: &Renderer,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<AtlasRect> {
+        let [x, y] = self.allocate(width, height)?;
+        let texture = renderer
+            .textures
+            .get(self.texture_id)
+            .expect("the texture of the atlas was removed");
+        texture.write_region(
+            queue,
+            data,
+            Origin3d { x, y, z: 0 },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+        );
+
+        let [atlas_width, atlas_height] = self.size.map(|size| size as f32);
+        Some(AtlasRect {
+            position: [x, y],
+            size: [width, height],
+            uv0: [x as f32 / atlas_width, y as f32 / atlas_height],
+            uv1: [
+                (x + width) as f32 / atlas_width,
+                (y + height) as f32 / atlas_height,
+            ],
+        })
+    }
+
+    /// Find space for a `width` by `height` image, in the lowest row it fits into
+    /// without wasting much height, or in a new row.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        let [atlas_width, atlas_height] = self.size;
+        let padded_width = width + PADDING;
+        let padded_height = height + PADDING;
+
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| {
+                shelf.height >= padded_height
+                    && shelf.height <= padded_height * 2
+                    && shelf.x + width <= atlas_width
+            })
+            .min_by_key(|shelf| shelf.height);
+        let shelf = match shelf {
+            Some(shelf) => shelf,
+            None => {
+                let y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                if width > atlas_width || y + height > atlas_height {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height: padded_height,
+                    x: 0,
+                });
+                self.shelves.last_mut().unwrap()
+            }
+        };
+
+        let position = [shelf.x, shelf.y];
+        shelf.x += padded_width;
+        Some(position)
+    }
+
+    /// The region of the atlas texture showing the image at `rect`.
+    pub fn region(&self, rect: &AtlasRect) -> TextureRegion {
+        TextureRegion {
+            texture_id: self.texture_id,
+            uv0: rect.uv0,
+            uv1: rect.uv1,
+        }
+    }
+
+    /// The id of the atlas texture, to draw images with their [`AtlasRect`].
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+
+    /// The size of the atlas in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// Forget all packed images, so their space is reused by the next insertions.
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+
+    /// Remove the atlas texture from the renderer.
+    pub fn remove(self, renderer: &mut Renderer) {
+        renderer.textures.remove(self.texture_id);
+        renderer.unpin_texture(self.texture_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atlas(width: u32, height: u32) -> TextureAtlas {
+        TextureAtlas {
+            texture_id: TextureId::new(0),
+            size: [width, height],
+            shelves: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn packs_images_of_similar_height_into_rows() {
+        let mut atlas = atlas(64, 64);
+        assert_eq!(atlas.allocate(10, 10), Some([0, 0]));
+        assert_eq!(atlas.allocate(10, 8), Some([11, 0]));
+        // Too high for the first row.
+        assert_eq!(atlas.allocate(10, 30), Some([0, 11]));
+        // Would waste more than half of the height of both rows.
+        assert_eq!(atlas.allocate(10, 4), Some([0, 42]));
+        // The lowest row the image fits into.
+        assert_eq!(atlas.allocate(10, 9), Some([22, 0]));
+    }
+
+    #[test]
+    fn starts_a_new_row_when_a_row_is_full() {
+        let mut atlas = atlas(64, 64);
+        assert_eq!(atlas.allocate(60, 10), Some([0, 0]));
+        assert_eq!(atlas.allocate(4, 10), Some([0, 11]));
+    }
+
+    #[test]
+    fn rejects_images_that_dont_fit() {
+        let mut atlas = atlas(64, 64);
+        assert_eq!(atlas.allocate(65, 1), None);
+        assert_eq!(atlas.allocate(1, 65), None);
+        assert_eq!(atlas.allocate(64, 64), Some([0, 0]));
+        assert_eq!(atlas.allocate(1, 1), None);
+
+        atlas.clear();
+        assert_eq!(atlas.allocate(1, 1), Some([0, 0]));
+    }
+}
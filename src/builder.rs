@@ -0,0 +1,389 @@
+//! A builder for `RendererConfig` that validates the configuration.
+
+use crate::{
+    shaders, DistanceField, RendererConfig, SamplerDefaults, FONT_ATLAS_FORMATS,
+    FS_ENTRY_POINT_LINEAR, FS_ENTRY_POINT_SRGB, MAX_VIEWS, VS_ENTRY_POINT,
+};
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroU32;
+use wgpu::{ColorTargetState, ShaderModuleDescriptor, ShaderSource, TextureFormat};
+
+/// A problem with a configuration found by [`RendererConfigBuilder::build`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RendererConfigError {
+    /// The WGSL shader doesn't declare a function with this entry point name.
+    MissingEntryPoint(String),
+    /// The sample count is not a power of two.
+    InvalidSampleCount(u32),
+    /// The texture format is a depth or stencil format.
+    NotAColorFormat(TextureFormat),
+    /// The depth format is not a depth or stencil format.
+    NotADepthFormat(TextureFormat),
+    /// More than [`MAX_VIEWS`] views were requested for multiview.
+    TooManyViews(u32),
+    /// Multiview and dual-source blending were both requested without a custom shader,
+    /// but the built-in shaders support only one of them.
+    ConflictingShaders,
+    /// Dual-source blending was requested together with additional color targets.
+    DualSourceWithMultipleTargets,
+    /// The font atlas format isn't one of [`FONT_ATLAS_FORMATS`].
+    UnsupportedFontAtlasFormat(TextureFormat),
+    /// Subpixel text was requested without dual-source blending.
+    SubpixelTextWithoutDualSource,
+}
+
+impl fmt::Display for RendererConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererConfigError::MissingEntryPoint(name) => {
+                write!(f, "imgui config error: the shader has no entry point '{name}'")
+            }
+            RendererConfigError::InvalidSampleCount(count) => {
+                write!(f, "imgui config error: sample count {count} is not a power of two")
+            }
+            RendererConfigError::NotAColorFormat(format) => {
+                write!(f, "imgui config error: {format:?} is not a color format")
+            }
+            RendererConfigError::NotADepthFormat(format) => {
+                write!(f, "imgui config error: {format:?} is not a depth format")
+            }
+            RendererConfigError::TooManyViews(views) => {
+                write!(
+                    f,
+                    "imgui config error: {views} views requested, at most {MAX_VIEWS} are supported"
+                )
+            }
+            RendererConfigError::ConflictingShaders => write!(
+                f,
+                "imgui config error: no built-in shader supports both multiview and dual-source blending"
+            ),
+            RendererConfigError::DualSourceWithMultipleTargets => write!(
+                f,
+                "imgui config error: dual-source blending requires a single color target"
+            ),
+            RendererConfigError::UnsupportedFontAtlasFormat(format) => {
+                write!(f, "imgui config error: {format:?} is not supported for font atlases")
+            }
+            RendererConfigError::SubpixelTextWithoutDualSource => write!(
+                f,
+                "imgui config error: subpixel text requires dual-source blending"
+            ),
+        }
+    }
+}
+
+impl Error for RendererConfigError {}
+
+/// Builds a [`RendererConfig`], checking the combination of settings up front instead of
+/// failing when the renderer is created.
+///
+/// Settings without a builder method can be changed on the built config.
+pub struct RendererConfigBuilder<'s> {
+    config: RendererConfig<'s>,
+    srgb_output: Option<bool>,
+}
+
+impl<'s> RendererConfigBuilder<'s> {
+    /// Start from the default config with the built-in shaders.
+    pub fn new() -> Self {
+        RendererConfigBuilder {
+            config: RendererConfig {
+                shader: None,
+                vertex_shader_entry_point: None,
+                fragment_shader_entry_point: None,
+                ..RendererConfig::new()
+            },
+            srgb_output: None,
+        }
+    }
+
+    /// The format of the render target. Unless [`Self::srgb_output`] is called, the
+    /// shader outputs linear color for sRGB formats and srgb color otherwise.
+    pub fn texture_format(mut self, format: TextureFormat) -> Self {
+        self.config.texture_format = format;
+        self
+    }
+
+    /// The format of the depth attachment of the render pass.
+    pub fn depth_format(mut self, format: TextureFormat) -> Self {
+        self.config.depth_format = Some(format);
+        self
+    }
+
+    /// The sample count of the render target.
+    pub fn sample_count(mut self, count: u32) -> Self {
+        self.config.sample_count = count;
+        self
+    }
+
+    /// Use a custom shader instead of the built-in ones.
+    pub fn shader(mut self, shader: ShaderModuleDescriptor<'s>) -> Self {
+        self.config.shader = Some(shader);
+        self
+    }
+
+    /// The vertex entry point, `vs_main` by default.
+    pub fn vertex_entry_point(mut self, entry_point: &'s str) -> Self {
+        self.config.vertex_shader_entry_point = Some(entry_point);
+        self
+    }
+
+    /// The fragment entry point, by default the built-in entry point matching the
+    /// output color space.
+    pub fn fragment_entry_point(mut self, entry_point: &'s str) -> Self {
+        self.config.fragment_shader_entry_point = Some(entry_point);
+        self
+    }
+
+    /// Whether the default fragment entry point outputs srgb instead of linear color.
+    pub fn srgb_output(mut self, srgb: bool) -> Self {
+        self.srgb_output = Some(srgb);
+        self
+    }
+
+    /// See [`RendererConfig::linearize_vertex_colors`].
+    pub fn linearize_vertex_colors(mut self, linearize: bool) -> Self {
+        self.config.linearize_vertex_colors = linearize;
+        self
+    }
+
+    /// See [`RendererConfig::with_multiview`].
+    pub fn multiview(mut self, views: NonZeroU32) -> Self {
+        self.config.multiview = Some(views);
+        self
+    }
+
+    /// See [`RendererConfig::with_dual_source_blending`].
+    pub fn dual_source_blending(mut self) -> Self {
+        self.config.dual_source_blending = true;
+        self
+    }
+
+    /// See [`RendererConfig::additional_color_targets`].
+    pub fn additional_color_targets(mut self, targets: Vec<Option<ColorTargetState>>) -> Self {
+        self.config.additional_color_targets = targets;
+        self
+    }
+
+    /// See [`RendererConfig::sampler_defaults`].
+    pub fn sampler_defaults(mut self, defaults: SamplerDefaults) -> Self {
+        self.config.sampler_defaults = defaults;
+        self
+    }
+
+    /// See [`RendererConfig::missing_texture_placeholder`].
+    pub fn missing_texture_placeholder(mut self) -> Self {
+        self.config.missing_texture_placeholder = true;
+        self
+    }
+
+    /// See [`RendererConfig::alpha8_font_atlas`].
+    pub fn alpha8_font_atlas(mut self) -> Self {
+        self.config.alpha8_font_atlas = true;
+        self
+    }
+
+    /// See [`RendererConfig::font_distance_field`].
+    pub fn font_distance_field(mut self, distance_field: DistanceField) -> Self {
+        self.config.font_distance_field = Some(distance_field);
+        self
+    }
+
+    /// See [`RendererConfig::with_subpixel_text`].
+    pub fn subpixel_text(mut self) -> Self {
+        self.config.subpixel_text = true;
+        self.config.dual_source_blending = true;
+        self
+    }
+
+    /// See [`RendererConfig::gamma_correct_text`].
+    pub fn gamma_correct_text(mut self) -> Self {
+        self.config.gamma_correct_text = true;
+        self
+    }
+
+    /// See [`RendererConfig::keep_font_atlas_data`].
+    pub fn keep_font_atlas_data(mut self) -> Self {
+        self.config.keep_font_atlas_data = true;
+        self
+    }
+
+    /// See [`RendererConfig::font_atlas_format`].
+    pub fn font_atlas_format(mut self, format: TextureFormat) -> Self {
+        self.config.font_atlas_format = Some(format);
+        self
+    }
+
+    /// Validate the settings and create the config.
+    pub fn build(self) -> Result<RendererConfig<'s>, RendererConfigError> {
+        let RendererConfigBuilder {
+            mut config,
+            srgb_output,
+        } = self;
+
+        if !config.sample_count.is_power_of_two() {
+            return Err(RendererConfigError::InvalidSampleCount(config.sample_count));
+        }
+        if config.texture_format.is_depth_stencil_format() {
+            return Err(RendererConfigError::NotAColorFormat(config.texture_format));
+        }
+        if let Some(format) = config.depth_format {
+            if !format.is_depth_stencil_format() {
+                return Err(RendererConfigError::NotADepthFormat(format));
+            }
+        }
+        validate(&config)?;
+
+        let shader = match config.shader.take() {
+            Some(shader) => shader,
+            None => match (config.multiview, config.dual_source_blending) {
+                (Some(_), true) => return Err(RendererConfigError::ConflictingShaders),
+                (Some(_), false) => shaders::MULTIVIEW,
+                (None, true) => shaders::DUAL_SOURCE,
+                (None, false) => shaders::DEFAULT,
+            },
+        };
+        let srgb = srgb_output.unwrap_or(!config.texture_format.is_srgb());
+        let vertex_entry_point = *config
+            .vertex_shader_entry_point
+            .get_or_insert(VS_ENTRY_POINT);
+        let fragment_entry_point = *config.fragment_shader_entry_point.get_or_insert(if srgb {
+            FS_ENTRY_POINT_SRGB
+        } else {
+            FS_ENTRY_POINT_LINEAR
+        });
+
+        // Only WGSL can be checked without compiling the shader.
+        if let ShaderSource::Wgsl(source) = &shader.source {
+            for entry_point in [vertex_entry_point, fragment_entry_point] {
+                if !declares_function(source, entry_point) {
+                    return Err(RendererConfigError::MissingEntryPoint(
+                        entry_point.to_owned(),
+                    ));
+                }
+            }
+        }
+
+        config.shader = Some(shader);
+        Ok(config)
+    }
+}
+
+impl Default for RendererConfigBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The checks of [`RendererConfigBuilder::build`] that `Renderer::new` repeats for
+/// configs created without the builder.
+pub(crate) fn validate(config: &RendererConfig) -> Result<(), RendererConfigError> {
+    if let Some(views) = config.multiview {
+        if views.get() as usize > MAX_VIEWS {
+            return Err(RendererConfigError::TooManyViews(views.get()));
+        }
+    }
+    if let Some(format) = config.font_atlas_format {
+        if !FONT_ATLAS_FORMATS.contains(&format) {
+            return Err(RendererConfigError::UnsupportedFontAtlasFormat(format));
+        }
+    }
+    if config.dual_source_blending && !config.additional_color_targets.is_empty() {
+        return Err(RendererConfigError::DualSourceWithMultipleTargets);
+    }
+    if config.subpixel_text && !config.dual_source_blending {
+        return Err(RendererConfigError::SubpixelTextWithoutDualSource);
+    }
+    Ok(())
+}
+
+/// Whether the WGSL `source` declares a function called `name`.
+fn declares_function(source: &str, name: &str) -> bool {
+    source.match_indices(name).any(|(start, _)| {
+        let before = source[..start].trim_end();
+        let after = source[start + name.len()..].trim_start();
+        source[..start].ends_with(char::is_whitespace)
+            && before.ends_with("fn")
+            && !before[..before.len() - 2].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && after.starts_with('(')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_declared_functions() {
+        let source = "fn vs_main(in: VertexInput) -> VertexOutput {}\n\
+            @fragment\nfn  fs_main_linear  (in: VertexOutput) {}";
+        assert!(declares_function(source, "vs_main"));
+        assert!(declares_function(source, "fs_main_linear"));
+        assert!(declares_function("fn\nvs_main()", "vs_main"));
+    }
+
+    #[test]
+    fn ignores_other_identifiers() {
+        assert!(!declares_function("fn vs_main_2() {}", "vs_main"));
+        assert!(!declares_function("fn my_vs_main() {}", "vs_main"));
+        assert!(!declares_function("fnvs_main() {}", "vs_main"));
+        assert!(!declares_function("let x = vs_main();", "vs_main"));
+        assert!(!declares_function("let myfn vs_main()", "vs_main"));
+        assert!(!declares_function("fn vs_main", "vs_main"));
+        assert!(!declares_function("", "vs_main"));
+    }
+
+    #[test]
+    fn rejects_configs_without_the_builder() {
+        let config = RendererConfig {
+            additional_color_targets: vec![None],
+            ..RendererConfig::new().with_dual_source_blending()
+        };
+        assert_eq!(
+            validate(&config),
+            Err(RendererConfigError::DualSourceWithMultipleTargets)
+        );
+        let config = RendererConfig {
+            subpixel_text: true,
+            ..RendererConfig::new()
+        };
+        assert_eq!(
+            validate(&config),
+            Err(RendererConfigError::SubpixelTextWithoutDualSource)
+        );
+        let config = RendererConfig {
+            font_atlas_format: Some(TextureFormat::Rgba16Float),
+            ..RendererConfig::new()
+        };
+        assert_eq!(
+            validate(&config),
+            Err(RendererConfigError::UnsupportedFontAtlasFormat(
+                TextureFormat::Rgba16Float
+            ))
+        );
+        let views = NonZeroU32::new(MAX_VIEWS as u32 + 1).unwrap();
+        let config = RendererConfig::new().with_multiview(views);
+        assert_eq!(
+            validate(&config),
+            Err(RendererConfigError::TooManyViews(MAX_VIEWS as u32 + 1))
+        );
+        assert_eq!(validate(&RendererConfig::new()), Ok(()));
+    }
+
+    #[test]
+    fn checks_the_entry_points_of_the_shader() {
+        assert!(RendererConfigBuilder::new().build().is_ok());
+        assert!(RendererConfigBuilder::new()
+            .srgb_output(true)
+            .build()
+            .is_ok());
+        assert_eq!(
+            RendererConfigBuilder::new()
+                .fragment_entry_point("fs_main")
+                .build()
+                .err(),
+            Some(RendererConfigError::MissingEntryPoint("fs_main".to_owned()))
+        );
+    }
+}
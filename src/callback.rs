@@ -0,0 +1,84 @@
+//! Custom render callbacks inside imgui draw lists.
+
+use crate::ScissorRect;
+use imgui::{sys, Ui};
+use wgpu::RenderPass;
+
+/// A custom render callback, see [`Renderer::register_callback`](crate::Renderer::register_callback).
+///
+/// The callback is invoked while the renderer records the imgui draw commands, with the
+/// render pass and a [`ClipStack`] holding the clip rect of its position in the draw list.
+/// The renderer restores its own pipeline, bind groups, buffers and scissor afterwards.
+pub type RenderCallback = Box<dyn Fn(&mut RenderPass<'_>, &mut ClipStack) + Send + Sync>;
+
+/// Identifies a registered [`RenderCallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderCallbackId(pub(crate) usize);
+
+impl RenderCallbackId {
+    /// Schedule the callback at the current position of the current window's draw list.
+    pub fn add_to_window_draw_list(self, _ui: &Ui) {
+        // Safety: holding a `Ui` guarantees that a frame is active, so there is a current
+        // window draw list. The id is stored in the user data and never dereferenced.
+        unsafe {
+            sys::ImDrawList_AddCallback(
+                sys::igGetWindowDrawList(),
+                Some(render_callback_marker),
+                self.0 as *mut _,
+            );
+        }
+    }
+}
+
+/// Identifies the draw commands of render callbacks, never actually called.
+pub(crate) unsafe extern "C" fn render_callback_marker(
+    _parent_list: *const sys::ImDrawList,
+    _cmd: *const sys::ImDrawCmd,
+) {
+}
+
+/// A stack of nested scissor rects for custom rendering inside imgui draw lists.
+///
+/// The bottom of the stack is the clip rect imgui assigned to the callback, which can't
+/// be popped. Pushed rects are intersected with the current one.
+#[derive(Debug)]
+pub struct ClipStack {
+    stack: Vec<ScissorRect>,
+}
+
+impl ClipStack {
+    pub(crate) fn new(base: ScissorRect) -> Self {
+        Self { stack: vec![base] }
+    }
+
+    /// The current scissor rect in physical pixels.
+    pub fn current(&self) -> ScissorRect {
+        *self.stack.last().unwrap()
+    }
+
+    /// Push `rect` intersected with the current scissor rect and apply it to `rpass`.
+    ///
+    /// Returns `false` if the intersection is empty, in which case the scissor of `rpass`
+    /// is left unchanged and nothing should be drawn until the matching [`Self::pop`].
+    pub fn push(&mut self, rpass: &mut RenderPass<'_>, rect: ScissorRect) -> bool {
+        let scissor = self.current().intersect(&rect);
+        self.stack.push(scissor);
+        if scissor.is_empty() {
+            false
+        } else {
+            scissor.apply(rpass);
+            true
+        }
+    }
+
+    /// Pop the innermost scissor rect and restore the previous one on `rpass`.
+    pub fn pop(&mut self, rpass: &mut RenderPass<'_>) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            let scissor = self.current();
+            if !scissor.is_empty() {
+                scissor.apply(rpass);
+            }
+        }
+    }
+}
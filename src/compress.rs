@@ -0,0 +1,462 @@
+//! Compressing RGBA images to BC1 and BC7 on the CPU.
+
+use crate::{Renderer, Texture, TextureConfig};
+use std::borrow::Cow;
+use wgpu::*;
+
+/// The interpolation weights of the 4-bit indices of BC7.
+const BC7_WEIGHTS: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+impl Texture {
+    /// Create a texture from tightly packed 8-bit RGBA `data`, compressing it to BC1 if it
+    /// is opaque or to BC7 otherwise, and upload it.
+    ///
+    /// This cuts the memory of the texture to an eighth or a quarter, at the cost of a
+    /// slower upload and some loss of quality. The data is uploaded uncompressed if the
+    /// device lacks `Features::TEXTURE_COMPRESSION_BC` or the size isn't a multiple of
+    /// the 4x4 blocks. The size, format and mip level count in `config` are replaced, an
+    /// `Rgba8UnormSrgb` format selects the sRGB variants.
+    pub fn from_rgba8_compressed(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        config: TextureConfig,
+    ) -> Texture {
+        let srgb = config.format == Some(TextureFormat::Rgba8UnormSrgb);
+        let compressible = device.features().contains(Features::TEXTURE_COMPRESSION_BC)
+            && width.is_multiple_of(4)
+            && height.is_multiple_of(4);
+        let opaque = data.chunks_exact(4).all(|pixel| pixel[3] == u8::MAX);
+
+        let (format, data): (_, Cow<[u8]>) = if !compressible {
+            (TextureFormat::Rgba8Unorm, Cow::Borrowed(data))
+        } else if opaque {
+            (
+                TextureFormat::Bc1RgbaUnorm,
+                Cow::Owned(compress_bc1(data, width)),
+            )
+        } else {
+            (
+                TextureFormat::Bc7RgbaUnorm,
+                Cow::Owned(compress_bc7(data, width)),
+            )
+        };
+        let format = if srgb {
+            format.add_srgb_suffix()
+        } else {
+            format
+        };
+
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                format: Some(format),
+                mip_level_count: 1,
+                ..config
+            },
+        );
+        texture.write(queue, &data, width, height);
+        texture
+    }
+}
+
+/// Compress RGBA `data` whose rows are `width` pixels to BC1 blocks, one row of blocks
+/// after another. The size has to be a multiple of 4.
+fn compress_bc1(data: &[u8], width: u32) -> Vec<u8> {
+    blocks(data, width)
+        .flat_map(|block| encode_bc1(&block))
+        .collect()
+}
+
+/// Compress RGBA `data` whose rows are `width` pixels to BC7 blocks, one row of blocks
+/// after another. The size has to be a multiple of 4.
+fn compress_bc7(data: &[u8], width: u32) -> Vec<u8> {
+    blocks(data, width)
+        .flat_map(|block| encode_bc7(&block))
+        .collect()
+}
+
+/// Compress single channel `data` whose rows are `width` pixels to BC4 blocks, one row of
+/// blocks after another. The size has to be a multiple of 4.
+pub(crate) fn compress_bc4(data: &[u8], width: u32) -> Vec<u8> {
+    let width = width as usize;
+    let block_rows = data.len() / width / 4;
+    (0..block_rows)
+        .flat_map(|block_y| {
+            (0..width / 4).flat_map(move |block_x| {
+                encode_bc4(&std::array::from_fn(|i| {
+                    data[(block_y * 4 + i / 4) * width + block_x * 4 + i % 4]
+                }))
+            })
+        })
+        .collect()
+}
+
+/// The 4x4 blocks of pixels of RGBA `data`, row by row.
+fn blocks(data: &[u8], width: u32) -> impl Iterator<Item = [[u8; 4]; 16]> + '_ {
+    let width = width as usize;
+    let block_rows = data.len() / 4 / width / 4;
+    (0..block_rows).flat_map(move |block_y| {
+        (0..width / 4).map(move |block_x| {
+            std::array::from_fn(|i| {
+                let offset = ((block_y * 4 + i / 4) * width + block_x * 4 + i % 4) * 4;
+                data[offset..offset + 4].try_into().unwrap()
+            })
+        })
+    })
+}
+
+/// The endpoints of a line through `pixels` along the direction they vary most in,
+/// considering the first `N` channels.
+fn principal_endpoints<const N: usize>(pixels: &[[u8; 4]; 16]) -> ([f32; N], [f32; N]) {
+    let pixels = pixels.map(|pixel| std::array::from_fn::<f32, N, _>(|c| pixel[c] as f32));
+    let mean: [f32; N] = std::array::from_fn(|c| pixels.iter().map(|p| p[c]).sum::<f32>() / 16.0);
+
+    let mut covariance = [[0.0; N]; N];
+    for pixel in &pixels {
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += (pixel[i] - mean[i]) * (pixel[j] - mean[j]);
+            }
+        }
+    }
+    // A few steps of power iteration are enough for the dominant axis. Starting from the
+    // channel that varies most, as a fixed start like the diagonal is orthogonal to the
+    // axis of e.g. red fading to green and would find no axis at all.
+    let channel = (0..N)
+        .max_by(|&a, &b| covariance[a][a].total_cmp(&covariance[b][b]))
+        .unwrap();
+    let mut axis = covariance[channel];
+    for _ in 0..8 {
+        let next: [f32; N] =
+            std::array::from_fn(|i| (0..N).map(|j| covariance[i][j] * axis[j]).sum::<f32>());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+
+    let (mut min, mut max) = (f32::MAX, f32::MIN);
+    for pixel in &pixels {
+        let t: f32 = (0..N).map(|c| (pixel[c] - mean[c]) * axis[c]).sum();
+        min = min.min(t);
+        max = max.max(t);
+    }
+    let at = |t: f32| std::array::from_fn(|c| (mean[c] + axis[c] * t).clamp(0.0, 255.0));
+    (at(min), at(max))
+}
+
+/// The index of the entry of `palette` closest to `pixel` in the first `N` channels.
+fn closest<const N: usize>(palette: &[[u32; 4]], pixel: [u8; 4]) -> u32 {
+    let distance = |color: &[u32; 4]| -> u32 {
+        (0..N)
+            .map(|c| (color[c] as i32 - pixel[c] as i32).pow(2) as u32)
+            .sum()
+    };
+    (0..palette.len())
+        .min_by_key(|&i| distance(&palette[i]))
+        .unwrap() as u32
+}
+
+/// Encode an opaque block in the four color mode of BC1.
+fn encode_bc1(block: &[[u8; 4]; 16]) -> [u8; 8] {
+    let (start, end) = principal_endpoints::<3>(block);
+    let to_565 = |color: [f32; 3]| {
+        let r = (color[0] * 31.0 / 255.0).round() as u16;
+        let g = (color[1] * 63.0 / 255.0).round() as u16;
+        let b = (color[2] * 31.0 / 255.0).round() as u16;
+        r << 11 | g << 5 | b
+    };
+    let from_565 = |color: u16| -> [u32; 4] {
+        let (r, g, b) = (
+            (color >> 11) as u32,
+            (color >> 5 & 63) as u32,
+            (color & 31) as u32,
+        );
+        [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]
+    };
+
+    // The four color mode requires the first endpoint to be the larger one.
+    let (mut color0, mut color1) = (to_565(end), to_565(start));
+    if color0 < color1 {
+        std::mem::swap(&mut color0, &mut color1);
+    }
+    let mut indices = 0;
+    if color0 != color1 {
+        let [c0, c1] = [from_565(color0), from_565(color1)];
+        let palette = [
+            c0,
+            c1,
+            std::array::from_fn(|c| (2 * c0[c] + c1[c]) / 3),
+            std::array::from_fn(|c| (c0[c] + 2 * c1[c]) / 3),
+        ];
+        for (i, &pixel) in block.iter().enumerate() {
+            indices |= closest::<3>(&palette, pixel) << (2 * i);
+        }
+    }
+
+    let mut encoded = [0; 8];
+    encoded[0..2].copy_from_slice(&color0.to_le_bytes());
+    encoded[2..4].copy_from_slice(&color1.to_le_bytes());
+    encoded[4..8].copy_from_slice(&indices.to_le_bytes());
+    encoded
+}
+
+/// Encode a block of BC4 between its smallest and largest value, in the mode with six
+/// interpolated values.
+fn encode_bc4(block: &[u8; 16]) -> [u8; 8] {
+    let red0 = *block.iter().max().unwrap();
+    let red1 = *block.iter().min().unwrap();
+    let mut indices = 0u64;
+    if red0 != red1 {
+        let (red0, red1) = (red0 as u32, red1 as u32);
+        let palette: [u32; 8] = std::array::from_fn(|i| match i {
+            0 => red0,
+            1 => red1,
+            i => ((8 - i as u32) * red0 + (i as u32 - 1) * red1 + 3) / 7,
+        });
+        for (i, &value) in block.iter().enumerate() {
+            let index = (0..8)
+                .min_by_key(|&index| palette[index].abs_diff(value as u32))
+                .unwrap();
+            indices |= (index as u64) << (3 * i);
+        }
+    }
+
+    let mut encoded = [0; 8];
+    encoded[0] = red0;
+    encoded[1] = red1;
+    encoded[2..8].copy_from_slice(&indices.to_le_bytes()[..6]);
+    encoded
+}
+
+/// Encode a block in mode 6 of BC7, with a single pair of RGBA endpoints and 4-bit
+/// indices.
+fn encode_bc7(block: &[[u8; 4]; 16]) -> [u8; 16] {
+    let (start, end) = principal_endpoints::<4>(block);
+
+    // Each endpoint has 7 bits per channel and a shared lowest bit, pick the one that
+    // is closest to the unquantized color.
+    let quantize = |color: [f32; 4]| -> ([u32; 4], u32) {
+        [0, 1]
+            .map(|p_bit| {
+                let quantized =
+                    color.map(|v| ((v - p_bit as f32) / 2.0).round().clamp(0.0, 127.0) as u32);
+                let error: f32 = (0..4)
+                    .map(|c| (color[c] - (quantized[c] << 1 | p_bit) as f32).powi(2))
+                    .sum();
+                (quantized, p_bit, error)
+            })
+            .into_iter()
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(quantized, p_bit, _)| (quantized, p_bit))
+            .unwrap()
+    };
+    let (mut endpoint0, mut endpoint1) = (quantize(start), quantize(end));
+
+    let expand = |(color, p_bit): ([u32; 4], u32)| color.map(|v| v << 1 | p_bit);
+    let palette = |e0: [u32; 4], e1: [u32; 4]| -> [[u32; 4]; 16] {
+        BC7_WEIGHTS.map(|w| std::array::from_fn(|c| ((64 - w) * e0[c] + w * e1[c] + 32) >> 6))
+    };
+    let mut indices =
+        block.map(|pixel| closest::<4>(&palette(expand(endpoint0), expand(endpoint1)), pixel));
+    // The highest bit of the first index is implied to be zero.
+    if indices[0] >= 8 {
+        std::mem::swap(&mut endpoint0, &mut endpoint1);
+        indices = indices.map(|index| 15 - index);
+    }
+
+    let mut bits = 0u128;
+    let mut position = 0;
+    let mut push = |value: u32, count: u32| {
+        bits |= (value as u128) << position;
+        position += count;
+    };
+    push(1 << 6, 7);
+    for c in 0..4 {
+        push(endpoint0.0[c], 7);
+        push(endpoint1.0[c], 7);
+    }
+    push(endpoint0.1, 1);
+    push(endpoint1.1, 1);
+    for (i, &index) in indices.iter().enumerate() {
+        push(index, if i == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode a BC1 block to RGBA pixels.
+    fn decode_bc1(block: &[u8]) -> [[u8; 4]; 16] {
+        let color0 = u16::from_le_bytes([block[0], block[1]]);
+        let color1 = u16::from_le_bytes([block[2], block[3]]);
+        let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+        let from_565 = |color: u16| -> [u32; 4] {
+            let (r, g, b) = (
+                (color >> 11) as u32,
+                (color >> 5 & 63) as u32,
+                (color & 31) as u32,
+            );
+            [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]
+        };
+        let [c0, c1] = [from_565(color0), from_565(color1)];
+        let palette: [[u32; 4]; 4] = if color0 > color1 {
+            [
+                c0,
+                c1,
+                std::array::from_fn(|c| (2 * c0[c] + c1[c]) / 3),
+                std::array::from_fn(|c| (c0[c] + 2 * c1[c]) / 3),
+            ]
+        } else {
+            [
+                c0,
+                c1,
+                std::array::from_fn(|c| (c0[c] + c1[c]) / 2),
+                [0, 0, 0, 255],
+            ]
+        };
+        std::array::from_fn(|i| palette[(indices >> (2 * i) & 3) as usize].map(|v| v as u8))
+    }
+
+    /// Decode a BC7 block in mode 6 to RGBA pixels.
+    fn decode_bc7(block: &[u8]) -> [[u8; 4]; 16] {
+        let bits = u128::from_le_bytes(block.try_into().unwrap());
+        assert_eq!(bits & 0x7f, 1 << 6, "not a mode 6 block");
+        let mut position = 7;
+        let mut take = |count: u32| {
+            let value = (bits >> position) as u32 & ((1 << count) - 1);
+            position += count;
+            value
+        };
+        let channels: [[u32; 2]; 4] = std::array::from_fn(|_| [take(7), take(7)]);
+        let p_bits = [take(1), take(1)];
+        let [e0, e1] = [0, 1].map(|e| channels.map(|channel| channel[e] << 1 | p_bits[e]));
+        std::array::from_fn(|i| {
+            let w = BC7_WEIGHTS[take(if i == 0 { 3 } else { 4 }) as usize];
+            std::array::from_fn(|c| (((64 - w) * e0[c] + w * e1[c] + 32) >> 6) as u8)
+        })
+    }
+
+    /// Decode a BC4 block to single channel values.
+    fn decode_bc4(block: &[u8]) -> [u8; 16] {
+        let (red0, red1) = (block[0] as f32, block[1] as f32);
+        let mut index_bytes = [0; 8];
+        index_bytes[..6].copy_from_slice(&block[2..8]);
+        let indices = u64::from_le_bytes(index_bytes);
+        let palette: [f32; 8] = std::array::from_fn(|i| match i {
+            0 => red0,
+            1 => red1,
+            _ if red0 > red1 => ((8 - i) as f32 * red0 + (i - 1) as f32 * red1) / 7.0,
+            6 => 0.0,
+            7 => 255.0,
+            _ => ((6 - i) as f32 * red0 + (i - 1) as f32 * red1) / 5.0,
+        });
+        std::array::from_fn(|i| palette[(indices >> (3 * i) & 7) as usize].round() as u8)
+    }
+
+    fn max_error(decoded: &[[u8; 4]; 16], block: &[[u8; 4]; 16]) -> u8 {
+        decoded
+            .iter()
+            .flatten()
+            .zip(block.iter().flatten())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap()
+    }
+
+    const COLORS: [[u8; 4]; 5] = [
+        [0, 0, 0, 255],
+        [255, 255, 255, 255],
+        [200, 100, 50, 255],
+        [13, 77, 191, 255],
+        [128, 128, 128, 255],
+    ];
+
+    #[test]
+    fn bc1_encodes_solid_blocks() {
+        for color in COLORS {
+            let block = [color; 16];
+            let encoded = encode_bc1(&block);
+            // 5 bits of red and blue, 6 bits of green.
+            assert!(max_error(&decode_bc1(&encoded), &block) <= 4, "{color:?}");
+        }
+    }
+
+    #[test]
+    fn bc1_encodes_two_colors_exactly() {
+        // Black and white, and red and green, whose channels vary in opposite directions.
+        for colors in [[COLORS[0], COLORS[1]], [[255, 0, 0, 255], [0, 255, 0, 255]]] {
+            let block = std::array::from_fn(|i| colors[i % 2]);
+            assert_eq!(decode_bc1(&encode_bc1(&block)), block);
+        }
+    }
+
+    #[test]
+    fn bc7_encodes_solid_blocks() {
+        for color in COLORS.into_iter().chain([[10, 20, 30, 40]]) {
+            let block = [color; 16];
+            assert!(
+                max_error(&decode_bc7(&encode_bc7(&block)), &block) <= 1,
+                "{color:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn bc7_encodes_gradients() {
+        let block: [[u8; 4]; 16] = std::array::from_fn(|i| {
+            let v = (i * 17) as u8;
+            [v, 255 - v, v / 2, 255 - v / 2]
+        });
+        assert!(max_error(&decode_bc7(&encode_bc7(&block)), &block) <= 10);
+
+        // The first pixel is at the end of the line, so the endpoints have to be swapped.
+        let reversed = std::array::from_fn(|i| block[15 - i]);
+        assert!(max_error(&decode_bc7(&encode_bc7(&reversed)), &reversed) <= 10);
+    }
+
+    #[test]
+    fn bc4_encodes_blocks() {
+        assert_eq!(decode_bc4(&encode_bc4(&[77; 16])), [77; 16]);
+
+        let block = std::array::from_fn(|i| (i * 17) as u8);
+        let decoded = decode_bc4(&encode_bc4(&block));
+        for (a, b) in decoded.iter().zip(&block) {
+            // Seven steps between 0 and 255.
+            assert!(a.abs_diff(*b) <= 19, "{decoded:?}");
+        }
+    }
+
+    #[test]
+    fn compresses_blocks_row_by_row() {
+        // An 8x8 image with a solid color in each quadrant.
+        let data: Vec<u8> = (0..64)
+            .flat_map(|i| COLORS[(i / 8 / 4) * 2 + i % 8 / 4])
+            .collect();
+        let compressed = compress_bc1(&data, 8);
+        assert_eq!(compressed.len(), 4 * 8);
+        for (block, &color) in compressed.chunks_exact(8).zip(&COLORS) {
+            assert!(max_error(&decode_bc1(block), &[color; 16]) <= 4);
+        }
+        assert_eq!(compress_bc7(&data, 8).len(), 4 * 16);
+
+        let single: Vec<u8> = (0..64).map(|i| (i / 8 / 4 * 2 + i % 8 / 4) as u8).collect();
+        let compressed = compress_bc4(&single, 8);
+        for (i, block) in compressed.chunks_exact(8).enumerate() {
+            assert_eq!(decode_bc4(block), [i as u8; 16]);
+        }
+    }
+}
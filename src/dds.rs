@@ -0,0 +1,404 @@
+//! Loading textures from DDS files.
+
+use crate::load::{check_mip_levels, level_size, unsupported, TextureData, TextureLoadError};
+use crate::{Renderer, Texture};
+use std::borrow::Cow;
+use wgpu::*;
+
+const MAGIC: &[u8; 4] = b"DDS ";
+const HEADER_SIZE: usize = 128;
+const DX10_HEADER_SIZE: usize = 20;
+
+const PIXEL_FORMAT_ALPHA_PIXELS: u32 = 0x1;
+const PIXEL_FORMAT_FOURCC: u32 = 0x4;
+const PIXEL_FORMAT_RGB: u32 = 0x40;
+const PIXEL_FORMAT_LUMINANCE: u32 = 0x20000;
+
+const CAPS2_CUBEMAP: u32 = 0x200;
+const CAPS2_VOLUME: u32 = 0x200000;
+
+const DX10_DIMENSION_TEXTURE3D: u32 = 4;
+const DX10_MISC_TEXTURECUBE: u32 = 0x4;
+
+impl Texture {
+    /// Create a texture from the contents of a DDS file and upload all its mip levels and
+    /// array layers.
+    ///
+    /// Both files with a DX10 header and legacy files are supported. Cube maps, arrays
+    /// and volumes are created with the matching [`TextureKind`](crate::TextureKind).
+    /// Compressed formats require the matching `Features::TEXTURE_COMPRESSION_*`.
+    pub fn from_dds(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        bytes: &[u8],
+    ) -> Result<Texture, TextureLoadError> {
+        parse(bytes)?.upload(device, queue, renderer, Some("imgui-wgpu dds texture"))
+    }
+}
+
+/// Parse the headers of a DDS file and gather the layers of each mip level.
+fn parse(bytes: &[u8]) -> Result<TextureData<'static>, TextureLoadError> {
+    if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+        return Err(TextureLoadError::Invalid("missing DDS magic"));
+    }
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let height = u32_at(12);
+    let width = u32_at(16);
+    let depth = u32_at(24).max(1);
+    let level_count = u32_at(28).max(1);
+    let pixel_format_flags = u32_at(80);
+    let four_cc = &bytes[84..88];
+    let caps2 = u32_at(112);
+
+    let (format, dimension, cube, layers, data_offset) =
+        if pixel_format_flags & PIXEL_FORMAT_FOURCC != 0 && four_cc == b"DX10" {
+            if bytes.len() < HEADER_SIZE + DX10_HEADER_SIZE {
+                return Err(TextureLoadError::Invalid("DX10 header is truncated"));
+            }
+            let dxgi_format = u32_at(128);
+            let format = dxgi_format_to_wgpu(dxgi_format)
+                .ok_or_else(|| unsupported(format!("DXGI format {dxgi_format}")))?;
+            let dimension = if u32_at(132) == DX10_DIMENSION_TEXTURE3D {
+                TextureDimension::D3
+            } else {
+                TextureDimension::D2
+            };
+            let cube = u32_at(136) & DX10_MISC_TEXTURECUBE != 0;
+            let faces = if cube { 6 } else { 1 };
+            let layers = u32_at(140)
+                .max(1)
+                .checked_mul(faces)
+                .ok_or(TextureLoadError::Invalid("too many array layers"))?;
+            (
+                format,
+                dimension,
+                cube,
+                layers,
+                HEADER_SIZE + DX10_HEADER_SIZE,
+            )
+        } else {
+            let format = legacy_format_to_wgpu(pixel_format_flags, four_cc, u32_at)
+                .ok_or_else(|| unsupported("legacy DDS pixel format"))?;
+            let (dimension, cube, layers) = if caps2 & CAPS2_VOLUME != 0 {
+                (TextureDimension::D3, false, 1)
+            } else if caps2 & CAPS2_CUBEMAP != 0 {
+                // Partial cube maps are not supported, all six faces must be present.
+                (TextureDimension::D2, true, 6)
+            } else {
+                (TextureDimension::D2, false, 1)
+            };
+            (format, dimension, cube, layers, HEADER_SIZE)
+        };
+    if width == 0 || height == 0 {
+        return Err(TextureLoadError::Invalid("size is zero"));
+    }
+    if dimension == TextureDimension::D3 && layers > 1 {
+        return Err(unsupported("3D texture array"));
+    }
+
+    let (size, slice_depth) = match dimension {
+        TextureDimension::D3 => (
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: depth,
+            },
+            depth,
+        ),
+        _ => (
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: layers,
+            },
+            1,
+        ),
+    };
+
+    check_mip_levels(size, dimension, level_count as usize)?;
+
+    // DDS stores the whole mip chain of each layer one after another, while the levels
+    // are uploaded with all their layers at once.
+    let single = Extent3d {
+        depth_or_array_layers: slice_depth,
+        ..size
+    };
+    let level_sizes = (0..level_count)
+        .map(|level| level_size(format, single, dimension, level))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Check the data is present before reserving memory for the sizes in the header.
+    let data_size = level_sizes
+        .iter()
+        .try_fold(0usize, |total, size| total.checked_add(*size))
+        .and_then(|size| size.checked_mul(layers as usize));
+    if data_size.is_none_or(|size| size > bytes.len() - data_offset) {
+        return Err(TextureLoadError::Invalid("image data is truncated"));
+    }
+    let mut levels: Vec<Vec<u8>> = level_sizes
+        .iter()
+        .map(|size| Vec::with_capacity(size * layers as usize))
+        .collect();
+    let mut offset = data_offset;
+    for _ in 0..layers {
+        for (level, size) in levels.iter_mut().zip(&level_sizes) {
+            let data = bytes
+                .get(offset..offset + size)
+                .ok_or(TextureLoadError::Invalid("image data is truncated"))?;
+            level.extend_from_slice(data);
+            offset += size;
+        }
+    }
+
+    Ok(TextureData {
+        format,
+        size,
+        dimension,
+        cube,
+        levels: levels.into_iter().map(Cow::Owned).collect(),
+    })
+}
+
+/// The wgpu format matching a `DXGI_FORMAT`, if there is one.
+fn dxgi_format_to_wgpu(dxgi_format: u32) -> Option<TextureFormat> {
+    use TextureFormat::*;
+
+    Some(match dxgi_format {
+        2 => Rgba32Float,
+        3 => Rgba32Uint,
+        4 => Rgba32Sint,
+        10 => Rgba16Float,
+        11 => Rgba16Unorm,
+        12 => Rgba16Uint,
+        13 => Rgba16Snorm,
+        14 => Rgba16Sint,
+        16 => Rg32Float,
+        17 => Rg32Uint,
+        18 => Rg32Sint,
+        24 => Rgb10a2Unorm,
+        25 => Rgb10a2Uint,
+        26 => Rg11b10Ufloat,
+        28 => Rgba8Unorm,
+        29 => Rgba8UnormSrgb,
+        30 => Rgba8Uint,
+        31 => Rgba8Snorm,
+        32 => Rgba8Sint,
+        34 => Rg16Float,
+        35 => Rg16Unorm,
+        36 => Rg16Uint,
+        37 => Rg16Snorm,
+        38 => Rg16Sint,
+        41 => R32Float,
+        42 => R32Uint,
+        43 => R32Sint,
+        49 => Rg8Unorm,
+        50 => Rg8Uint,
+        51 => Rg8Snorm,
+        52 => Rg8Sint,
+        54 => R16Float,
+        56 => R16Unorm,
+        57 => R16Uint,
+        58 => R16Snorm,
+        59 => R16Sint,
+        61 => R8Unorm,
+        62 => R8Uint,
+        63 => R8Snorm,
+        64 => R8Sint,
+        67 => Rgb9e5Ufloat,
+        71 => Bc1RgbaUnorm,
+        72 => Bc1RgbaUnormSrgb,
+        74 => Bc2RgbaUnorm,
+        75 => Bc2RgbaUnormSrgb,
+        77 => Bc3RgbaUnorm,
+        78 => Bc3RgbaUnormSrgb,
+        80 => Bc4RUnorm,
+        81 => Bc4RSnorm,
+        83 => Bc5RgUnorm,
+        84 => Bc5RgSnorm,
+        87 => Bgra8Unorm,
+        91 => Bgra8UnormSrgb,
+        95 => Bc6hRgbUfloat,
+        96 => Bc6hRgbFloat,
+        98 => Bc7RgbaUnorm,
+        99 => Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+/// The wgpu format matching a legacy DDS pixel format, if there is one. `u32_at` reads
+/// the header, for the bit count and masks of uncompressed formats.
+fn legacy_format_to_wgpu(
+    flags: u32,
+    four_cc: &[u8],
+    u32_at: impl Fn(usize) -> u32,
+) -> Option<TextureFormat> {
+    use TextureFormat::*;
+
+    if flags & PIXEL_FORMAT_FOURCC != 0 {
+        return Some(match four_cc {
+            b"DXT1" => Bc1RgbaUnorm,
+            b"DXT2" | b"DXT3" => Bc2RgbaUnorm,
+            b"DXT4" | b"DXT5" => Bc3RgbaUnorm,
+            b"ATI1" | b"BC4U" => Bc4RUnorm,
+            b"BC4S" => Bc4RSnorm,
+            b"ATI2" | b"BC5U" => Bc5RgUnorm,
+            b"BC5S" => Bc5RgSnorm,
+            // D3DFMT values stored as a number instead of a four character code.
+            _ => match u32::from_le_bytes(four_cc.try_into().ok()?) {
+                36 => Rgba16Unorm,
+                111 => R16Float,
+                112 => Rg16Float,
+                113 => Rgba16Float,
+                114 => R32Float,
+                115 => Rg32Float,
+                116 => Rgba32Float,
+                _ => return None,
+            },
+        });
+    }
+
+    let bit_count = u32_at(88);
+    let masks = [u32_at(92), u32_at(96), u32_at(100), u32_at(104)];
+    // Formats without alpha are not supported, as their unused byte isn't necessarily opaque.
+    if flags & PIXEL_FORMAT_RGB != 0 && flags & PIXEL_FORMAT_ALPHA_PIXELS != 0 && bit_count == 32 {
+        match masks {
+            [0xff, 0xff00, 0xff_0000, 0xff00_0000] => Some(Rgba8Unorm),
+            [0xff_0000, 0xff00, 0xff, 0xff00_0000] => Some(Bgra8Unorm),
+            _ => None,
+        }
+    } else if flags & PIXEL_FORMAT_LUMINANCE != 0 && bit_count == 8 {
+        Some(R8Unorm)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The headers of a DDS file with the pixel format `flags` and `four_cc`.
+    fn header(width: u32, height: u32, level_count: u32, flags: u32, four_cc: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; HEADER_SIZE];
+        let mut put = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        put(4, 124);
+        put(12, height);
+        put(16, width);
+        put(28, level_count);
+        put(76, 32);
+        put(80, flags);
+        bytes[..4].copy_from_slice(MAGIC);
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    /// A legacy RGBA8 file with `data_size` bytes of image data.
+    fn rgba8(width: u32, height: u32, level_count: u32, data_size: usize) -> Vec<u8> {
+        let mut bytes = header(
+            width,
+            height,
+            level_count,
+            PIXEL_FORMAT_RGB | PIXEL_FORMAT_ALPHA_PIXELS,
+            &[0; 4],
+        );
+        bytes[88..92].copy_from_slice(&32u32.to_le_bytes());
+        for (i, mask) in [0xff, 0xff00, 0xff_0000, 0xff00_0000u32].iter().enumerate() {
+            bytes[92 + 4 * i..96 + 4 * i].copy_from_slice(&mask.to_le_bytes());
+        }
+        bytes.extend((0..data_size).map(|i| i as u8));
+        bytes
+    }
+
+    /// A file with a DX10 header for `array_size` RGBA8 layers.
+    fn dx10(width: u32, height: u32, misc: u32, array_size: u32, data_size: usize) -> Vec<u8> {
+        let mut bytes = header(width, height, 1, PIXEL_FORMAT_FOURCC, b"DX10");
+        // DXGI_FORMAT_R8G8B8A8_UNORM in a 2D texture.
+        for value in [28, 3, misc, array_size, 0u32] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend((0..data_size).map(|i| i as u8));
+        bytes
+    }
+
+    fn size(width: u32, height: u32, depth_or_array_layers: u32) -> Extent3d {
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers,
+        }
+    }
+
+    #[test]
+    fn parses_mip_levels() {
+        let data = parse(&rgba8(4, 2, 3, 32 + 8 + 4)).unwrap();
+        assert_eq!(data.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(data.size, size(4, 2, 1));
+        assert!(!data.cube);
+        let levels: Vec<_> = data.levels.iter().map(|level| level.len()).collect();
+        assert_eq!(levels, [32, 8, 4]);
+        assert_eq!(data.levels[2][..], [40, 41, 42, 43]);
+    }
+
+    #[test]
+    fn gathers_the_layers_of_each_level() {
+        let mut bytes = header(8, 8, 2, PIXEL_FORMAT_FOURCC, b"DXT1");
+        bytes[112..116].copy_from_slice(&CAPS2_CUBEMAP.to_le_bytes());
+        // Each face has a 32 byte level of 2x2 blocks and an 8 byte level of one block.
+        bytes.extend((0..6).flat_map(|face| [[face; 32].as_slice(), &[face + 10; 8]].concat()));
+        let data = parse(&bytes).unwrap();
+        assert_eq!(data.format, TextureFormat::Bc1RgbaUnorm);
+        assert_eq!(data.size, size(8, 8, 6));
+        assert!(data.cube);
+        assert_eq!(data.levels[0].len(), 6 * 32);
+        assert_eq!(data.levels[1].len(), 6 * 8);
+        assert_eq!(data.levels[0][32..64], [1; 32]);
+        assert_eq!(data.levels[1][8..16], [11; 8]);
+    }
+
+    #[test]
+    fn parses_dx10_arrays() {
+        let data = parse(&dx10(2, 2, 0, 3, 3 * 16)).unwrap();
+        assert_eq!(data.size, size(2, 2, 3));
+        assert_eq!(data.levels[0].len(), 3 * 16);
+
+        let data = parse(&dx10(2, 2, DX10_MISC_TEXTURECUBE, 1, 6 * 16)).unwrap();
+        assert_eq!(data.size, size(2, 2, 6));
+        assert!(data.cube);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let invalid = |bytes: &[u8]| matches!(parse(bytes), Err(TextureLoadError::Invalid(_)));
+        assert!(invalid(b"DDS "));
+        assert!(invalid(&rgba8(4, 4, 1, 64)[1..]));
+        assert!(invalid(&rgba8(0, 4, 1, 0)));
+        // Truncated image data.
+        assert!(invalid(&rgba8(4, 4, 2, 64 + 15)));
+        assert!(invalid(&dx10(2, 2, 0, 2, 16)));
+        assert!(invalid(&dx10(2, 2, 0, 1, 16)[..HEADER_SIZE + 8]));
+    }
+
+    #[test]
+    fn rejects_sizes_from_the_header_that_overflow() {
+        let invalid = |bytes: &[u8]| matches!(parse(bytes), Err(TextureLoadError::Invalid(_)));
+        // The mip levels of a 4x4 texture end at level 3.
+        assert!(invalid(&rgba8(4, 4, 4, 1 << 10)));
+        assert!(invalid(&rgba8(4, 4, u32::MAX, 1 << 10)));
+        // Too large to reserve memory for, with little data.
+        assert!(invalid(&rgba8(u32::MAX, u32::MAX, 1, 64)));
+        assert!(invalid(&rgba8(1 << 16, 1 << 16, 17, 64)));
+        assert!(invalid(&dx10(2, 2, DX10_MISC_TEXTURECUBE, u32::MAX, 64)));
+        assert!(invalid(&dx10(1 << 15, 1 << 15, 0, u32::MAX, 64)));
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        let bytes = header(4, 4, 1, PIXEL_FORMAT_FOURCC, b"ETC2");
+        assert!(matches!(
+            parse(&bytes),
+            Err(TextureLoadError::Unsupported(_))
+        ));
+    }
+}
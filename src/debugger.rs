@@ -0,0 +1,79 @@
+//! A window listing the textures of the renderer.
+
+use crate::Renderer;
+use imgui::{Image, TableColumnSetup, TableFlags, Ui};
+
+/// The largest side of the texture previews in pixels.
+const THUMBNAIL_SIZE: f32 = 64.0;
+
+impl Renderer {
+    /// Draw a window listing every texture of [`Self::textures`] with its label, size,
+    /// format, memory and a preview, e.g. to hunt down textures that are never removed.
+    ///
+    /// Call it every frame while the window should be shown, before rendering the frame.
+    pub fn show_texture_debugger(&self, ui: &Ui) {
+        ui.window("Textures").build(|| {
+            let total: u64 = self
+                .textures_iter()
+                .map(|(_, texture)| texture.memory_size())
+                .sum();
+            ui.text(format!(
+                "{} textures, {}",
+                self.textures_iter().count(),
+                format_bytes(total)
+            ));
+
+            let columns =
+                ["Preview", "Id", "Label", "Size", "Format", "Memory"].map(TableColumnSetup::new);
+            let flags = TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::RESIZABLE;
+            let Some(_table) = ui.begin_table_header_with_flags("textures", columns, flags) else {
+                return;
+            };
+            for (id, texture) in self.textures_iter() {
+                let size = texture.size();
+                let scale = THUMBNAIL_SIZE / size.width.max(size.height).max(1) as f32;
+                let [uv0, uv1] = self
+                    .region(id)
+                    .map_or([[0.0, 0.0], [1.0, 1.0]], |region| [region.uv0, region.uv1]);
+
+                ui.table_next_column();
+                Image::new(id, [size.width as f32 * scale, size.height as f32 * scale])
+                    .uv0(uv0)
+                    .uv1(uv1)
+                    .build(ui);
+                ui.table_next_column();
+                ui.text(format!("{}", id.id()));
+                ui.table_next_column();
+                ui.text(texture.label().unwrap_or("-"));
+                ui.table_next_column();
+                ui.text(format!(
+                    "{}x{}x{}",
+                    size.width, size.height, size.depth_or_array_layers
+                ));
+                ui.table_next_column();
+                match &texture.texture {
+                    Some(texture) => ui.text(format!("{:?}", texture.format())),
+                    None => ui.text("-"),
+                }
+                ui.table_next_column();
+                ui.text(format_bytes(texture.memory_size()));
+            }
+        });
+    }
+}
+
+/// Format a number of bytes with a binary unit.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
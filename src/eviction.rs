@@ -0,0 +1,98 @@
+//! Removing textures that weren't drawn for a while.
+
+use crate::Renderer;
+use imgui::{DrawCmd, DrawData, TextureId};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// The state of [`Renderer::set_texture_eviction`].
+#[derive(Default)]
+pub(crate) struct TextureEviction {
+    /// Textures unused for more frames are removed, `None` disables eviction.
+    max_unused_frames: Option<u64>,
+    frame: u64,
+    /// The last frame each texture was drawn, or seen first if it wasn't drawn yet.
+    last_used: HashMap<TextureId, u64>,
+    /// The textures drawn by the draw data prepared since the last frame started.
+    drawn: Mutex<HashSet<TextureId>>,
+    pinned: HashSet<TextureId>,
+}
+
+impl Renderer {
+    /// Remove textures from [`Self::textures`] that weren't drawn by the draw data passed
+    /// to [`Self::prepare`] for `frames` frames, as counted by [`Self::begin_frame`], e.g. thumbnails of a long running tool that are no longer shown.
+    /// Pass `None` to disable eviction, which is the default.
+    ///
+    /// The font atlas, textures pinned with [`Self::pin_texture`] and the textures of
+    /// [`TextureAtlas`](crate::TextureAtlas)es, [`FrameGrabber`](crate::FrameGrabber)s and
+    /// [`Self::insert_region`] are never removed.
+    pub fn set_texture_eviction(&mut self, frames: Option<u32>) {
+        self.eviction.max_unused_frames = frames.map(u64::from);
+        if frames.is_none() {
+            self.eviction.last_used.clear();
+            self.eviction.drawn.get_mut().unwrap().clear();
+        }
+    }
+
+    /// Keep the texture `texture_id` even if it wasn't drawn for a while, see
+    /// [`Self::set_texture_eviction`].
+    pub fn pin_texture(&mut self, texture_id: TextureId) {
+        self.eviction.pinned.insert(texture_id);
+    }
+
+    /// Allow evicting a texture pinned with [`Self::pin_texture`] again.
+    pub fn unpin_texture(&mut self, texture_id: TextureId) {
+        self.eviction.pinned.remove(&texture_id);
+    }
+
+    /// Record the textures drawn by `draw_data` for the current frame.
+    pub(crate) fn record_drawn_textures(&self, draw_data: &DrawData) {
+        if self.eviction.max_unused_frames.is_none() {
+            return;
+        }
+        let mut drawn = self.eviction.drawn.lock().unwrap();
+        for draw_list in draw_data.draw_lists() {
+            for cmd in draw_list.commands() {
+                if let DrawCmd::Elements { cmd_params, .. } = cmd {
+                    drawn.insert(cmd_params.texture_id);
+                }
+            }
+        }
+    }
+
+    /// Finish the frame counted for eviction and remove the textures unused for too long.
+    pub(crate) fn evict_unused_textures(&mut self) {
+        let eviction = &mut self.eviction;
+        let Some(max_unused_frames) = eviction.max_unused_frames else {
+            return;
+        };
+        eviction.frame += 1;
+        let frame = eviction.frame;
+
+        for id in eviction.drawn.get_mut().unwrap().drain() {
+            eviction.last_used.insert(id, frame);
+        }
+
+        let font_textures = &self.font_textures;
+        let evicted: Vec<_> = self
+            .textures
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|&id| {
+                let last_used = *eviction.last_used.entry(id).or_insert(frame);
+                frame - last_used > max_unused_frames
+                    && !font_textures.values().any(|&font| font == id)
+                    && !eviction.pinned.contains(&id)
+            })
+            .collect();
+        for id in evicted {
+            self.textures.remove(id);
+            self.texture_pipelines.remove(&id);
+        }
+
+        let textures = &self.textures;
+        eviction
+            .last_used
+            .retain(|&id, _| textures.get(id).is_some());
+    }
+}
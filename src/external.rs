@@ -0,0 +1,43 @@
+//! Importing textures from memory shared with other processes or APIs.
+
+use crate::{RawTextureConfig, Renderer, Texture};
+use std::sync::Arc;
+use wgpu::core::hal_api::HalApi;
+use wgpu::*;
+
+impl Texture {
+    /// Create a texture from a wgpu-hal texture of the backend `A`, e.g. to display frames
+    /// decoded by FFmpeg or rendered with CUDA or by another process.
+    ///
+    /// Importing a platform handle is backend specific: create the native image from a
+    /// DXGI shared handle, a Vulkan external memory fd or DMA-BUF, or an IOSurface, and
+    /// wrap it with `texture_from_raw` of the device of `wgpu::hal::dx12`,
+    /// `wgpu::hal::vulkan` or `wgpu::hal::metal`. The view and bind group are created
+    /// like in [`Self::from_raw_parts`].
+    ///
+    /// # Safety
+    ///
+    /// - `hal_texture` must be created from the hal device of `device`.
+    /// - `hal_texture` must be created respecting `desc` and be initialized.
+    /// - Access to the shared memory must be synchronized with its producer by the
+    ///   application, wgpu doesn't know about other users of it.
+    pub unsafe fn from_hal<A: HalApi>(
+        device: &Device,
+        renderer: &Renderer,
+        hal_texture: A::Texture,
+        desc: &TextureDescriptor,
+        config: &RawTextureConfig,
+    ) -> Self {
+        let texture = unsafe { device.create_texture_from_hal::<A>(hal_texture, desc) };
+        let view = texture.create_view(&config.kind.view_desc(None));
+        Self::from_raw_parts(
+            device,
+            renderer,
+            Arc::new(texture),
+            Arc::new(view),
+            None,
+            Some(config),
+            desc.size,
+        )
+    }
+}
@@ -0,0 +1,107 @@
+//! Copying rendered frames into textures of the renderer, e.g. for previews.
+
+use crate::{mipmap, Renderer, Texture, TextureConfig};
+use imgui::TextureId;
+use wgpu::*;
+
+/// Copies a view, e.g. the frame that is about to be presented, into a texture of the
+/// renderer every frame, scaled to the size of the texture.
+///
+/// This allows tools to show a preview of what was just rendered or a picture-in-picture
+/// view of another pass. The source has to be a filterable 2D view, so a surface texture
+/// needs `TextureUsages::TEXTURE_BINDING` in its configuration.
+pub struct FrameGrabber {
+    pipeline: RenderPipeline,
+    sampler: Sampler,
+    format: TextureFormat,
+    texture_id: Option<TextureId>,
+}
+
+impl FrameGrabber {
+    /// Create a grabber copying into a texture of `format`, which has to be renderable.
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        Self {
+            pipeline: mipmap::blit_pipeline(device, format),
+            sampler: mipmap::blit_sampler(device),
+            format,
+            texture_id: None,
+        }
+    }
+
+    /// Record a copy of `source` into the texture into `encoder` and return its id,
+    /// creating the texture on first use and whenever `size` changes.
+    ///
+    /// Record it after everything that renders to `source` and outside of passes using
+    /// it, e.g. before the imgui pass for a view of the scene rendered to the surface.
+    pub fn grab(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        renderer: &mut Renderer,
+        source: &TextureView,
+        size: [u32; 2],
+    ) -> TextureId {
+        let size = Extent3d {
+            width: size[0].max(1),
+            height: size[1].max(1),
+            depth_or_array_layers: 1,
+        };
+        let current = self.texture_id.filter(|&id| {
+            renderer
+                .textures
+                .get(id)
+                .is_some_and(|texture| texture.size() == size)
+        });
+        let texture_id = match current {
+            Some(id) => id,
+            None => {
+                let config = TextureConfig {
+                    size,
+                    label: Some("imgui-wgpu frame grab"),
+                    format: Some(self.format),
+                    usage: TextureUsages::TEXTURE_BINDING
+                        | TextureUsages::RENDER_ATTACHMENT
+                        | TextureUsages::COPY_SRC,
+                    ..Default::default()
+                };
+                let texture = Texture::new(device, renderer, config);
+                match self.texture_id {
+                    Some(id) if renderer.textures.get(id).is_some() => {
+                        renderer.textures.replace(id, texture);
+                        id
+                    }
+                    _ => {
+                        let id = renderer.textures.insert(texture);
+                        renderer.pin_texture(id);
+                        id
+                    }
+                }
+            }
+        };
+        self.texture_id = Some(texture_id);
+
+        let target = renderer.textures.get(texture_id).unwrap().view();
+        mipmap::blit_view(
+            device,
+            encoder,
+            &self.pipeline,
+            &self.sampler,
+            source,
+            target,
+        );
+        texture_id
+    }
+
+    /// The texture of the last grabbed frame, or `None` until the first one.
+    pub fn texture_id(&self) -> Option<TextureId> {
+        self.texture_id
+    }
+
+    /// Remove the texture from the renderer.
+    pub fn remove(self, renderer: &mut Renderer) {
+        if let Some(id) = self.texture_id {
+            renderer.textures.remove(id);
+            renderer.unpin_texture(id);
+        }
+    }
+}
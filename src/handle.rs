@@ -0,0 +1,52 @@
+//! Textures that are removed from the renderer when their handle is dropped.
+
+use crate::{Renderer, Texture};
+use imgui::TextureId;
+use std::sync::{Arc, Mutex};
+
+/// The ids of dropped [`TextureHandle`]s, removed by the renderer later.
+pub(crate) type DroppedTextures = Arc<Mutex<Vec<TextureId>>>;
+
+/// Owns a texture inserted with [`Renderer::register`], which is removed from
+/// [`Renderer::textures`] when the handle is dropped.
+///
+/// The renderer can't be borrowed on drop, so the texture is removed after the next
+/// [`Renderer::render`] or call of [`Renderer::register`].
+pub struct TextureHandle {
+    id: TextureId,
+    dropped: DroppedTextures,
+}
+
+impl TextureHandle {
+    /// The id to draw the texture with.
+    pub fn id(&self) -> TextureId {
+        self.id
+    }
+}
+
+impl Drop for TextureHandle {
+    fn drop(&mut self) {
+        self.dropped.lock().unwrap().push(self.id);
+    }
+}
+
+impl Renderer {
+    /// Insert a texture into [`Self::textures`] that is removed again when the returned
+    /// handle is dropped, e.g. for previews that are replaced regularly.
+    pub fn register(&mut self, texture: Texture) -> TextureHandle {
+        self.remove_dropped_textures();
+        TextureHandle {
+            id: self.textures.insert(texture),
+            dropped: self.dropped_textures.clone(),
+        }
+    }
+
+    /// Remove the textures of all dropped [`TextureHandle`]s.
+    pub(crate) fn remove_dropped_textures(&mut self) {
+        let dropped = std::mem::take(&mut *self.dropped_textures.lock().unwrap());
+        for id in dropped {
+            self.textures.remove(id);
+            self.texture_pipelines.remove(&id);
+        }
+    }
+}
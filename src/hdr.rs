@@ -0,0 +1,39 @@
+//! Loading HDR images into half float textures.
+
+use crate::{Renderer, Texture, TextureConfig};
+use image::ImageResult;
+use wgpu::*;
+
+impl Texture {
+    /// Decode a Radiance HDR or OpenEXR file and upload it to an `Rgba16Float` texture.
+    ///
+    /// The colors are not clamped, display the texture with
+    /// [`TextureStyle::tonemap`](crate::TextureStyle::tonemap) set and adjust the
+    /// [`TextureStyle::exposure`](crate::TextureStyle::exposure) to inspect them.
+    pub fn from_hdr(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        bytes: &[u8],
+    ) -> ImageResult<Texture> {
+        let image = image::load_from_memory(bytes)?;
+        let (width, height) = (image.width(), image.height());
+
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                label: Some("imgui-wgpu hdr texture"),
+                format: Some(TextureFormat::Rgba16Float),
+                ..Default::default()
+            },
+        );
+        texture.write_f32_as_f16(queue, image.to_rgba32f().as_raw(), width, height);
+        Ok(texture)
+    }
+}
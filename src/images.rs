@@ -0,0 +1,106 @@
+//! Creating textures from images of the `image` crate.
+
+use crate::{Renderer, Texture, TextureConfig, TextureKind};
+use image::DynamicImage;
+#[cfg(feature = "decode")]
+use image::ImageResult;
+use std::borrow::Cow;
+use wgpu::*;
+
+impl Texture {
+    /// Create a texture from `image` and upload it.
+    ///
+    /// 8-bit images are converted to `Rgba8Unorm`, or to `Rgba8UnormSrgb` if that is the
+    /// format in `config`. Otherwise 16-bit images are kept as `Rgba16Unorm` if the device
+    /// supports `Features::TEXTURE_FORMAT_16BIT_NORM` and float images as `Rgba32Float`.
+    /// The size, format and kind in `config` are replaced to match the image.
+    pub fn from_image(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        image: &DynamicImage,
+        config: TextureConfig,
+    ) -> Texture {
+        let srgb = config.format == Some(TextureFormat::Rgba8UnormSrgb);
+        let keep_16bit = !srgb
+            && device
+                .features()
+                .contains(Features::TEXTURE_FORMAT_16BIT_NORM);
+        let (format, data): (_, Cow<[u8]>) = match image {
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_)
+                if keep_16bit =>
+            {
+                let rgba = image.to_rgba16();
+                let data = bytemuck::cast_slice(rgba.as_raw()).to_vec();
+                (TextureFormat::Rgba16Unorm, Cow::Owned(data))
+            }
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) if !srgb => {
+                let rgba = image.to_rgba32f();
+                let data = bytemuck::cast_slice(rgba.as_raw()).to_vec();
+                (TextureFormat::Rgba32Float, Cow::Owned(data))
+            }
+            DynamicImage::ImageRgba8(rgba) => {
+                (rgba8_format(srgb), Cow::Borrowed(&rgba.as_raw()[..]))
+            }
+            _ => (rgba8_format(srgb), Cow::Owned(image.to_rgba8().into_raw())),
+        };
+        let kind = if format == TextureFormat::Rgba32Float
+            && !device.features().contains(Features::FLOAT32_FILTERABLE)
+        {
+            TextureKind::NonFilterable
+        } else {
+            TextureKind::Filterable
+        };
+
+        let (width, height) = (image.width(), image.height());
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                format: Some(format),
+                kind,
+                ..config
+            },
+        );
+        texture.write(queue, &data, width, height);
+        texture
+    }
+
+    /// Decode an encoded image file, detecting its format from its contents, and upload
+    /// it like [`Self::from_image`]. Returns the texture and its width and height.
+    ///
+    /// PNG and JPEG are supported, other formats can be enabled with the features of the
+    /// `image` crate.
+    #[cfg(feature = "decode")]
+    pub fn from_encoded_bytes(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        bytes: &[u8],
+    ) -> ImageResult<(Texture, [u32; 2])> {
+        let image = image::load_from_memory(bytes)?;
+        let config = TextureConfig {
+            label: Some("imgui-wgpu image texture"),
+            ..Default::default()
+        };
+        let texture = Self::from_image(device, queue, renderer, &image, config);
+        Ok((texture, [image.width(), image.height()]))
+    }
+}
+
+/// The 8-bit RGBA format with or without sRGB decoding.
+fn rgba8_format(srgb: bool) -> TextureFormat {
+    if srgb {
+        TextureFormat::Rgba8UnormSrgb
+    } else {
+        TextureFormat::Rgba8Unorm
+    }
+}
@@ -0,0 +1,30 @@
+// All textures used by the frame, selected per draw command by the push constant.
+@group(1) @binding(0)
+var u_Textures: binding_array<texture_2d<f32>>;
+@group(1) @binding(1)
+var u_Samplers: binding_array<sampler>;
+
+struct PushConstants {
+    texture_index: u32,
+};
+
+var<push_constant> push_constants: PushConstants;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let index = push_constants.texture_index;
+    return textureSample(u_Textures[index], u_Samplers[index], uv);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color);
+
+    return FragmentOutput(color * sample_texture(in.v_UV));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color;
+
+    return FragmentOutput(color * sample_texture(in.v_UV));
+}
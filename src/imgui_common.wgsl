@@ -0,0 +1,38 @@
+struct VertexInput {
+    @location(0) a_Pos: vec2<f32>,
+    @location(1) a_UV: vec2<f32>,
+    @location(2) a_Color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @location(0) v_UV: vec2<f32>,
+    @location(1) v_Color: vec4<f32>,
+    @builtin(position) v_Position: vec4<f32>,
+};
+
+// Set by the renderer from `RendererConfig::linearize_vertex_colors`.
+override LINEARIZE_VERTEX_COLORS: bool = false;
+
+// Pass a vertex to the fragment stage, transformed to clip space by `matrix`.
+fn transform(in: VertexInput, matrix: mat4x4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.v_UV = in.a_UV;
+    out.v_Color = select(in.a_Color, srgb_to_linear(in.a_Color), LINEARIZE_VERTEX_COLORS);
+    out.v_Position = matrix * vec4<f32>(in.a_Pos.xy, 0.0, 1.0);
+    return out;
+}
+
+// The vertex color in linear space, for targets without an sRGB format.
+fn linear_vertex_color(color: vec4<f32>) -> vec4<f32> {
+    // Vertex colors have already been converted if LINEARIZE_VERTEX_COLORS is set.
+    return select(srgb_to_linear(color), color, LINEARIZE_VERTEX_COLORS);
+}
+
+fn srgb_to_linear(srgb: vec4<f32>) -> vec4<f32> {
+    let color_srgb = srgb.rgb;
+    let selector = ceil(color_srgb - 0.04045); // 0 if under value, 1 if over
+    let under = color_srgb / 12.92;
+    let over = pow((color_srgb + 0.055) / 1.055, vec3<f32>(2.4));
+    let result = mix(under, over, selector);
+    return vec4<f32>(result, srgb.a);
+}
@@ -0,0 +1,66 @@
+// Set by `Texture::set_cube_projection`.
+struct TextureParams {
+    projection: u32,
+};
+
+@group(1) @binding(0)
+var u_Texture: texture_cube<f32>;
+@group(1) @binding(1)
+var u_Sampler: sampler;
+@group(1) @binding(2)
+var<uniform> u_Params: TextureParams;
+
+const PI: f32 = 3.14159265358979;
+
+// The direction of the cube texel displayed at `uv`, `w` is 0 outside of the projection.
+fn cube_direction(uv: vec2<f32>) -> vec4<f32> {
+    // Equirectangular projection.
+    if u_Params.projection == 0u {
+        let lon = (uv.x * 2.0 - 1.0) * PI;
+        let lat = (0.5 - uv.y) * PI;
+        return vec4<f32>(cos(lat) * sin(lon), sin(lat), cos(lat) * cos(lon), 1.0);
+    }
+
+    // Horizontal cross of 4x3 faces around +Z, with +Y above and -Y below.
+    let grid = uv * vec2<f32>(4.0, 3.0);
+    let cell = vec2<i32>(floor(grid));
+    let s = fract(grid.x) * 2.0 - 1.0;
+    let t = fract(grid.y) * 2.0 - 1.0;
+    var direction = vec4<f32>(0.0, 0.0, 1.0, 1.0);
+    if cell.y == 0 && cell.x == 1 {
+        direction = vec4<f32>(s, 1.0, t, 1.0);
+    } else if cell.y == 2 && cell.x == 1 {
+        direction = vec4<f32>(s, -1.0, -t, 1.0);
+    } else if cell.y == 1 && cell.x == 0 {
+        direction = vec4<f32>(-1.0, -t, s, 1.0);
+    } else if cell.y == 1 && cell.x == 1 {
+        direction = vec4<f32>(s, -t, 1.0, 1.0);
+    } else if cell.y == 1 && cell.x == 2 {
+        direction = vec4<f32>(1.0, -t, -s, 1.0);
+    } else if cell.y == 1 && cell.x == 3 {
+        direction = vec4<f32>(-s, -t, -1.0, 1.0);
+    } else {
+        direction.w = 0.0;
+    }
+    return direction;
+}
+
+fn sample_cube(uv: vec2<f32>) -> vec4<f32> {
+    let direction = cube_direction(uv);
+    let texel = textureSample(u_Texture, u_Sampler, direction.xyz);
+    return vec4<f32>(texel.rgb, texel.a * direction.w);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    return FragmentOutput(shade(color, sample_cube(style_uv(in.v_UV))));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return FragmentOutput(shade(color, sample_cube(style_uv(in.v_UV))));
+}
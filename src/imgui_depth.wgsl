@@ -0,0 +1,42 @@
+// Set by `Texture::set_depth_range`.
+struct TextureParams {
+    near: f32,
+    far: f32,
+};
+
+@group(1) @binding(0)
+var u_Texture: texture_depth_2d;
+@group(1) @binding(1)
+var u_Sampler: sampler;
+@group(1) @binding(2)
+var<uniform> u_Params: TextureParams;
+
+// Display the depth as grayscale, linearized between the near and far plane of a
+// perspective projection if they are set.
+fn sample_depth(uv: vec2<f32>) -> vec4<f32> {
+    let depth = textureSample(u_Texture, u_Sampler, uv);
+    let near = u_Params.near;
+    let far = u_Params.far;
+    var value = depth;
+    if far != near {
+        // Reversed depth swaps near and far, the distance is the same either way.
+        let distance = near * far / (far - depth * (far - near));
+        let closest = min(near, far);
+        value = clamp((distance - closest) / abs(far - near), 0.0, 1.0);
+    }
+    return vec4<f32>(vec3<f32>(value), 1.0);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    return FragmentOutput(shade(color, sample_depth(style_uv(in.v_UV))));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return FragmentOutput(shade(color, sample_depth(style_uv(in.v_UV))));
+}
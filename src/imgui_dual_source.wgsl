@@ -0,0 +1,45 @@
+struct FragmentOutput {
+    // The color premultiplied with its coverage.
+    @location(0) o_Target: vec4<f32>,
+    // The per-channel coverage the destination is blended with.
+    @location(0) @second_blend_source o_Coverage: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var u_Texture: texture_2d<f32>;
+@group(1) @binding(1)
+var u_Sampler: sampler;
+
+fn dual_source_output(color: vec4<f32>) -> FragmentOutput {
+    return FragmentOutput(vec4<f32>(color.rgb * color.a, color.a), vec4<f32>(color.a));
+}
+
+// Sample the texture at `uv`, styled and multiplied by `color`. Subpixel text samples the
+// coverage of the red and blue subpixels of horizontal RGB panels a third of a pixel to the
+// left and right, and blends each channel with its own coverage.
+fn textured_output(uv: vec2<f32>, color: vec4<f32>) -> FragmentOutput {
+    let styled_uv = style_uv(uv);
+    let offset = vec2<f32>(dpdx(styled_uv.x) / 3.0, 0.0);
+    let center = shade(color, textureSample(u_Texture, u_Sampler, styled_uv));
+    let left = shade(color, textureSample(u_Texture, u_Sampler, styled_uv - offset)).a;
+    let right = shade(color, textureSample(u_Texture, u_Sampler, styled_uv + offset)).a;
+    if u_Style.subpixel > 0.0 {
+        let coverage = vec3<f32>(left, center.a, right);
+        return FragmentOutput(vec4<f32>(center.rgb * coverage, center.a), vec4<f32>(coverage, center.a));
+    }
+    return dual_source_output(center);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    return textured_output(in.v_UV, color);
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return textured_output(in.v_UV, color);
+}
@@ -0,0 +1,36 @@
+@group(1) @binding(0)
+var u_Texture: texture_2d<f32>;
+@group(1) @binding(1)
+var u_Sampler: sampler;
+@group(1) @binding(3)
+var u_Chroma: texture_2d<f32>;
+
+// Convert the limited range BT.709 YUV of the frame to gamma encoded RGB.
+fn sample_video(uv: vec2<f32>) -> vec4<f32> {
+    let y = textureSample(u_Texture, u_Sampler, uv).r;
+    let cbcr = textureSample(u_Chroma, u_Sampler, uv).rg;
+    let luma = (y - 16.0 / 255.0) * (255.0 / 219.0);
+    let cb = (cbcr.r - 128.0 / 255.0) * (255.0 / 224.0);
+    let cr = (cbcr.g - 128.0 / 255.0) * (255.0 / 224.0);
+    let rgb = vec3<f32>(
+        luma + 1.5748 * cr,
+        luma - 0.1873 * cb - 0.4681 * cr,
+        luma + 1.8556 * cb,
+    );
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    // The planes are unorm textures, so the gamma is not removed by the sampler.
+    return FragmentOutput(shade(color, srgb_to_linear(sample_video(style_uv(in.v_UV)))));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return FragmentOutput(shade(color, sample_video(style_uv(in.v_UV))));
+}
@@ -0,0 +1,3 @@
+struct FragmentOutput {
+    @location(0) o_Target: vec4<f32>,
+};
@@ -0,0 +1,25 @@
+@group(1) @binding(0)
+var u_Texture: texture_2d<i32>;
+
+// Integer texels can't be sampled, so load the texel under the UV coordinates and
+// display its color channels as 8-bit signed normalized values.
+fn load_texel(uv: vec2<f32>) -> vec4<f32> {
+    let size = textureDimensions(u_Texture);
+    let coords = min(vec2<u32>(uv * vec2<f32>(size)), size - 1u);
+    let texel = vec3<f32>(textureLoad(u_Texture, coords, 0).rgb) / 127.0;
+    return vec4<f32>(clamp(texel, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    return FragmentOutput(shade(color, load_texel(style_uv(in.v_UV))));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return FragmentOutput(shade(color, load_texel(style_uv(in.v_UV))));
+}
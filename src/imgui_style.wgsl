@@ -0,0 +1,92 @@
+// Set by `Texture::set_style`.
+struct TextureStyle {
+    tint: vec4<f32>,
+    uv_scale: vec2<f32>,
+    uv_offset: vec2<f32>,
+    exposure: f32,
+    tonemap: f32,
+    colormap: f32,
+    alpha_mask: f32,
+    distance_field: f32,
+    distance_width: f32,
+    distance_edge: f32,
+    subpixel: f32,
+    coverage_gamma: f32,
+};
+
+@group(1) @binding(4)
+var<uniform> u_Style: TextureStyle;
+
+// Map the UV coordinates of the draw command to the part of the texture that is displayed.
+fn style_uv(uv: vec2<f32>) -> vec2<f32> {
+    return uv * u_Style.uv_scale + u_Style.uv_offset;
+}
+
+// Scale the color of the texture by the exposure and optionally tonemap it, e.g. for HDR images.
+// Single channel textures can be displayed through a colormap instead.
+fn style_color(sampled: vec4<f32>) -> vec4<f32> {
+    let masked = select(sampled, vec4<f32>(1.0, 1.0, 1.0, sampled.r), u_Style.alpha_mask > 0.0);
+    // Distance fields have the edge of glyphs at 0.5, in the alpha or, for multi-channel
+    // ones, in the median of the colors. The edge is antialiased over about a pixel.
+    let median = max(min(masked.r, masked.g), min(max(masked.r, masked.g), masked.b));
+    let distance = select(masked.a, median, u_Style.distance_field > 1.0);
+    let smoothing = max(fwidth(distance) * 0.5 + u_Style.distance_edge, 1e-5);
+    let threshold = 0.5 - u_Style.distance_width;
+    let coverage = smoothstep(threshold - smoothing, threshold + smoothing, distance);
+    let color = select(masked, vec4<f32>(1.0, 1.0, 1.0, coverage), u_Style.distance_field > 0.0);
+    let exposed = color.rgb * exp2(u_Style.exposure);
+    let mapped = select(exposed, exposed / (exposed + 1.0), u_Style.tonemap > 0.0);
+    if u_Style.colormap > 0.0 {
+        return vec4<f32>(colormap(clamp(mapped.r, 0.0, 1.0), u32(u_Style.colormap)), 1.0);
+    }
+    return vec4<f32>(mapped, color.a);
+}
+
+// Multiply the styled color of the texture by the vertex color. Raising the coverage to a
+// power of the coverage gamma depending on the luminance of the vertex color thickens dark
+// and thins light text, which otherwise look too thin and bold when blended in linear space.
+fn shade(vertex_color: vec4<f32>, sampled: vec4<f32>) -> vec4<f32> {
+    let styled = style_color(sampled);
+    let luminance = dot(vertex_color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let corrected = pow(max(styled.a, 1e-6), pow(u_Style.coverage_gamma, luminance * 2.0 - 1.0));
+    let coverage = select(styled.a, corrected, u_Style.coverage_gamma != 1.0);
+    return vertex_color * vec4<f32>(styled.rgb, coverage);
+}
+
+// Polynomial fits of the colormaps of `Colormap`, indexed by its variants starting at 1.
+fn colormap(t: f32, index: u32) -> vec3<f32> {
+    switch index {
+        case 2u: {
+            let c0 = vec3<f32>(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
+            let c1 = vec3<f32>(0.1050930431085774, 1.404613529898575, 1.384590162594685);
+            let c2 = vec3<f32>(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
+            let c3 = vec3<f32>(-4.634230498983486, -5.799100973351585, -19.33244095627987);
+            let c4 = vec3<f32>(6.228269936347081, 14.17993336680509, 56.69055260068105);
+            let c5 = vec3<f32>(4.776384997670288, -13.74514537774601, -65.35303263337234);
+            let c6 = vec3<f32>(-5.435455855934631, 4.645852612178535, 26.3124352495832);
+            return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+        }
+        case 3u: {
+            let c0 = vec3<f32>(0.0002189403691192265, 0.001651004631001012, -0.01948089843709184);
+            let c1 = vec3<f32>(0.1065134194856116, 0.5639564367884091, 3.932712388889277);
+            let c2 = vec3<f32>(11.60249308247187, -3.972853965665698, -15.9423941062914);
+            let c3 = vec3<f32>(-41.70399613139459, 17.43639888205313, 44.35414519872813);
+            let c4 = vec3<f32>(77.162935699427, -33.40235894210092, -81.80730925738993);
+            let c5 = vec3<f32>(-71.31942824499214, 32.62606426397723, 73.20951985803202);
+            let c6 = vec3<f32>(25.13112622477341, -12.24266895238567, -23.07032500287172);
+            return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+        }
+        case 4u: {
+            let v4 = vec4<f32>(1.0, t, t * t, t * t * t);
+            let v2 = v4.zw * v4.z;
+            return clamp(vec3<f32>(
+                dot(v4, vec4<f32>(0.13572138, 4.61539260, -42.66032258, 132.13108234)) + dot(v2, vec2<f32>(-152.94239396, 59.28637943)),
+                dot(v4, vec4<f32>(0.09140261, 2.19418839, 4.84296658, -14.18503333)) + dot(v2, vec2<f32>(4.27729857, 2.82956604)),
+                dot(v4, vec4<f32>(0.10667330, 12.64194608, -60.58204836, 110.36276771)) + dot(v2, vec2<f32>(-89.90310912, 27.34824973)),
+            ), vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        default: {
+            return vec3<f32>(t);
+        }
+    }
+}
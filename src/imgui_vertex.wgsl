@@ -0,0 +1,11 @@
+struct Uniforms {
+    u_Matrix: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    return transform(in, uniforms.u_Matrix);
+}
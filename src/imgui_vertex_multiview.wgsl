@@ -0,0 +1,13 @@
+struct Uniforms {
+    u_Matrix: mat4x4<f32>,
+    // Per-view transforms applied after the projection, see `Renderer::set_view_transforms`.
+    u_ViewMatrices: array<mat4x4<f32>, 4>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput, @builtin(view_index) view_index: i32) -> VertexOutput {
+    return transform(in, uniforms.u_ViewMatrices[view_index] * uniforms.u_Matrix);
+}
@@ -0,0 +1,25 @@
+// Set by `Texture::set_slice`.
+struct TextureParams {
+    slice: f32,
+};
+
+@group(1) @binding(0)
+var u_Texture: texture_3d<f32>;
+@group(1) @binding(1)
+var u_Sampler: sampler;
+@group(1) @binding(2)
+var<uniform> u_Params: TextureParams;
+
+@fragment
+fn fs_main_linear(in: VertexOutput) -> FragmentOutput {
+    let color = linear_vertex_color(in.v_Color) * u_Style.tint;
+
+    return FragmentOutput(shade(color, textureSample(u_Texture, u_Sampler, vec3<f32>(style_uv(in.v_UV), u_Params.slice))));
+}
+
+@fragment
+fn fs_main_srgb(in: VertexOutput) -> FragmentOutput {
+    let color = in.v_Color * u_Style.tint;
+
+    return FragmentOutput(shade(color, textureSample(u_Texture, u_Sampler, vec3<f32>(style_uv(in.v_UV), u_Params.slice))));
+}
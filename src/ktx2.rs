@@ -0,0 +1,341 @@
+//! Loading textures from KTX2 files.
+
+use crate::load::{check_mip_levels, level_size, unsupported, TextureData, TextureLoadError};
+use crate::{Renderer, Texture};
+use std::borrow::Cow;
+use std::io::Read;
+use wgpu::*;
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+
+impl Texture {
+    /// Create a texture from the contents of a KTX2 file and upload all its mip levels.
+    ///
+    /// Files with 2D array layers, cube faces or 3D depth are created with the matching
+    /// [`TextureKind`](crate::TextureKind). Zstandard supercompression is supported, but
+    /// Basis Universal and Zlib are not. Compressed formats require the matching
+    /// `Features::TEXTURE_COMPRESSION_*`.
+    pub fn from_ktx2(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        bytes: &[u8],
+    ) -> Result<Texture, TextureLoadError> {
+        parse(bytes)?.upload(device, queue, renderer, Some("imgui-wgpu ktx2 texture"))
+    }
+}
+
+/// Parse the header, level index and mip levels of a KTX2 file.
+fn parse(bytes: &[u8]) -> Result<TextureData<'_>, TextureLoadError> {
+    if bytes.len() < HEADER_SIZE || bytes[..12] != IDENTIFIER {
+        return Err(TextureLoadError::Invalid("missing KTX2 identifier"));
+    }
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let vk_format = u32_at(12);
+    let width = u32_at(20);
+    let height = u32_at(24).max(1);
+    let depth = u32_at(28).max(1);
+    let layers = u32_at(32).max(1);
+    let faces = u32_at(36);
+    let level_count = u32_at(40).max(1) as usize;
+    let supercompression = u32_at(44);
+
+    let format = vk_format_to_wgpu(vk_format)
+        .ok_or_else(|| unsupported(format!("Vulkan format {vk_format}")))?;
+    if width == 0 {
+        return Err(TextureLoadError::Invalid("width is zero"));
+    }
+    if depth > 1 && (layers > 1 || faces > 1) {
+        return Err(unsupported("3D texture array"));
+    }
+    if !matches!(
+        supercompression,
+        SUPERCOMPRESSION_NONE | SUPERCOMPRESSION_ZSTD
+    ) {
+        return Err(unsupported(format!(
+            "supercompression scheme {supercompression}"
+        )));
+    }
+
+    let (dimension, depth_or_array_layers) = if depth > 1 {
+        (TextureDimension::D3, depth)
+    } else {
+        let layers = layers
+            .checked_mul(faces.max(1))
+            .ok_or(TextureLoadError::Invalid("too many array layers"))?;
+        (TextureDimension::D2, layers)
+    };
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers,
+    };
+    check_mip_levels(size, dimension, level_count)?;
+
+    let level_index_end = HEADER_SIZE + level_count * LEVEL_INDEX_ENTRY_SIZE;
+    if bytes.len() < level_index_end {
+        return Err(TextureLoadError::Invalid("level index is truncated"));
+    }
+    let u64_at = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+    let levels = (0..level_count)
+        .map(|level| {
+            let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+            let offset = u64_at(entry) as usize;
+            let length = u64_at(entry + 8) as usize;
+            let data = offset
+                .checked_add(length)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(TextureLoadError::Invalid("mip level is out of bounds"))?;
+            if supercompression == SUPERCOMPRESSION_ZSTD {
+                // Never decompress more than the level holds, whatever the header claims.
+                let expected = level_size(format, size, dimension, level as u32)?;
+                let uncompressed_length = u64_at(entry + 16).min(expected as u64) as usize;
+                decompress_zstd(data, uncompressed_length).map(Cow::Owned)
+            } else {
+                Ok(Cow::Borrowed(data))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(TextureData {
+        format,
+        size,
+        dimension,
+        cube: faces == 6,
+        levels,
+    })
+}
+
+/// Decompress a Zstandard supercompressed mip level of at most `uncompressed_length`
+/// bytes.
+fn decompress_zstd(data: &[u8], uncompressed_length: usize) -> Result<Vec<u8>, TextureLoadError> {
+    let mut source = data;
+    let decoder = ruzstd::decoding::StreamingDecoder::new(&mut source)
+        .map_err(|_| TextureLoadError::Invalid("bad Zstandard frame"))?;
+    // Grow the level with the decompressed data instead of trusting the header.
+    let mut level = Vec::new();
+    decoder
+        .take(uncompressed_length as u64)
+        .read_to_end(&mut level)
+        .map_err(|_| TextureLoadError::Invalid("bad Zstandard data"))?;
+    Ok(level)
+}
+
+/// The wgpu format matching a `VkFormat`, if there is one.
+fn vk_format_to_wgpu(vk_format: u32) -> Option<TextureFormat> {
+    use AstcBlock::*;
+    use TextureFormat::*;
+
+    let astc = |block, srgb| Astc {
+        block,
+        channel: if srgb {
+            AstcChannel::UnormSrgb
+        } else {
+            AstcChannel::Unorm
+        },
+    };
+    Some(match vk_format {
+        9 => R8Unorm,
+        10 => R8Snorm,
+        13 => R8Uint,
+        14 => R8Sint,
+        16 => Rg8Unorm,
+        17 => Rg8Snorm,
+        20 => Rg8Uint,
+        21 => Rg8Sint,
+        37 => Rgba8Unorm,
+        38 => Rgba8Snorm,
+        41 => Rgba8Uint,
+        42 => Rgba8Sint,
+        43 => Rgba8UnormSrgb,
+        44 => Bgra8Unorm,
+        50 => Bgra8UnormSrgb,
+        64 => Rgb10a2Unorm,
+        70 => R16Unorm,
+        71 => R16Snorm,
+        74 => R16Uint,
+        75 => R16Sint,
+        76 => R16Float,
+        77 => Rg16Unorm,
+        78 => Rg16Snorm,
+        81 => Rg16Uint,
+        82 => Rg16Sint,
+        83 => Rg16Float,
+        91 => Rgba16Unorm,
+        92 => Rgba16Snorm,
+        95 => Rgba16Uint,
+        96 => Rgba16Sint,
+        97 => Rgba16Float,
+        98 => R32Uint,
+        99 => R32Sint,
+        100 => R32Float,
+        101 => Rg32Uint,
+        102 => Rg32Sint,
+        103 => Rg32Float,
+        107 => Rgba32Uint,
+        108 => Rgba32Sint,
+        109 => Rgba32Float,
+        122 => Rg11b10Ufloat,
+        123 => Rgb9e5Ufloat,
+        131 | 133 => Bc1RgbaUnorm,
+        132 | 134 => Bc1RgbaUnormSrgb,
+        135 => Bc2RgbaUnorm,
+        136 => Bc2RgbaUnormSrgb,
+        137 => Bc3RgbaUnorm,
+        138 => Bc3RgbaUnormSrgb,
+        139 => Bc4RUnorm,
+        140 => Bc4RSnorm,
+        141 => Bc5RgUnorm,
+        142 => Bc5RgSnorm,
+        143 => Bc6hRgbUfloat,
+        144 => Bc6hRgbFloat,
+        145 => Bc7RgbaUnorm,
+        146 => Bc7RgbaUnormSrgb,
+        147 => Etc2Rgb8Unorm,
+        148 => Etc2Rgb8UnormSrgb,
+        149 => Etc2Rgb8A1Unorm,
+        150 => Etc2Rgb8A1UnormSrgb,
+        151 => Etc2Rgba8Unorm,
+        152 => Etc2Rgba8UnormSrgb,
+        153 => EacR11Unorm,
+        154 => EacR11Snorm,
+        155 => EacRg11Unorm,
+        156 => EacRg11Snorm,
+        157..=184 => {
+            let blocks = [
+                B4x4, B5x4, B5x5, B6x5, B6x6, B8x5, B8x6, B8x8, B10x5, B10x6, B10x8, B10x10,
+                B12x10, B12x12,
+            ];
+            let index = vk_format - 157;
+            astc(blocks[index as usize / 2], index % 2 == 1)
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A KTX2 file of RGBA8 layers with a level index of `(offset, length)` pairs, followed
+    /// by `data`.
+    fn ktx2(header: [u32; 5], levels: &[(u64, u64)], data: &[u8]) -> Vec<u8> {
+        let [width, height, depth, layers, faces] = header;
+        let mut bytes = IDENTIFIER.to_vec();
+        for value in [
+            37,
+            1,
+            width,
+            height,
+            depth,
+            layers,
+            faces,
+            levels.len() as u32,
+        ] {
+            bytes.extend(u32::to_le_bytes(value));
+        }
+        bytes.resize(HEADER_SIZE, 0);
+        for &(offset, length) in levels {
+            for value in [offset, length, length] {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    /// The offset of the data of [`ktx2`] with `level_count` levels.
+    fn data_offset(level_count: usize) -> u64 {
+        (HEADER_SIZE + level_count * LEVEL_INDEX_ENTRY_SIZE) as u64
+    }
+
+    #[test]
+    fn parses_mip_levels() {
+        let start = data_offset(2);
+        let data: Vec<u8> = (0..20).collect();
+        let bytes = ktx2([2, 2, 0, 0, 1], &[(start, 16), (start + 16, 4)], &data);
+        let texture = parse(&bytes).unwrap();
+        assert_eq!(texture.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(
+            texture.size,
+            Extent3d {
+                width: 2,
+                height: 2,
+                depth_or_array_layers: 1
+            }
+        );
+        assert_eq!(texture.dimension, TextureDimension::D2);
+        assert!(!texture.cube);
+        assert_eq!(texture.levels[0][..], data[..16]);
+        assert_eq!(texture.levels[1][..], data[16..]);
+    }
+
+    #[test]
+    fn parses_layers_faces_and_depth() {
+        let start = data_offset(1);
+        let cube_array = ktx2([1, 1, 0, 2, 6], &[(start, 48)], &[0; 48]);
+        let texture = parse(&cube_array).unwrap();
+        assert_eq!(texture.size.depth_or_array_layers, 12);
+        assert!(texture.cube);
+
+        let volume = ktx2([1, 1, 3, 0, 1], &[(start, 12)], &[0; 12]);
+        let texture = parse(&volume).unwrap();
+        assert_eq!(texture.size.depth_or_array_layers, 3);
+        assert_eq!(texture.dimension, TextureDimension::D3);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let invalid = |bytes: &[u8]| matches!(parse(bytes), Err(TextureLoadError::Invalid(_)));
+        let start = data_offset(1);
+        let bytes = ktx2([1, 1, 0, 0, 1], &[(start, 4)], &[0; 4]);
+        assert!(parse(&bytes).is_ok());
+        assert!(invalid(&bytes[1..]));
+        assert!(invalid(&bytes[..HEADER_SIZE + 8]));
+        assert!(invalid(&ktx2([0, 1, 0, 0, 1], &[(start, 4)], &[0; 4])));
+        // Levels outside of the file, or whose end overflows.
+        assert!(invalid(&ktx2([1, 1, 0, 0, 1], &[(start, 5)], &[0; 4])));
+        assert!(invalid(&ktx2([1, 1, 0, 0, 1], &[(u64::MAX, 4)], &[0; 4])));
+        assert!(invalid(&ktx2(
+            [1, 1, 0, 0, 1],
+            &[(start, u64::MAX)],
+            &[0; 4]
+        )));
+    }
+
+    #[test]
+    fn rejects_sizes_from_the_header_that_overflow() {
+        let invalid = |bytes: &[u8]| matches!(parse(bytes), Err(TextureLoadError::Invalid(_)));
+        let start = data_offset(1);
+        assert!(invalid(&ktx2(
+            [1, 1, 0, u32::MAX, 6],
+            &[(start, 4)],
+            &[0; 4]
+        )));
+        // A 1x1 texture has a single mip level.
+        let levels = [(start, 4); 2];
+        assert!(invalid(&ktx2([1, 1, 0, 0, 1], &levels, &[0; 4])));
+        let mut bytes = ktx2([1, 1, 0, 0, 1], &[(start, 4)], &[0; 4]);
+        bytes[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(invalid(&bytes));
+    }
+
+    #[test]
+    fn rejects_unsupported_files() {
+        let unsupported =
+            |bytes: &[u8]| matches!(parse(bytes), Err(TextureLoadError::Unsupported(_)));
+        let start = data_offset(1);
+        let mut bytes = ktx2([1, 1, 0, 0, 1], &[(start, 4)], &[0; 4]);
+        // Basis Universal supercompression.
+        bytes[44..48].copy_from_slice(&1u32.to_le_bytes());
+        assert!(unsupported(&bytes));
+        assert!(unsupported(&ktx2([1, 1, 2, 2, 1], &[(start, 4)], &[0; 4])));
+    }
+}
@@ -1,17 +1,128 @@
+use callback::render_callback_marker;
+use eviction::TextureEviction;
+use handle::DroppedTextures;
 use imgui::{
-    Context, DrawCmd::Elements, DrawData, DrawIdx, DrawList, DrawVert, TextureId, Textures,
+    Context, DrawCmd, DrawCmd::Elements, DrawCmdParams, DrawData, DrawIdx, DrawList, DrawVert,
+    FontAtlas, TextureId,
 };
+use shared::TextureResources;
 use smallvec::SmallVec;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::mem::size_of;
-use std::sync::Arc;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, RwLockReadGuard};
+use upload::UploadQueue;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::*;
 
+#[cfg(feature = "animation")]
+mod animated;
+mod atlas;
+mod builder;
+mod callback;
+#[cfg(feature = "compress")]
+mod compress;
+#[cfg(feature = "dds")]
+mod dds;
+#[cfg(feature = "debugger")]
+mod debugger;
+mod eviction;
+#[cfg(all(feature = "external-memory", not(target_arch = "wasm32")))]
+mod external;
+mod grab;
+mod handle;
+#[cfg(feature = "hdr")]
+mod hdr;
+#[cfg(feature = "image")]
+mod images;
+#[cfg(feature = "ktx2")]
+mod ktx2;
+#[cfg(any(feature = "ktx2", feature = "dds"))]
+mod load;
+mod memory;
+mod mipmap;
+mod placeholder;
+mod pool;
+mod readback;
+mod region;
+mod registry;
+mod report;
+mod sampler;
+#[cfg(feature = "save")]
+mod save;
+mod scale;
+mod scissor;
+mod shaders;
+mod shared;
+mod sink;
+#[cfg(feature = "svg")]
+mod svg;
+mod upload;
+pub mod util;
+mod video;
+pub mod widgets;
+
+#[cfg(feature = "animation")]
+pub use animated::AnimatedTexture;
+pub use atlas::{AtlasRect, TextureAtlas};
+pub use builder::{RendererConfigBuilder, RendererConfigError};
+pub use callback::{ClipStack, RenderCallback, RenderCallbackId};
+pub use grab::FrameGrabber;
+pub use handle::TextureHandle;
+#[cfg(any(feature = "ktx2", feature = "dds"))]
+pub use load::TextureLoadError;
+pub use memory::{MemoryBudgetCallback, MemoryReport};
+pub use pool::TexturePool;
+pub use region::TextureRegion;
+pub use registry::TextureRegistry;
+pub use report::{FrameReport, SkipReason, SkippedCommand};
+pub use sampler::SamplerDefaults;
+#[cfg(feature = "save")]
+pub use save::TextureSaveError;
+pub use scissor::{ScissorRect, ScissorRounding};
+pub use shared::SharedRendererResources;
+pub use sink::{FramePlanes, FrameSource, VideoFrame, VideoTextureSink};
+pub use video::VideoTexture;
+
+/// Open a `profiling` scope if the `profiling` feature is enabled.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        profiling::scope!($name);
+    };
+}
+
 static VS_ENTRY_POINT: &str = "vs_main";
 static FS_ENTRY_POINT_LINEAR: &str = "fs_main_linear";
 static FS_ENTRY_POINT_SRGB: &str = "fs_main_srgb";
+static LINEARIZE_VERTEX_COLORS_CONSTANT: &str = "LINEARIZE_VERTEX_COLORS";
+
+const IDENTITY_MATRIX: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// The maximum number of views supported by [`RendererConfig::multiview`].
+pub const MAX_VIEWS: usize = 4;
+
+/// The formats supported by [`RendererConfig::font_atlas_format`].
+pub const FONT_ATLAS_FORMATS: [TextureFormat; 4] = [
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::R8Unorm,
+    TextureFormat::Bc4RUnorm,
+];
+
+/// The coverage gamma of the font atlas with [`RendererConfig::gamma_correct_text`].
+pub const TEXT_COVERAGE_GAMMA: f32 = 1.8;
+
+/// Upper bound for the size of a single font atlas upload.
+const FONT_UPLOAD_BAND_SIZE: u64 = 16 << 20;
 
 pub type RendererResult<T> = Result<T, RendererError>;
 
@@ -24,8 +135,43 @@ unsafe impl bytemuck::Zeroable for DrawVertPod {}
 unsafe impl bytemuck::Pod for DrawVertPod {}
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum RendererError {
+    /// No texture with this id was ever inserted.
     BadTexture(TextureId),
+    /// The texture of this id was removed, see [`TextureRegistry::is_stale`].
+    StaleTexture(TextureId),
+    /// The data passed to [`Renderer::enqueue_upload`] is shorter than the first mip
+    /// level of the texture.
+    UploadTooShort {
+        /// The texture the data was enqueued for.
+        texture_id: TextureId,
+        /// The size of the first mip level in bytes.
+        expected: usize,
+        /// The size of the data in bytes.
+        actual: usize,
+    },
+    /// The font atlas is larger than the maximum texture size of the device, see
+    /// [`Renderer::reload_font_texture`].
+    FontAtlasTooLarge {
+        /// The width of the atlas in pixels.
+        width: u32,
+        /// The height of the atlas in pixels.
+        height: u32,
+        /// The maximum width and height of textures, `Limits::max_texture_dimension_2d`.
+        max: u32,
+    },
+    /// The texture doesn't own a `wgpu::Texture` that could be written, because it was
+    /// created with [`Texture::from_view`] or [`Renderer::insert_region`].
+    BorrowedTexture(TextureId),
+    /// The scale factor passed to [`Renderer::handle_scale_factor_change`] isn't a finite
+    /// number greater than 0.
+    InvalidScaleFactor(f32),
+    /// The configuration requires device features that are not enabled.
+    MissingFeatures(Features),
+    /// The configuration combines settings that can't be used together, see
+    /// [`RendererConfigBuilder::build`].
+    InvalidConfig(RendererConfigError),
 }
 
 impl fmt::Display for RendererError {
@@ -34,6 +180,44 @@ impl fmt::Display for RendererError {
             RendererError::BadTexture(id) => {
                 write!(f, "imgui render error: bad texture id '{}'", id.id())
             }
+            RendererError::StaleTexture(id) => {
+                write!(
+                    f,
+                    "imgui render error: texture id '{}' was removed",
+                    id.id()
+                )
+            }
+            RendererError::UploadTooShort {
+                texture_id,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "imgui render error: {actual} bytes enqueued for texture id '{}', which needs {expected}",
+                    texture_id.id()
+                )
+            }
+            RendererError::FontAtlasTooLarge { width, height, max } => {
+                write!(
+                    f,
+                    "imgui render error: the font atlas of {width}x{height} pixels exceeds the maximum texture size of {max}"
+                )
+            }
+            RendererError::BorrowedTexture(id) => {
+                write!(
+                    f,
+                    "imgui render error: texture id '{}' doesn't own a wgpu::Texture",
+                    id.id()
+                )
+            }
+            RendererError::InvalidScaleFactor(scale) => {
+                write!(f, "imgui render error: invalid scale factor {scale}")
+            }
+            RendererError::MissingFeatures(features) => {
+                write!(f, "imgui render error: requires {features:?}")
+            }
+            RendererError::InvalidConfig(ref error) => error.fmt(f),
         }
     }
 }
@@ -47,6 +231,436 @@ enum ShaderStage {
     Compute,
 }
 
+/// How the shaders read a texture, which depends on its format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureKind {
+    /// Filterable float textures, which covers all color formats except 32-bit floats.
+    #[default]
+    Filterable,
+    /// Float textures that can't be filtered, like `Rgba32Float` or `R32Float` without
+    /// `Features::FLOAT32_FILTERABLE`. The sampler always uses nearest filtering.
+    NonFilterable,
+    /// Unsigned integer textures like `R8Uint`. Their color channels are displayed as
+    /// 8-bit normalized values, larger values are clamped to 1.
+    Uint,
+    /// Signed integer textures like `R8Sint`. Their color channels are displayed as
+    /// 8-bit normalized values, negative values are clamped to 0.
+    Sint,
+    /// Filterable float 2D array textures, displaying the layer selected with
+    /// [`Texture::set_layer`].
+    Array,
+    /// Filterable float cube textures, displayed with the projection selected with
+    /// [`Texture::set_cube_projection`].
+    Cube,
+    /// Filterable float 3D textures, displaying the Z slice selected with
+    /// [`Texture::set_slice`].
+    Volume,
+    /// Depth textures, displayed as grayscale and optionally linearized with
+    /// [`Texture::set_depth_range`]. Only the depth aspect of depth-stencil formats is shown.
+    Depth,
+    /// NV12 video frames, converted from YUV to RGB while drawing. Textures of this kind
+    /// are created with [`VideoTexture::create_texture`].
+    Nv12,
+}
+
+/// How a [`TextureKind::Cube`] texture is unfolded for display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CubeProjection {
+    /// Longitude and latitude map to x and y, best displayed with a 2:1 aspect ratio.
+    #[default]
+    Equirectangular,
+    /// A horizontal cross of 4x3 faces, with -X, +Z, +X and -Z in the middle row and
+    /// +Y and -Y above and below +Z.
+    Cross,
+}
+
+/// How the red channel of a single channel texture is displayed, see
+/// [`TextureStyle::colormap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    /// Black to white.
+    Grayscale,
+    /// Perceptually uniform dark blue to yellow, e.g. for heightmaps.
+    Viridis,
+    /// Perceptually uniform black to light yellow through red.
+    Inferno,
+    /// Dark blue to dark red through the rainbow, e.g. for depth buffers.
+    Turbo,
+}
+
+/// How a texture is displayed by the built-in shaders, see [`Texture::set_style`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureStyle {
+    /// Multiplied with the color of the texture, e.g. to highlight or fade an image.
+    pub tint: [f32; 4],
+    /// Multiplied with the UV coordinates of the draw commands.
+    pub uv_scale: [f32; 2],
+    /// Added to the scaled UV coordinates, together with `uv_scale` this selects a
+    /// sub-rectangle of the texture, e.g. an image of an atlas.
+    pub uv_offset: [f32; 2],
+    /// The color of the texture is multiplied with 2 to the power of `exposure`, e.g. to
+    /// inspect the dark or bright parts of an HDR image.
+    pub exposure: f32,
+    /// Map the exposed color of the texture from `0..inf` to `0..1` with the Reinhard
+    /// operator, e.g. to display HDR images loaded with `Texture::from_hdr`.
+    pub tonemap: bool,
+    /// Display the red channel through a colormap, e.g. for `R8Unorm` masks or
+    /// `R16Float` heightmaps, which are displayed in red otherwise. The value is clamped
+    /// to `0..1` after applying `exposure` and `tonemap`, and the alpha is opaque.
+    pub colormap: Option<Colormap>,
+    /// Display the red channel as the alpha of white, e.g. for `R8Unorm` masks or the
+    /// font atlas of [`RendererConfig::alpha8_font_atlas`]. Applied before the others.
+    pub alpha_mask: bool,
+    /// Display the texture as a signed distance field, e.g. for font atlases whose glyphs
+    /// stay crisp at any scale, see [`RendererConfig::font_distance_field`]. Applied after
+    /// `alpha_mask`.
+    pub distance_field: Option<DistanceField>,
+    /// Blend each color channel with its own coverage, sampled a third of a pixel apart,
+    /// so text is antialiased per subpixel on horizontal RGB panels, see
+    /// [`RendererConfig::subpixel_text`]. Only the dual-source blending shaders support it.
+    pub subpixel: bool,
+    /// Raise the coverage in the alpha channel to the power of this gamma for black, and of
+    /// its inverse for white vertex colors, see [`RendererConfig::gamma_correct_text`].
+    /// `1.0` disables the correction.
+    pub coverage_gamma: f32,
+}
+
+/// How a signed distance field texture is displayed, see [`TextureStyle::distance_field`].
+///
+/// The texture holds the distance to the edge of the shape, with the edge at 0.5 and
+/// larger values inside. The shape is displayed in white with antialiased edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DistanceField {
+    /// Whether the distance is the median of the red, green and blue channels of a
+    /// multi-channel distance field (MSDF), which keeps corners sharp. Otherwise the
+    /// distance is in the alpha channel.
+    pub multi_channel: bool,
+    /// Moves the edge outwards by this distance, e.g. to display bold text, or inwards
+    /// if negative.
+    pub width: f32,
+    /// Softens the edge by this distance beyond the antialiasing, e.g. for glows.
+    pub edge: f32,
+}
+
+impl Default for TextureStyle {
+    /// Display the whole texture without tint.
+    fn default() -> Self {
+        Self {
+            tint: [1.0; 4],
+            uv_scale: [1.0; 2],
+            uv_offset: [0.0; 2],
+            exposure: 0.0,
+            tonemap: false,
+            colormap: None,
+            alpha_mask: false,
+            distance_field: None,
+            subpixel: false,
+            coverage_gamma: 1.0,
+        }
+    }
+}
+
+impl TextureStyle {
+    /// The contents of the uniform buffer, matching `TextureStyle` in the shaders.
+    fn to_uniform(self) -> [f32; 20] {
+        let [r, g, b, a] = self.tint;
+        let [scale_u, scale_v] = self.uv_scale;
+        let [offset_u, offset_v] = self.uv_offset;
+        let tonemap = if self.tonemap { 1.0 } else { 0.0 };
+        // 0 disables the distance field, 1 is a single and 2 a multi-channel one.
+        let distance_mode = match self.distance_field {
+            None => 0.0,
+            Some(DistanceField { multi_channel, .. }) => 1.0 + multi_channel as u32 as f32,
+        };
+        let distance_field = self.distance_field.unwrap_or_default();
+        // 0 disables the colormap, the variants start at 1.
+        let colormap = self
+            .colormap
+            .map_or(0.0, |colormap| colormap as u32 as f32 + 1.0);
+        [
+            r,
+            g,
+            b,
+            a,
+            scale_u,
+            scale_v,
+            offset_u,
+            offset_v,
+            self.exposure,
+            tonemap,
+            colormap,
+            if self.alpha_mask { 1.0 } else { 0.0 },
+            distance_mode,
+            distance_field.width,
+            distance_field.edge,
+            if self.subpixel { 1.0 } else { 0.0 },
+            self.coverage_gamma,
+            0.0,
+            0.0,
+            0.0,
+        ]
+    }
+}
+
+impl TextureKind {
+    /// All kinds, in the order the renderer stores their layouts and pipelines.
+    const ALL: [TextureKind; 9] = [
+        TextureKind::Filterable,
+        TextureKind::NonFilterable,
+        TextureKind::Uint,
+        TextureKind::Sint,
+        TextureKind::Array,
+        TextureKind::Cube,
+        TextureKind::Volume,
+        TextureKind::Depth,
+        TextureKind::Nv12,
+    ];
+
+    /// Create the bind group layout of textures of this kind.
+    fn create_bind_group_layout(self, device: &Device) -> BindGroupLayout {
+        let (sample_type, sampler_type) = match self {
+            TextureKind::Filterable
+            | TextureKind::Array
+            | TextureKind::Cube
+            | TextureKind::Volume
+            | TextureKind::Nv12 => (
+                TextureSampleType::Float { filterable: true },
+                SamplerBindingType::Filtering,
+            ),
+            TextureKind::NonFilterable => (
+                TextureSampleType::Float { filterable: false },
+                SamplerBindingType::NonFiltering,
+            ),
+            // Integer textures are loaded, but keep the sampler binding of the other kinds.
+            TextureKind::Uint => (TextureSampleType::Uint, SamplerBindingType::NonFiltering),
+            TextureKind::Sint => (TextureSampleType::Sint, SamplerBindingType::NonFiltering),
+            TextureKind::Depth => (TextureSampleType::Depth, SamplerBindingType::NonFiltering),
+        };
+        let texture_entries = [
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type,
+                    view_dimension: self.view_dimension(),
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(sampler_type),
+                count: None,
+            },
+        ];
+        let params_entry = BindGroupLayoutEntry {
+            binding: 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let style_entry = BindGroupLayoutEntry {
+            binding: 4,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        // The chroma plane of video frames, the luma plane is bound as the texture.
+        let chroma_entry = BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: BindingType::Texture {
+                multisampled: false,
+                sample_type,
+                view_dimension: TextureViewDimension::D2,
+            },
+            count: None,
+        };
+        let entries: Vec<_> = texture_entries
+            .into_iter()
+            .chain(self.has_params().then_some(params_entry))
+            .chain((self == TextureKind::Nv12).then_some(chroma_entry))
+            .chain([style_entry])
+            .collect();
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("imgui-wgpu bind group layout"),
+            entries: &entries,
+        })
+    }
+
+    /// The dimension of views of this kind of texture.
+    fn view_dimension(self) -> TextureViewDimension {
+        match self {
+            TextureKind::Filterable
+            | TextureKind::NonFilterable
+            | TextureKind::Uint
+            | TextureKind::Sint
+            | TextureKind::Depth
+            | TextureKind::Nv12 => TextureViewDimension::D2,
+            TextureKind::Array => TextureViewDimension::D2Array,
+            TextureKind::Cube => TextureViewDimension::Cube,
+            TextureKind::Volume => TextureViewDimension::D3,
+        }
+    }
+
+    /// Whether textures of this kind have a uniform buffer with display parameters.
+    fn has_params(self) -> bool {
+        match self {
+            TextureKind::Filterable
+            | TextureKind::NonFilterable
+            | TextureKind::Uint
+            | TextureKind::Sint
+            | TextureKind::Nv12 => false,
+            TextureKind::Array | TextureKind::Cube | TextureKind::Volume | TextureKind::Depth => {
+                true
+            }
+        }
+    }
+
+    /// The built-in shader drawing this kind of texture, or `None` if it is drawn with
+    /// the renderer's shader.
+    fn builtin_shader(self) -> Option<ShaderModuleDescriptor<'static>> {
+        match self {
+            TextureKind::Filterable | TextureKind::NonFilterable => None,
+            TextureKind::Uint => Some(shaders::UINT),
+            TextureKind::Sint => Some(shaders::SINT),
+            TextureKind::Array => Some(shaders::ARRAY),
+            TextureKind::Cube => Some(shaders::CUBE),
+            TextureKind::Volume => Some(shaders::VOLUME),
+            TextureKind::Depth => Some(shaders::DEPTH),
+            TextureKind::Nv12 => Some(shaders::NV12),
+        }
+    }
+
+    /// The descriptor of views of this kind of texture, based on `desc` if given.
+    ///
+    /// The dimension and aspect of this kind are used unless `desc` sets them.
+    fn view_desc<'a>(self, desc: Option<&TextureViewDescriptor<'a>>) -> TextureViewDescriptor<'a> {
+        let desc = desc.cloned().unwrap_or_default();
+        TextureViewDescriptor {
+            dimension: desc.dimension.or(Some(self.view_dimension())),
+            aspect: match desc.aspect {
+                TextureAspect::All => self.view_aspect(),
+                aspect => aspect,
+            },
+            ..desc
+        }
+    }
+
+    /// The aspect of the texture shown by views of this kind.
+    fn view_aspect(self) -> TextureAspect {
+        match self {
+            TextureKind::Depth => TextureAspect::DepthOnly,
+            _ => TextureAspect::All,
+        }
+    }
+
+    /// Adjust `desc` to the filtering supported by this kind of texture.
+    fn sampler_desc<'a>(self, desc: &SamplerDescriptor<'a>) -> SamplerDescriptor<'a> {
+        match self {
+            TextureKind::Filterable
+            | TextureKind::Array
+            | TextureKind::Cube
+            | TextureKind::Volume
+            | TextureKind::Nv12 => desc.clone(),
+            TextureKind::NonFilterable
+            | TextureKind::Uint
+            | TextureKind::Sint
+            | TextureKind::Depth => SamplerDescriptor {
+                mag_filter: FilterMode::Nearest,
+                min_filter: FilterMode::Nearest,
+                mipmap_filter: FilterMode::Nearest,
+                anisotropy_clamp: 1,
+                ..desc.clone()
+            },
+        }
+    }
+}
+
+/// Create the uniform buffer holding the display parameters of a texture.
+fn create_texture_params(device: &Device) -> Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("imgui-wgpu texture params"),
+        size: 16,
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Create the uniform buffer holding the [`TextureStyle`] of a texture.
+fn create_texture_style(device: &Device, style: TextureStyle) -> Buffer {
+    device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("imgui-wgpu texture style"),
+        contents: bytemuck::bytes_of(&style.to_uniform()),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    })
+}
+
+/// Copy the tightly packed rows of `data` to `staging`, whose rows are
+/// `padded_bytes_per_row` apart.
+fn pad_rows(data: &[u8], staging: &mut [u8], bytes_per_row: u32, padded_bytes_per_row: u32) {
+    for (src, dst) in data
+        .chunks(bytes_per_row as usize)
+        .zip(staging.chunks_mut(padded_bytes_per_row as usize))
+    {
+        dst[..src.len()].copy_from_slice(src);
+    }
+}
+
+/// Create the bind group of a texture of `kind`, `params` is required by some kinds.
+/// Without a `style` buffer the default [`TextureStyle`] of the renderer is used.
+#[allow(clippy::too_many_arguments)]
+fn create_texture_bind_group(
+    device: &Device,
+    renderer: &Renderer,
+    label: Option<&str>,
+    kind: TextureKind,
+    view: &TextureView,
+    sampler: &Sampler,
+    params: Option<&Buffer>,
+    style: Option<&Buffer>,
+) -> BindGroup {
+    let texture_entries = [
+        BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(view),
+        },
+        BindGroupEntry {
+            binding: 1,
+            resource: BindingResource::Sampler(sampler),
+        },
+    ];
+    let params_entry = params.map(|params| BindGroupEntry {
+        binding: 2,
+        resource: params.as_entire_binding(),
+    });
+    let style_entry = BindGroupEntry {
+        binding: 4,
+        resource: style
+            .unwrap_or(&renderer.resources.default_style)
+            .as_entire_binding(),
+    };
+    let entries: Vec<_> = texture_entries
+        .into_iter()
+        .chain(params_entry)
+        .chain([style_entry])
+        .collect();
+    device.create_bind_group(&BindGroupDescriptor {
+        label,
+        layout: &renderer.resources.layouts[kind as usize],
+        entries: &entries,
+    })
+}
+
 /// Config for creating a texture from raw parts
 ///
 #[derive(Clone)]
@@ -55,6 +669,8 @@ pub struct RawTextureConfig<'a> {
     pub label: Option<&'a str>,
     /// The sampler descriptor of the texture.
     pub sampler_desc: SamplerDescriptor<'a>,
+    /// How the texture is read, see [`TextureKind`].
+    pub kind: TextureKind,
 }
 
 /// Config for creating a texture.
@@ -67,6 +683,11 @@ pub struct TextureConfig<'a> {
     /// An optional label for the texture used for debugging.
     pub label: Option<&'a str>,
     /// The format of the texture, if not set uses the format from the renderer.
+    ///
+    /// Block-compressed formats (BCn, ETC2 and ASTC) require the matching
+    /// `Features::TEXTURE_COMPRESSION_*` and a size that is a multiple of the block size.
+    /// Their pre-compressed data can be uploaded with [`Texture::write`] and
+    /// [`Texture::write_mip`].
     pub format: Option<TextureFormat>,
     /// The usage of the texture.
     pub usage: TextureUsages,
@@ -78,6 +699,28 @@ pub struct TextureConfig<'a> {
     pub dimension: TextureDimension,
     /// The sampler descriptor of the texture.
     pub sampler_desc: SamplerDescriptor<'a>,
+    /// How the texture is read, which has to match its format.
+    pub kind: TextureKind,
+    /// The part of the texture that is displayed, e.g. a single mip level or array
+    /// layer, or a format to view it as. If not set the whole texture is displayed.
+    ///
+    /// The dimension and aspect required by `kind` are used unless they are set. To show
+    /// a single aspect of a depth-stencil format, select `TextureAspect::DepthOnly` with
+    /// [`TextureKind::Depth`] or `TextureAspect::StencilOnly` with [`TextureKind::Uint`].
+    pub view_desc: Option<TextureViewDescriptor<'a>>,
+    /// The formats other views of the texture can have besides its format and the one of
+    /// `view_desc`, see [`Texture::with_view_format`].
+    ///
+    /// A texture can only be viewed in the formats that differ from its own by the sRGB
+    /// suffix, e.g. `Rgba8UnormSrgb` for an `Rgba8Unorm` texture.
+    pub view_formats: &'a [TextureFormat],
+    /// Regenerate the mip levels after the first before the next frame is rendered
+    /// whenever the first level is written with the queue, e.g. by [`Texture::write`].
+    ///
+    /// Only used with a `mip_level_count` above 1. The texture needs the usage required
+    /// by [`Texture::generate_mipmaps`]. Writes recorded into an encoder, like
+    /// [`Texture::write_from_buffer`], don't trigger it.
+    pub auto_mipmaps: bool,
 }
 
 impl<'a> Default for TextureConfig<'a> {
@@ -111,22 +754,73 @@ impl<'a> Default for TextureConfig<'a> {
             sample_count: 1,
             dimension: TextureDimension::D2,
             sampler_desc,
+            kind: TextureKind::default(),
+            view_desc: None,
+            view_formats: &[],
+            auto_mipmaps: false,
+        }
+    }
+}
+
+impl<'a> TextureConfig<'a> {
+    /// A config for a texture of `size` displayed with nearest-neighbor filtering, so
+    /// pixel art and other small images stay crisp when they are shown scaled up.
+    ///
+    /// Addressing is clamped, so neighboring images of a sprite atlas don't bleed into
+    /// each other at the edges.
+    pub fn pixel_art(size: Extent3d) -> Self {
+        let default = Self::default();
+        Self {
+            size,
+            sampler_desc: SamplerDescriptor {
+                label: Some("imgui-wgpu pixel art sampler"),
+                mag_filter: FilterMode::Nearest,
+                min_filter: FilterMode::Nearest,
+                mipmap_filter: FilterMode::Nearest,
+                ..default.sampler_desc
+            },
+            ..default
         }
     }
 }
 
 /// A container for a bindable texture.
+///
+/// Cloning is cheap, as all GPU resources are reference counted. Clones share the
+/// texture, its bind group and its parameters, so e.g. [`Self::set_layer`] on a clone
+/// also changes what the original displays.
+#[derive(Clone)]
 pub struct Texture {
-    texture: Arc<wgpu::Texture>,
-    view: Arc<wgpu::TextureView>,
+    /// `None` for textures created with [`Texture::from_view`].
+    texture: Option<Arc<wgpu::Texture>>,
+    view: Option<Arc<wgpu::TextureView>>,
+    sampler: Option<Arc<Sampler>>,
     bind_group: Arc<BindGroup>,
+    kind: TextureKind,
+    params: Option<Arc<Buffer>>,
     size: Extent3d,
+    /// The view descriptor from the [`TextureConfig`], without its label.
+    view_desc: Option<TextureViewDescriptor<'static>>,
+    /// Created by the first call of [`Texture::set_style`].
+    style: Option<Arc<Buffer>>,
+    /// The UV coordinates of the corners of textures inserted with
+    /// [`Renderer::insert_region`].
+    uv_rect: Option<[[f32; 2]; 2]>,
+    label: Option<String>,
+    /// Set by writes to the first mip level of textures created with
+    /// [`TextureConfig::auto_mipmaps`], until the renderer regenerates the others.
+    mipmaps_stale: Option<Arc<AtomicBool>>,
+    /// The view formats of the `wgpu::Texture`, kept when it is resized.
+    view_formats: Vec<TextureFormat>,
 }
 
 impl Texture {
     /// Create a `Texture` from its raw parts.
     /// - `bind_group`: The bind group used by the texture. If it is `None`, the bind group will be created like in `Self::new`.
     /// - `config`: The config used for creating the bind group. If `bind_group` is `Some(_)`, it will be ignored
+    ///
+    /// A custom `bind_group` has to match the layout of its [`TextureKind`], which includes
+    /// the uniform buffer of the [`TextureStyle`] at binding 4.
     pub fn from_raw_parts(
         device: &Device,
         renderer: &Renderer,
@@ -136,93 +830,361 @@ impl Texture {
         config: Option<&RawTextureConfig>,
         size: Extent3d,
     ) -> Self {
+        let kind = config.map_or(TextureKind::default(), |config| config.kind);
+        let mut sampler = None;
+        let params = (bind_group.is_none() && kind.has_params())
+            .then(|| Arc::new(create_texture_params(device)));
         let bind_group = bind_group.unwrap_or_else(|| {
             let config = config.unwrap();
 
             // Create the texture sampler.
-            let sampler = device.create_sampler(&config.sampler_desc);
+            let sampler =
+                sampler.insert(renderer.texture_sampler(device, kind, &config.sampler_desc));
 
             // Create the texture bind group from the layout.
-            Arc::new(device.create_bind_group(&BindGroupDescriptor {
-                label: config.label,
-                layout: &renderer.texture_layout,
-                entries: &[
-                    BindGroupEntry {
-                        binding: 0,
-                        resource: BindingResource::TextureView(&view),
-                    },
-                    BindGroupEntry {
-                        binding: 1,
-                        resource: BindingResource::Sampler(&sampler),
-                    },
-                ],
-            }))
+            Arc::new(create_texture_bind_group(
+                device,
+                renderer,
+                config.label,
+                kind,
+                &view,
+                sampler,
+                params.as_deref(),
+                None,
+            ))
         });
 
         Self {
-            texture,
-            view,
+            texture: Some(texture),
+            view: Some(view),
+            sampler,
             bind_group,
+            kind,
+            params,
+            size,
+            view_desc: None,
+            style: None,
+            uv_rect: None,
+            label: config.and_then(|config| config.label).map(str::to_owned),
+            mipmaps_stale: None,
+            view_formats: Vec::new(),
+        }
+    }
+
+    /// Create a `Texture` that only holds a bind group over `view`, which stays owned by
+    /// the application, e.g. a render target of an engine.
+    ///
+    /// wgpu keeps the view alive while the bind group exists. Such textures can't be
+    /// written to or read back, and [`Self::texture`] and [`Self::view`] panic. They are
+    /// drawn with regular bind groups in the bindless mode.
+    pub fn from_view(
+        device: &Device,
+        renderer: &Renderer,
+        view: &TextureView,
+        config: &RawTextureConfig,
+        size: Extent3d,
+    ) -> Self {
+        let kind = config.kind;
+        let params = kind
+            .has_params()
+            .then(|| Arc::new(create_texture_params(device)));
+        let sampler = renderer.texture_sampler(device, kind, &config.sampler_desc);
+        let bind_group = create_texture_bind_group(
+            device,
+            renderer,
+            config.label,
+            kind,
+            view,
+            &sampler,
+            params.as_deref(),
+            None,
+        );
+
+        Self {
+            texture: None,
+            view: None,
+            sampler: Some(sampler),
+            bind_group: Arc::new(bind_group),
+            kind,
+            params,
             size,
+            view_desc: None,
+            style: None,
+            uv_rect: None,
+            label: config.label.map(str::to_owned),
+            mipmaps_stale: None,
+            view_formats: Vec::new(),
         }
     }
 
     /// Create a new GPU texture width the specified `config`.
     pub fn new(device: &Device, renderer: &Renderer, config: TextureConfig) -> Self {
+        let format = config.format.unwrap_or(renderer.config.texture_format);
+        let required_features = format.required_features();
+        assert!(
+            device.features().contains(required_features),
+            "{format:?} textures require {required_features:?}"
+        );
+        if format.is_compressed() {
+            let (block_width, block_height) = format.block_dimensions();
+            assert!(
+                config.size.width.is_multiple_of(block_width)
+                    && config.size.height.is_multiple_of(block_height),
+                "the size of {format:?} textures must be a multiple of {block_width}x{block_height}"
+            );
+        }
+
         // Create the wgpu texture.
+        let mut view_desc = config.view_desc.map(|desc| TextureViewDescriptor {
+            label: None,
+            ..desc
+        });
+        // 2D kinds display the first layer of textures with several array layers, the
+        // others can be displayed with `Texture::layer_view`.
+        if config.dimension == TextureDimension::D2
+            && config.size.depth_or_array_layers > 1
+            && config.kind.view_dimension() == TextureViewDimension::D2
+        {
+            let desc = view_desc.get_or_insert_with(Default::default);
+            if desc
+                .dimension
+                .is_none_or(|dimension| dimension == TextureViewDimension::D2)
+            {
+                desc.array_layer_count.get_or_insert(1);
+            }
+        }
+        let mut view_formats = vec![format];
+        view_formats.extend(view_desc.as_ref().and_then(|desc| desc.format));
+        view_formats.extend_from_slice(config.view_formats);
+        view_formats.dedup();
         let texture = Arc::new(device.create_texture(&TextureDescriptor {
             label: config.label,
             size: config.size,
             mip_level_count: config.mip_level_count,
             sample_count: config.sample_count,
             dimension: config.dimension,
-            format: config.format.unwrap_or(renderer.config.texture_format),
+            format,
             usage: config.usage,
-            view_formats: &[config.format.unwrap_or(renderer.config.texture_format)],
+            view_formats: &view_formats,
         }));
 
         // Extract the texture view.
-        let view = Arc::new(texture.create_view(&TextureViewDescriptor::default()));
+        let view = Arc::new(texture.create_view(&config.kind.view_desc(view_desc.as_ref())));
 
         // Create the texture sampler.
-        let sampler = device.create_sampler(&config.sampler_desc);
+        let sampler = renderer.texture_sampler(device, config.kind, &config.sampler_desc);
 
         // Create the texture bind group from the layout.
-        let bind_group = Arc::new(device.create_bind_group(&BindGroupDescriptor {
-            label: config.label,
-            layout: &renderer.texture_layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(&view),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&sampler),
-                },
-            ],
-        }));
+        let params = config
+            .kind
+            .has_params()
+            .then(|| Arc::new(create_texture_params(device)));
+        let bind_group = Arc::new(create_texture_bind_group(
+            device,
+            renderer,
+            config.label,
+            config.kind,
+            &view,
+            &sampler,
+            params.as_deref(),
+            None,
+        ));
 
         Self {
-            texture,
-            view,
+            texture: Some(texture),
+            view: Some(view),
+            sampler: Some(sampler),
             bind_group,
+            kind: config.kind,
+            params,
             size: config.size,
+            view_desc,
+            style: None,
+            uv_rect: None,
+            label: config.label.map(str::to_owned),
+            mipmaps_stale: (config.auto_mipmaps && config.mip_level_count > 1)
+                .then(|| Arc::new(AtomicBool::new(false))),
+            view_formats,
+        }
+    }
+
+    /// Create a texture displaying the same `wgpu::Texture` through a view of `format`,
+    /// e.g. an `Rgba8Unorm` texture holding sRGB colors through an `Rgba8UnormSrgb` view,
+    /// so it isn't displayed washed out.
+    ///
+    /// `format` has to be one of the [`TextureConfig::view_formats`] of the texture. The
+    /// view otherwise matches the one of this texture.
+    pub fn with_view_format(
+        &self,
+        device: &Device,
+        renderer: &Renderer,
+        format: TextureFormat,
+    ) -> Self {
+        assert!(
+            self.view_formats.contains(&format),
+            "{format:?} isn't a view format of the texture"
+        );
+        let view_desc = TextureViewDescriptor {
+            format: Some(format),
+            ..self.view_desc.clone().unwrap_or_default()
+        };
+        self.with_view(device, renderer, self.kind, view_desc)
+    }
+
+    /// Create a texture displaying the `aspect` of the same `wgpu::Texture`, e.g. to
+    /// register both the depth and the stencil of a depth-stencil texture.
+    ///
+    /// `TextureAspect::DepthOnly` is displayed as [`TextureKind::Depth`] and
+    /// `TextureAspect::StencilOnly` as [`TextureKind::Uint`], other aspects keep the kind
+    /// of this texture. The view otherwise matches the one of this texture.
+    pub fn for_aspect(&self, device: &Device, renderer: &Renderer, aspect: TextureAspect) -> Self {
+        let kind = match aspect {
+            TextureAspect::DepthOnly => TextureKind::Depth,
+            TextureAspect::StencilOnly => TextureKind::Uint,
+            _ => self.kind,
+        };
+        let view_desc = TextureViewDescriptor {
+            aspect,
+            ..self.view_desc.clone().unwrap_or_default()
+        };
+        self.with_view(device, renderer, kind, view_desc)
+    }
+
+    /// Create a texture displaying the array layer `layer` of the same `wgpu::Texture`
+    /// as a 2D texture, e.g. a single cascade of a shadow map or a face of a cube map.
+    ///
+    /// [`TextureKind::Array`] and [`TextureKind::Cube`] textures are displayed as
+    /// [`TextureKind::Filterable`], other kinds are kept.
+    pub fn layer_view(&self, device: &Device, renderer: &Renderer, layer: u32) -> Self {
+        assert!(
+            self.kind != TextureKind::Volume && layer < self.size.depth_or_array_layers,
+            "the texture has no array layer {layer}"
+        );
+        let kind = match self.kind {
+            TextureKind::Array | TextureKind::Cube => TextureKind::Filterable,
+            kind => kind,
+        };
+        let view_desc = TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..self.view_desc.clone().unwrap_or_default()
+        };
+        self.with_view(device, renderer, kind, view_desc)
+    }
+
+    /// Create a texture of `kind` with a new view of the same `wgpu::Texture`.
+    fn with_view(
+        &self,
+        device: &Device,
+        renderer: &Renderer,
+        kind: TextureKind,
+        view_desc: TextureViewDescriptor<'static>,
+    ) -> Self {
+        let view = Arc::new(
+            self.texture()
+                .create_view(&kind.view_desc(Some(&view_desc))),
+        );
+        let sampler =
+            renderer.texture_sampler(device, kind, &TextureConfig::default().sampler_desc);
+        let params = kind
+            .has_params()
+            .then(|| Arc::new(create_texture_params(device)));
+        let bind_group = Arc::new(create_texture_bind_group(
+            device,
+            renderer,
+            self.label.as_deref(),
+            kind,
+            &view,
+            &sampler,
+            params.as_deref(),
+            None,
+        ));
+
+        Self {
+            texture: self.texture.clone(),
+            view: Some(view),
+            sampler: Some(sampler),
+            bind_group,
+            kind,
+            params,
+            size: self.size,
+            view_desc: Some(view_desc),
+            style: None,
+            uv_rect: None,
+            label: self.label.clone(),
+            mipmaps_stale: self.mipmaps_stale.clone(),
+            view_formats: self.view_formats.clone(),
         }
     }
 
     /// Write `data` to the texture.
     ///
-    /// - `data`: 32-bit RGBA bitmap data.
+    /// - `data`: Tightly packed bitmap data in the format of the texture, for
+    ///   compressed formats one row of blocks after another.
     /// - `width`: The width of the source bitmap (`data`) in pixels.
     /// - `height`: The height of the source bitmap (`data`) in pixels.
     pub fn write(&self, queue: &Queue, data: &[u8], width: u32, height: u32) {
+        self.write_rows(queue, 0, data, width, 0, height);
+    }
+
+    /// Write 16-bit per channel `data` to a texture with a 16-bit format, e.g.
+    /// `Rgba16Unorm` for high bit depth images, like [`Self::write`].
+    pub fn write_u16(&self, queue: &Queue, data: &[u16], width: u32, height: u32) {
+        self.write(queue, bytemuck::cast_slice(data), width, height);
+    }
+
+    /// Convert `data` to half floats and write it to a texture with the `R16Float`,
+    /// `Rg16Float` or `Rgba16Float` format, like [`Self::write`].
+    pub fn write_f32_as_f16(&self, queue: &Queue, data: &[f32], width: u32, height: u32) {
+        assert!(
+            matches!(
+                self.texture().format(),
+                TextureFormat::R16Float | TextureFormat::Rg16Float | TextureFormat::Rgba16Float
+            ),
+            "the texture doesn't have a half float format"
+        );
+        let data: Vec<u16> = data.iter().copied().map(util::f32_to_f16).collect();
+        self.write_u16(queue, &data, width, height);
+    }
+
+    /// Write `data` to the mip level `level` of a texture created with a
+    /// `mip_level_count` above `level`.
+    ///
+    /// - `data`: Tightly packed bitmap data in the format of the texture.
+    /// - `width`: The width of the mip level in pixels.
+    /// - `height`: The height of the mip level in pixels.
+    pub fn write_mip(&self, queue: &Queue, level: u32, data: &[u8], width: u32, height: u32) {
+        self.write_rows(queue, level, data, width, 0, height);
+    }
+
+    /// Write `height` rows of tightly packed `data` to mip level `level`, starting at row
+    /// `first_row`.
+    fn write_rows(
+        &self,
+        queue: &Queue,
+        level: u32,
+        data: &[u8],
+        width: u32,
+        first_row: u32,
+        height: u32,
+    ) {
+        profile_scope!("imgui-wgpu texture upload");
+
+        if level == 0 {
+            self.mark_mipmaps_stale();
+        }
+        let (extent, bytes_per_row, rows) = self.copy_layout(width, height);
         queue.write_texture(
             // destination (sub)texture
             ImageCopyTexture {
-                texture: &self.texture,
-                mip_level: 0,
-                origin: Origin3d { x: 0, y: 0, z: 0 },
+                texture: self.texture(),
+                mip_level: level,
+                origin: Origin3d {
+                    x: 0,
+                    y: first_row,
+                    z: 0,
+                },
                 aspect: TextureAspect::All,
             },
             // source bitmap data
@@ -230,74 +1192,843 @@ impl Texture {
             // layout of the source bitmap
             ImageDataLayout {
                 offset: 0,
-                bytes_per_row: Some(width * 4),
-                rows_per_image: Some(height),
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(rows),
             },
             // size of the source bitmap
-            Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
+            extent,
         );
     }
 
-    /// The width of the texture in pixels.
-    pub fn width(&self) -> u32 {
-        self.size.width
-    }
+    /// Record a copy of `data` to the texture into `encoder`, like [`Self::write`] but
+    /// ordered with the other commands of the encoder.
+    ///
+    /// The rows are copied to a staging buffer padded to `COPY_BYTES_PER_ROW_ALIGNMENT`,
+    /// so `data` can stay tightly packed for any width.
+    pub fn write_staged(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) {
+        profile_scope!("imgui-wgpu texture upload");
 
-    /// The height of the texture in pixels.
-    pub fn height(&self) -> u32 {
-        self.size.height
-    }
+        let (extent, bytes_per_row, rows) = self.copy_layout(width, height);
+        let padded_bytes_per_row = bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+        if rows == 0 || bytes_per_row == 0 {
+            return;
+        }
 
-    /// The depth of the texture.
-    pub fn depth(&self) -> u32 {
-        self.size.depth_or_array_layers
-    }
+        let staging_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("imgui-wgpu texture staging buffer"),
+            size: (padded_bytes_per_row * rows) as u64,
+            usage: BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        pad_rows(
+            data,
+            &mut staging_buffer.slice(..).get_mapped_range_mut(),
+            bytes_per_row,
+            padded_bytes_per_row,
+        );
+        staging_buffer.unmap();
 
-    /// The size of the texture in pixels.
-    pub fn size(&self) -> Extent3d {
-        self.size
+        encoder.copy_buffer_to_texture(
+            ImageCopyBuffer {
+                buffer: &staging_buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(rows),
+                },
+            },
+            ImageCopyTexture {
+                texture: self.texture(),
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            extent,
+        );
     }
 
-    /// The underlying `wgpu::Texture`.
-    pub fn texture(&self) -> &wgpu::Texture {
-        &self.texture
+    /// The size of the part of a staging buffer used by [`Self::write_with_encoder`] to
+    /// upload a bitmap of `width` and `height` pixels.
+    pub fn staging_size(&self, width: u32, height: u32) -> BufferAddress {
+        let (_, bytes_per_row, rows) = self.copy_layout(width, height);
+        bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT) as BufferAddress
+            * rows as BufferAddress
     }
 
-    /// The `wgpu::TextureView` of the underlying texture.
-    pub fn view(&self) -> &wgpu::TextureView {
-        &self.view
-    }
-}
+    /// Record a copy of `data` to the texture into `encoder`, staging it in the
+    /// application's own `staging` buffer instead of creating one like
+    /// [`Self::write_staged`].
+    ///
+    /// `data` is written to `staging` at `offset` with `queue.write_buffer`, so it lands
+    /// before the commands of `encoder` run once they are submitted to `queue`. Several
+    /// uploads recorded into the same encoder need distinct parts of `staging`: the
+    /// returned offset is the end of the part used by this one, see
+    /// [`Self::staging_size`]. `staging` needs `BufferUsages::COPY_DST | COPY_SRC`, and
+    /// `offset` has to be a multiple of `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_with_encoder(
+        &self,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        staging: &Buffer,
+        offset: BufferAddress,
+        data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> BufferAddress {
+        profile_scope!("imgui-wgpu texture upload");
 
-/// Configuration for the renderer.
-pub struct RendererConfig<'s> {
-    pub texture_format: TextureFormat,
-    pub depth_format: Option<TextureFormat>,
-    pub sample_count: u32,
-    pub shader: Option<ShaderModuleDescriptor<'s>>,
-    pub vertex_shader_entry_point: Option<&'s str>,
-    pub fragment_shader_entry_point: Option<&'s str>,
-}
+        let (extent, bytes_per_row, rows) = self.copy_layout(width, height);
+        let padded_bytes_per_row = bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+        let Some(size) = BufferSize::new(self.staging_size(width, height)) else {
+            return offset;
+        };
+        assert!(
+            offset + size.get() <= staging.size(),
+            "the staging buffer is too small for the upload"
+        );
 
-impl<'s> RendererConfig<'s> {
-    /// Create a new renderer config with custom shaders.
-    pub fn with_shaders(shader: ShaderModuleDescriptor<'s>) -> Self {
-        RendererConfig {
-            texture_format: TextureFormat::Rgba8Unorm,
-            depth_format: None,
-            sample_count: 1,
-            shader: Some(shader),
-            vertex_shader_entry_point: Some(VS_ENTRY_POINT),
-            fragment_shader_entry_point: Some(FS_ENTRY_POINT_LINEAR),
-        }
+        pad_rows(
+            data,
+            &mut queue
+                .write_buffer_with(staging, offset, size)
+                .expect("the staging buffer can't be written"),
+            bytes_per_row,
+            padded_bytes_per_row,
+        );
+        encoder.copy_buffer_to_texture(
+            ImageCopyBuffer {
+                buffer: staging,
+                layout: ImageDataLayout {
+                    offset,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(rows),
+                },
+            },
+            ImageCopyTexture {
+                texture: self.texture(),
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            extent,
+        );
+        offset + size.get()
     }
-}
 
-impl Default for RendererConfig<'_> {
+    /// Record a copy of the whole first mip level, with all its layers, from `buffer` to
+    /// the texture into `encoder`, e.g. to display the output of a compute pass without
+    /// reading it back to the CPU.
+    ///
+    /// - `layout`: Where the data starts in `buffer`, its `bytes_per_row` has to be a
+    ///   multiple of `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    ///
+    /// `buffer` needs `BufferUsages::COPY_SRC`.
+    pub fn write_from_buffer(
+        &self,
+        encoder: &mut CommandEncoder,
+        buffer: &Buffer,
+        layout: ImageDataLayout,
+    ) {
+        let (extent, _, _) = self.copy_layout(self.size.width, self.size.height);
+        encoder.copy_buffer_to_texture(
+            ImageCopyBuffer { buffer, layout },
+            ImageCopyTexture {
+                texture: self.texture(),
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            Extent3d {
+                depth_or_array_layers: self.size.depth_or_array_layers,
+                ..extent
+            },
+        );
+    }
+
+    /// Record a copy of the region of the first mip level of `source` starting at
+    /// `src_origin` with size `extent` to `dst_origin` of this texture into `encoder`,
+    /// e.g. to compose thumbnails or atlases on the GPU.
+    ///
+    /// `source` needs `TextureUsages::COPY_SRC` and this texture `TextureUsages::COPY_DST`.
+    /// Their formats have to match, apart from the sRGB suffix, and so do their sample
+    /// counts. The region has to fit into both textures and, for compressed formats, be
+    /// aligned to the blocks.
+    pub fn copy_from(
+        &self,
+        encoder: &mut CommandEncoder,
+        source: &Texture,
+        src_origin: Origin3d,
+        extent: Extent3d,
+        dst_origin: Origin3d,
+    ) {
+        let (src, dst) = (source.texture(), self.texture());
+        assert!(
+            src.usage().contains(TextureUsages::COPY_SRC),
+            "the source texture needs TextureUsages::COPY_SRC"
+        );
+        assert!(
+            dst.usage().contains(TextureUsages::COPY_DST),
+            "the destination texture needs TextureUsages::COPY_DST"
+        );
+        let format = dst.format();
+        assert!(
+            src.format().remove_srgb_suffix() == format.remove_srgb_suffix(),
+            "can't copy {:?} to {format:?}",
+            src.format()
+        );
+        assert_eq!(
+            src.sample_count(),
+            dst.sample_count(),
+            "the textures must have the same sample count"
+        );
+        let fits = |origin: Origin3d, size: Extent3d| {
+            origin.x + extent.width <= size.width
+                && origin.y + extent.height <= size.height
+                && origin.z + extent.depth_or_array_layers <= size.depth_or_array_layers
+        };
+        assert!(
+            fits(src_origin, src.size()) && fits(dst_origin, dst.size()),
+            "the region doesn't fit into both textures"
+        );
+        let (block_width, block_height) = format.block_dimensions();
+        assert!(
+            [src_origin.x, dst_origin.x, extent.width]
+                .iter()
+                .all(|value| value.is_multiple_of(block_width))
+                && [src_origin.y, dst_origin.y, extent.height]
+                    .iter()
+                    .all(|value| value.is_multiple_of(block_height)),
+            "the region must be aligned to the {block_width}x{block_height} blocks of {format:?}"
+        );
+
+        encoder.copy_texture_to_texture(
+            ImageCopyTexture {
+                texture: src,
+                mip_level: 0,
+                origin: src_origin,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyTexture {
+                texture: dst,
+                mip_level: 0,
+                origin: dst_origin,
+                aspect: TextureAspect::All,
+            },
+            extent,
+        );
+    }
+
+    /// Record the generation of all mip levels after the first into `encoder`, so the
+    /// texture doesn't shimmer when it is shown scaled down.
+    ///
+    /// Textures with `TextureUsages::STORAGE_BINDING` and a format supported by storage
+    /// textures, like `Rgba8Unorm` or `Rgba16Float`, are downsampled in a single compute
+    /// pass, which is much faster for large images. Otherwise each level is rendered from
+    /// the previous one with linear filtering, which requires a renderable, filterable
+    /// format and `TextureUsages::RENDER_ATTACHMENT`. Either way only 2D textures are
+    /// supported and the pipeline is created on every call.
+    pub fn generate_mipmaps(&self, device: &Device, encoder: &mut CommandEncoder) {
+        profile_scope!("imgui-wgpu generate mipmaps");
+
+        let texture = self.texture();
+        let storage_format = mipmap::storage_format(device, texture.format())
+            .filter(|_| texture.usage().contains(TextureUsages::STORAGE_BINDING));
+        match storage_format {
+            Some(storage_format) => mipmap::compute(device, encoder, texture, storage_format),
+            None => mipmap::blit(device, encoder, texture),
+        }
+    }
+
+    /// Have the renderer regenerate the mip levels after the first before the next frame,
+    /// if the texture was created with [`TextureConfig::auto_mipmaps`].
+    fn mark_mipmaps_stale(&self) {
+        if let Some(stale) = &self.mipmaps_stale {
+            stale.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The copy size, bytes per row and number of rows of tightly packed data of a
+    /// `width` by `height` image in the format of the texture, or `None` if the texture
+    /// doesn't own a `wgpu::Texture`.
+    fn try_copy_layout(&self, width: u32, height: u32) -> Option<(Extent3d, u32, u32)> {
+        // Compressed formats are copied in whole blocks, including the partial blocks at
+        // the edges of mip levels that aren't a multiple of the block size.
+        let format = self.try_texture()?.format();
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format
+            .block_copy_size(None)
+            .expect("the texture format can't be written with a single aspect");
+        let width = width.next_multiple_of(block_width);
+        let height = height.next_multiple_of(block_height);
+        let extent = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        Some((
+            extent,
+            width / block_width * block_size,
+            height / block_height,
+        ))
+    }
+
+    /// Like [`Self::try_copy_layout`], panicking like [`Self::texture`].
+    fn copy_layout(&self, width: u32, height: u32) -> (Extent3d, u32, u32) {
+        self.try_copy_layout(width, height)
+            .expect("the texture doesn't own a wgpu::Texture")
+    }
+
+    /// Write `data` to the region of the texture starting at `origin` with size `extent`,
+    /// e.g. to only update the part of a large texture that changed.
+    ///
+    /// - `layout`: The layout of `data`, whose `bytes_per_row` depends on the format.
+    pub fn write_region(
+        &self,
+        queue: &Queue,
+        data: &[u8],
+        origin: Origin3d,
+        extent: Extent3d,
+        layout: ImageDataLayout,
+    ) {
+        profile_scope!("imgui-wgpu texture upload");
+
+        self.mark_mipmaps_stale();
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: self.texture(),
+                mip_level: 0,
+                origin,
+                aspect: TextureAspect::All,
+            },
+            data,
+            layout,
+            extent,
+        );
+    }
+
+    /// Select the layer displayed by textures of kind [`TextureKind::Array`].
+    ///
+    /// This has no effect on other kinds of textures.
+    pub fn set_layer(&self, queue: &Queue, layer: u32) {
+        if let (TextureKind::Array, Some(params)) = (self.kind, &self.params) {
+            queue.write_buffer(params, 0, bytemuck::bytes_of(&layer));
+        }
+    }
+
+    /// Select how textures of kind [`TextureKind::Cube`] are unfolded.
+    ///
+    /// This has no effect on other kinds of textures.
+    pub fn set_cube_projection(&self, queue: &Queue, projection: CubeProjection) {
+        if let (TextureKind::Cube, Some(params)) = (self.kind, &self.params) {
+            queue.write_buffer(params, 0, bytemuck::bytes_of(&(projection as u32)));
+        }
+    }
+
+    /// Select the Z slice displayed by textures of kind [`TextureKind::Volume`], from 0
+    /// for the front to 1 for the back. Slices in between are filtered like the texture's
+    /// sampler filters in X and Y.
+    ///
+    /// This has no effect on other kinds of textures.
+    pub fn set_slice(&self, queue: &Queue, slice: f32) {
+        if let (TextureKind::Volume, Some(params)) = (self.kind, &self.params) {
+            queue.write_buffer(params, 0, bytemuck::bytes_of(&slice));
+        }
+    }
+
+    /// Linearize the values of textures of kind [`TextureKind::Depth`], which were
+    /// rendered with a perspective projection from `near` to `far`. Reversed depth is
+    /// supported by swapping both. Passing equal values displays the raw depth.
+    ///
+    /// This has no effect on other kinds of textures.
+    pub fn set_depth_range(&self, queue: &Queue, near: f32, far: f32) {
+        if let (TextureKind::Depth, Some(params)) = (self.kind, &self.params) {
+            queue.write_buffer(params, 0, bytemuck::bytes_of(&[near, far]));
+        }
+    }
+
+    /// The width of the texture in pixels.
+    pub fn width(&self) -> u32 {
+        self.size.width
+    }
+
+    /// The height of the texture in pixels.
+    pub fn height(&self) -> u32 {
+        self.size.height
+    }
+
+    /// The depth of the texture.
+    pub fn depth(&self) -> u32 {
+        self.size.depth_or_array_layers
+    }
+
+    /// The size of the texture in pixels.
+    pub fn size(&self) -> Extent3d {
+        self.size
+    }
+
+    /// The bind group the texture is drawn with, e.g. to draw it with a custom pipeline.
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    /// The label of the texture from its config, if it has one.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// The underlying `wgpu::Texture`.
+    ///
+    /// Panics if the texture was created with [`Self::from_view`] or
+    /// [`Renderer::insert_region`], see [`Self::try_texture`].
+    pub fn texture(&self) -> &wgpu::Texture {
+        self.try_texture()
+            .expect("the texture doesn't own a wgpu::Texture")
+    }
+
+    /// The underlying `wgpu::Texture`, or `None` if the texture was created with
+    /// [`Self::from_view`] or [`Renderer::insert_region`].
+    ///
+    /// The methods writing, copying or reading the texture panic like [`Self::texture`]
+    /// for these textures.
+    pub fn try_texture(&self) -> Option<&wgpu::Texture> {
+        self.texture.as_deref()
+    }
+
+    /// The `wgpu::TextureView` of the underlying texture.
+    ///
+    /// Panics if the texture was created with [`Self::from_view`], see
+    /// [`Self::try_view`].
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.try_view()
+            .expect("textures created from a view don't own their wgpu::TextureView")
+    }
+
+    /// The `wgpu::TextureView` of the underlying texture, or `None` if the texture was
+    /// created with [`Self::from_view`].
+    pub fn try_view(&self) -> Option<&wgpu::TextureView> {
+        self.view.as_deref()
+    }
+
+    /// Rebuild the bind group with a sampler created from `sampler_desc`, e.g. to switch
+    /// between nearest and linear filtering of a displayed image.
+    ///
+    /// The filtering is adjusted to the kind of the texture like in [`Self::new`].
+    /// Textures created with [`Self::from_view`] can't change their sampler.
+    pub fn set_sampler(
+        &mut self,
+        device: &Device,
+        renderer: &Renderer,
+        sampler_desc: &SamplerDescriptor,
+    ) {
+        assert!(
+            self.kind != TextureKind::Nv12,
+            "video textures can't change their sampler"
+        );
+        let sampler_desc = self.kind.sampler_desc(sampler_desc);
+        self.sampler = Some(renderer.resources.samplers.get(device, &sampler_desc));
+        self.rebuild_bind_group(device, renderer);
+    }
+
+    /// Tint the texture or display only a part of it, see [`TextureStyle`].
+    ///
+    /// The style is applied by the built-in shaders, custom shaders can read it from
+    /// binding 4 of the texture bind group. The uniform buffer holding it is created on
+    /// the first call, textures created with [`Self::from_view`] can't be styled.
+    pub fn set_style(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        style: TextureStyle,
+    ) {
+        assert!(
+            self.kind != TextureKind::Nv12,
+            "video textures can't be styled"
+        );
+        match &self.style {
+            Some(buffer) => queue.write_buffer(buffer, 0, bytemuck::bytes_of(&style.to_uniform())),
+            None => {
+                self.style = Some(Arc::new(create_texture_style(device, style)));
+                self.rebuild_bind_group(device, renderer);
+            }
+        }
+    }
+
+    /// Recreate the bind group after the view, sampler or style changed.
+    fn rebuild_bind_group(&mut self, device: &Device, renderer: &Renderer) {
+        // Textures created from raw parts with their own bind group have no sampler.
+        let kind = self.kind;
+        let sampler = self.sampler.get_or_insert_with(|| {
+            renderer.texture_sampler(device, kind, &TextureConfig::default().sampler_desc)
+        });
+        let view = self
+            .view
+            .as_deref()
+            .expect("textures created from a view can't be changed");
+        self.bind_group = Arc::new(create_texture_bind_group(
+            device,
+            renderer,
+            self.label.as_deref(),
+            kind,
+            view,
+            sampler,
+            self.params.as_deref(),
+            self.style.as_deref(),
+        ));
+    }
+
+    /// Recreate the texture with a new `size`, keeping its format, usage, mip level count,
+    /// sample count, dimension, sampler and kind. The contents are not preserved, and
+    /// textures created with [`Self::from_view`] or [`Renderer::insert_region`] can't be
+    /// resized.
+    ///
+    /// Use [`Renderer::resize_texture`] to resize a texture inserted into
+    /// [`Renderer::textures`] without removing it.
+    pub fn resize(&mut self, device: &Device, renderer: &Renderer, size: Extent3d) {
+        assert!(
+            self.kind != TextureKind::Nv12,
+            "use VideoTexture::create_texture for a new video size"
+        );
+        if size == self.size {
+            return;
+        }
+
+        let old = self.texture();
+        let format = old.format();
+        let texture = device.create_texture(&TextureDescriptor {
+            label: self.label.as_deref(),
+            size,
+            mip_level_count: old.mip_level_count(),
+            sample_count: old.sample_count(),
+            dimension: old.dimension(),
+            format,
+            usage: old.usage(),
+            view_formats: &self.view_formats,
+        });
+        let view = texture.create_view(&self.kind.view_desc(self.view_desc.as_ref()));
+        self.texture = Some(Arc::new(texture));
+        self.view = Some(Arc::new(view));
+        self.size = size;
+        self.rebuild_bind_group(device, renderer);
+    }
+
+    /// Split the texture into the `wgpu::Texture`, its view, the bind group and the size,
+    /// e.g. to use them in passes of the application.
+    ///
+    /// Both the texture and the view are `None` for textures created with
+    /// [`Self::from_view`], and the texture is `None` for aliases inserted with
+    /// [`Renderer::insert_region`]. Clone the texture first to keep displaying it.
+    #[allow(clippy::type_complexity)]
+    pub fn into_raw_parts(
+        self,
+    ) -> (
+        Option<Arc<wgpu::Texture>>,
+        Option<Arc<wgpu::TextureView>>,
+        Arc<BindGroup>,
+        Extent3d,
+    ) {
+        (self.texture, self.view, self.bind_group, self.size)
+    }
+
+    /// Destroy the underlying `wgpu::Texture` right away instead of when the last
+    /// reference to it is dropped.
+    ///
+    /// Returns `false` without destroying anything if the texture, its view, its bind
+    /// group or its buffers are still referenced elsewhere, e.g. by a clone in another
+    /// registry. The resources are then freed when the last reference is dropped.
+    pub fn destroy(self) -> bool {
+        let buffers = [&self.params, &self.style].into_iter().flatten();
+        let unique = self
+            .texture
+            .as_ref()
+            .is_none_or(|texture| Arc::strong_count(texture) == 1)
+            && self
+                .view
+                .as_ref()
+                .is_none_or(|view| Arc::strong_count(view) == 1)
+            && Arc::strong_count(&self.bind_group) == 1
+            && buffers.clone().all(|buffer| Arc::strong_count(buffer) == 1);
+        if !unique {
+            return false;
+        }
+        if let Some(texture) = &self.texture {
+            texture.destroy();
+        }
+        for buffer in buffers {
+            buffer.destroy();
+        }
+        true
+    }
+}
+
+/// Policy for releasing vertex and index buffer memory after a spike in usage.
+///
+/// Without a policy the buffers only ever grow, so a single huge UI frame keeps
+/// its memory allocated for the lifetime of the `RenderData`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BufferShrinkPolicy {
+    /// A buffer counts as under-used while the data uploaded to it is smaller than
+    /// this fraction of its size.
+    pub usage_threshold: f32,
+    /// The number of consecutive under-used frames after which the buffer is recreated
+    /// at the currently required size.
+    pub frames: u32,
+}
+
+impl Default for BufferShrinkPolicy {
+    /// Shrink a buffer if less than 25% of it was used for 120 frames.
+    fn default() -> Self {
+        Self {
+            usage_threshold: 0.25,
+            frames: 120,
+        }
+    }
+}
+
+impl BufferShrinkPolicy {
+    /// Record one frame of usage and return whether the buffer should be shrunk.
+    fn should_shrink(&self, buffer_size: usize, used: usize, underused_frames: &mut u32) -> bool {
+        if (used as f32) < buffer_size as f32 * self.usage_threshold {
+            *underused_frames += 1;
+        } else {
+            *underused_frames = 0;
+        }
+
+        // A policy of 0 frames shrinks after the first under-used frame, not every frame.
+        if *underused_frames >= self.frames.max(1) {
+            *underused_frames = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Configuration of the bindless texture-array mode.
+///
+/// In this mode all textures used by a frame are bound once as a `binding_array` and
+/// selected per draw command through a push constant. It requires the
+/// `TEXTURE_BINDING_ARRAY` and `PUSH_CONSTANTS` features and a `max_push_constant_size`
+/// of at least 4 bytes. The built-in shader is always used for this mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BindlessConfig {
+    /// The maximum number of distinct textures in a frame. Frames that use more
+    /// textures fall back to regular bind groups. This is clamped to the device limits.
+    pub max_textures: u32,
+}
+
+impl Default for BindlessConfig {
+    fn default() -> Self {
+        Self { max_textures: 64 }
+    }
+}
+
+/// The layout of a single vertex in the vertex buffer.
+///
+/// Only needs to be changed if `ImDrawVert` was patched (e.g. an extra UV channel or
+/// float colors), together with a custom shader matching the new layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VertexLayout {
+    /// The size of a single vertex in bytes.
+    pub array_stride: BufferAddress,
+    /// The attributes of a vertex.
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl Default for VertexLayout {
+    /// The layout of the stock `ImDrawVert`.
+    fn default() -> Self {
+        Self {
+            array_stride: size_of::<DrawVert>() as BufferAddress,
+            attributes: vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Unorm8x4].to_vec(),
+        }
+    }
+}
+
+/// How a draw command is rendered, see [`RendererConfig::draw_classifier`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DrawMode {
+    /// Alpha blended, used for all imgui geometry by default.
+    #[default]
+    Blended,
+    /// Without blending, for large opaque fills (e.g. heatmaps in profiling tools)
+    /// where blending only costs bandwidth.
+    Opaque,
+}
+
+/// Selects the [`DrawMode`] of a draw command, see [`RendererConfig::draw_classifier`].
+pub type DrawClassifier = Box<dyn Fn(&DrawList, &DrawCmdParams) -> DrawMode + Send + Sync>;
+
+/// A hook setting application bind groups on the render pass, see
+/// [`RendererConfig::bind_group_hook`].
+pub type BindGroupHook = Box<dyn Fn(&mut RenderPass<'_>) + Send + Sync>;
+
+/// A hook called before each draw command, see [`RendererConfig::draw_cmd_hook`].
+pub type DrawCmdHook =
+    Box<dyn Fn(&mut RenderPass<'_>, &DrawCmdParams, &ScissorRect, &Texture) + Send + Sync>;
+
+/// Configuration for the renderer.
+pub struct RendererConfig<'s> {
+    pub texture_format: TextureFormat,
+    pub depth_format: Option<TextureFormat>,
+    pub sample_count: u32,
+    pub shader: Option<ShaderModuleDescriptor<'s>>,
+    pub vertex_shader_entry_point: Option<&'s str>,
+    pub fragment_shader_entry_point: Option<&'s str>,
+    /// When set, vertex and index buffers are shrunk again after prolonged low usage.
+    pub buffer_shrink_policy: Option<BufferShrinkPolicy>,
+    /// Convert vertex colors from sRGB to linear in the vertex shader.
+    ///
+    /// Use this when theme colors are authored in sRGB but the render target is linear
+    /// (e.g. `Rgba16Float`) and the fragment shader outputs its colors unconverted, like
+    /// [`Self::new_srgb`]. With the linear output of [`Self::new`] this only moves the
+    /// conversion from the fragment to the vertex shader. Custom shaders must declare a
+    /// `LINEARIZE_VERTEX_COLORS: bool` override constant for this to take effect.
+    pub linearize_vertex_colors: bool,
+    /// When set and supported by the device, bind all textures of a frame as a single
+    /// texture array instead of switching bind groups for every draw command.
+    pub bindless: Option<BindlessConfig>,
+    /// Additional bind group layouts appended to the pipeline layout as groups 2 and up,
+    /// so custom shaders can access application data.
+    pub extra_bind_group_layouts: Vec<&'s BindGroupLayout>,
+    /// Called by `split_render` before drawing to set the bind groups matching
+    /// `extra_bind_group_layouts`.
+    pub bind_group_hook: Option<BindGroupHook>,
+    /// The layout of the vertex buffer.
+    pub vertex_layout: VertexLayout,
+    /// Called for every draw command to select its [`DrawMode`]. If not set, everything
+    /// is drawn with blending.
+    pub draw_classifier: Option<DrawClassifier>,
+    /// Additional color targets written at locations 1 and up, e.g. an ID buffer for
+    /// picking or a UI mask. A custom fragment shader decides what is written to them.
+    ///
+    /// The bindless mode is disabled while additional targets are set, as its shader only
+    /// writes `@location(0)`, like the built-in shaders drawing textures of kinds other
+    /// than [`TextureKind::Filterable`], whose draws leave these targets unchanged. Can't
+    /// be combined with [`Self::dual_source_blending`].
+    pub additional_color_targets: Vec<Option<ColorTargetState>>,
+    /// Blend the main color target with a second fragment output as per-channel coverage,
+    /// for subpixel-antialiased text or advanced compositing.
+    ///
+    /// The fragment shader has to write a premultiplied color to `@location(0)` and the
+    /// coverage to `@location(0) @second_blend_source`, which the shaders selected by
+    /// [`Self::with_dual_source_blending`] do. Requires `Features::DUAL_SOURCE_BLENDING`.
+    pub dual_source_blending: bool,
+    /// Render to this many array layers at once with multiview. The vertex shader gets
+    /// the view index as `@builtin(view_index)`, which the shaders selected by
+    /// [`Self::with_multiview`] use to apply per-view transforms. At most [`MAX_VIEWS`]
+    /// views are supported and the bindless mode is disabled. Requires `Features::MULTIVIEW`.
+    pub multiview: Option<NonZeroU32>,
+    /// The winding order of front-facing triangles. imgui doesn't emit consistently
+    /// wound triangles, so this only matters together with [`Self::cull_mode`].
+    pub front_face: FrontFace,
+    /// Which triangles to cull, e.g. when rendering through a mirrored transform.
+    pub cull_mode: Option<Face>,
+    /// Disable depth clipping. Requires `Features::DEPTH_CLIP_CONTROL`.
+    pub unclipped_depth: bool,
+    /// Enable conservative rasterization. Requires `Features::CONSERVATIVE_RASTERIZATION`.
+    pub conservative: bool,
+    /// How clip rects are rounded to whole pixels, which matters for fractional scale factors.
+    pub scissor_rounding: ScissorRounding,
+    /// Called right before each draw command is drawn, with its parameters, scissor rect
+    /// and texture, to change dynamic state like the blend constant or to insert debug
+    /// markers. The hook must not change the pipeline, bind groups or scissor rect.
+    pub draw_cmd_hook: Option<DrawCmdHook>,
+    /// The sampler settings of textures that don't configure their own, e.g. to enable
+    /// anisotropic filtering for all images.
+    pub sampler_defaults: SamplerDefaults,
+    /// Draw a magenta and black checkerboard instead of textures that don't exist, instead
+    /// of failing the frame with [`RendererError::BadTexture`] or
+    /// [`RendererError::StaleTexture`]. The ids are listed by [`Renderer::missing_textures`].
+    pub missing_texture_placeholder: bool,
+    /// Upload the font atlas as `R8Unorm` instead of `Rgba8Unorm`, which cuts its memory
+    /// and upload size to a quarter, e.g. for large CJK atlases.
+    ///
+    /// The atlas is displayed with [`TextureStyle::alpha_mask`], so frames drawing text
+    /// don't use the bindless mode. Colored glyphs aren't supported.
+    pub alpha8_font_atlas: bool,
+    /// Display the font atlas as a signed distance field, for atlases whose glyphs were
+    /// rasterized as distance fields, e.g. by a custom font loader filling the atlas,
+    /// so text stays crisp when it is scaled, like in zoomable node editors.
+    pub font_distance_field: Option<DistanceField>,
+    /// Render text with subpixel antialiasing for horizontal RGB panels, which is more
+    /// legible at small sizes on standard-DPI monitors, by setting
+    /// [`TextureStyle::subpixel`] on the font atlas. Requires
+    /// [`Self::dual_source_blending`], see [`Self::with_subpixel_text`].
+    pub subpixel_text: bool,
+    /// Correct the coverage of text for blending in linear space, as on sRGB render
+    /// targets, where dark text on light backgrounds otherwise looks too thin and light
+    /// text on dark backgrounds too bold. Sets [`TextureStyle::coverage_gamma`] of the
+    /// font atlas to [`TEXT_COVERAGE_GAMMA`].
+    pub gamma_correct_text: bool,
+    /// Keep the pixels of the font atlas in CPU memory after uploading them, instead of
+    /// clearing them to save memory. [`Renderer::reload_font_texture`] then uploads the
+    /// atlas again without rebuilding the fonts, e.g. after the device was lost, and the
+    /// application can read the pixels through `FontAtlas::build_rgba32_texture`.
+    pub keep_font_atlas_data: bool,
+    /// The format of the font atlas, one of [`FONT_ATLAS_FORMATS`]. `Rgba8Unorm` by
+    /// default, or `R8Unorm` with [`Self::alpha8_font_atlas`], independent of
+    /// [`Self::texture_format`].
+    ///
+    /// Single channel formats are displayed with [`TextureStyle::alpha_mask`]. The atlas
+    /// is compressed to `Bc4RUnorm` on the CPU, which halves the memory of `R8Unorm`, and
+    /// falls back to `R8Unorm` without the `compress` feature, if the device lacks
+    /// `Features::TEXTURE_COMPRESSION_BC` or the atlas size isn't a multiple of 4.
+    pub font_atlas_format: Option<TextureFormat>,
+}
+
+impl<'s> RendererConfig<'s> {
+    /// Create a builder which validates the config, see [`RendererConfigBuilder`].
+    pub fn builder() -> RendererConfigBuilder<'s> {
+        RendererConfigBuilder::new()
+    }
+
+    /// Create a new renderer config with custom shaders.
+    pub fn with_shaders(shader: ShaderModuleDescriptor<'s>) -> Self {
+        RendererConfig {
+            texture_format: TextureFormat::Rgba8Unorm,
+            depth_format: None,
+            sample_count: 1,
+            shader: Some(shader),
+            vertex_shader_entry_point: Some(VS_ENTRY_POINT),
+            fragment_shader_entry_point: Some(FS_ENTRY_POINT_LINEAR),
+            buffer_shrink_policy: None,
+            linearize_vertex_colors: false,
+            bindless: None,
+            extra_bind_group_layouts: Vec::new(),
+            bind_group_hook: None,
+            vertex_layout: VertexLayout::default(),
+            draw_classifier: None,
+            additional_color_targets: Vec::new(),
+            dual_source_blending: false,
+            multiview: None,
+            front_face: FrontFace::Cw,
+            cull_mode: None,
+            unclipped_depth: false,
+            conservative: false,
+            scissor_rounding: ScissorRounding::default(),
+            draw_cmd_hook: None,
+            sampler_defaults: SamplerDefaults::default(),
+            missing_texture_placeholder: false,
+            alpha8_font_atlas: false,
+            font_distance_field: None,
+            subpixel_text: false,
+            gamma_correct_text: false,
+            keep_font_atlas_data: false,
+            font_atlas_format: None,
+        }
+    }
+}
+
+impl Default for RendererConfig<'_> {
     /// Create a new renderer config with precompiled default shaders outputting linear color.
     ///
     /// If you write to a Bgra8UnormSrgb framebuffer, this is what you want.
@@ -313,7 +2044,7 @@ impl RendererConfig<'_> {
     pub fn new() -> Self {
         RendererConfig {
             fragment_shader_entry_point: Some(FS_ENTRY_POINT_LINEAR),
-            ..Self::with_shaders(include_wgsl!("imgui.wgsl"))
+            ..Self::with_shaders(shaders::DEFAULT)
         }
     }
 
@@ -323,42 +2054,227 @@ impl RendererConfig<'_> {
     pub fn new_srgb() -> Self {
         RendererConfig {
             fragment_shader_entry_point: Some(FS_ENTRY_POINT_SRGB),
-            ..Self::with_shaders(include_wgsl!("imgui.wgsl"))
+            ..Self::with_shaders(shaders::DEFAULT)
+        }
+    }
+
+    /// Create a new renderer config for rendering to `format`, with precompiled default
+    /// shaders outputting linear color for sRGB formats and srgb color otherwise.
+    ///
+    /// Unlike [`Self::new`] and [`Self::new_srgb`] this also sets `texture_format`, so
+    /// pass the format the surface or render target is configured with.
+    pub fn for_surface_format(format: TextureFormat) -> Self {
+        let config = if format.is_srgb() {
+            Self::new()
+        } else {
+            Self::new_srgb()
+        };
+        RendererConfig {
+            texture_format: format,
+            ..config
+        }
+    }
+
+    /// Switch to the precompiled multiview shaders rendering to `views` array layers at
+    /// once, e.g. both eyes of an XR overlay. See [`Renderer::set_view_transforms`].
+    ///
+    /// Requires `Features::MULTIVIEW`.
+    pub fn with_multiview(self, views: NonZeroU32) -> Self {
+        RendererConfig {
+            shader: Some(shaders::MULTIVIEW),
+            multiview: Some(views),
+            ..self
+        }
+    }
+
+    /// Switch to the precompiled dual-source blending shaders, keeping the fragment entry point.
+    ///
+    /// Requires `Features::DUAL_SOURCE_BLENDING`.
+    pub fn with_dual_source_blending(self) -> Self {
+        RendererConfig {
+            shader: Some(shaders::DUAL_SOURCE),
+            dual_source_blending: true,
+            ..self
+        }
+    }
+
+    /// Switch to the precompiled dual-source blending shaders and render text with
+    /// subpixel antialiasing, see [`Self::subpixel_text`].
+    ///
+    /// Requires `Features::DUAL_SOURCE_BLENDING`.
+    pub fn with_subpixel_text(self) -> Self {
+        RendererConfig {
+            subpixel_text: true,
+            ..self.with_dual_source_blending()
         }
     }
 }
 
+/// The attachments of a render pass, see [`Renderer::split_render_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderTarget {
+    pub texture_format: TextureFormat,
+    pub sample_count: u32,
+    pub depth_format: Option<TextureFormat>,
+}
+
+/// GPU resources that were still referenced elsewhere when the renderer was destroyed,
+/// see [`Renderer::destroy`].
+#[derive(Clone, Debug, Default)]
+pub struct TeardownReport {
+    /// Textures whose texture, view, bind group or buffers are still referenced elsewhere,
+    /// which were left to be freed with their last reference.
+    pub referenced_textures: Vec<TextureId>,
+}
+
 pub struct RenderData {
     fb_size: [f32; 2],
     last_size: [f32; 2],
     last_pos: [f32; 2],
     vertex_buffer: Option<Buffer>,
     vertex_buffer_size: usize,
+    vertex_buffer_underused_frames: u32,
     index_buffer: Option<Buffer>,
     index_buffer_size: usize,
+    index_buffer_underused_frames: u32,
     draw_list_offsets: SmallVec<[(i32, u32); 4]>,
+    uniform_buffer: Buffer,
+    uniform_bind_group: BindGroup,
+    bindless: Option<Arc<BindlessBindings>>,
     render: bool,
 }
 
-pub struct Renderer {
-    pipeline: RenderPipeline,
-    uniform_buffer: Buffer,
-    uniform_bind_group: BindGroup,
+impl RenderData {
+    /// Destroy the vertex, index and uniform buffers right away instead of when they
+    /// are dropped.
+    pub fn destroy(self) {
+        for buffer in [
+            self.vertex_buffer,
+            self.index_buffer,
+            Some(self.uniform_buffer),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            buffer.destroy();
+        }
+    }
+}
+
+/// The textures used by a prepared frame in bindless mode.
+struct BindlessBindings {
+    bind_group: BindGroup,
+    slots: HashMap<TextureId, u32>,
+}
+
+/// The bindings of the previous frame, reused while the same textures are drawn.
+struct BindlessCache {
+    /// The ids, views and samplers of the textures in the array, in slot order.
+    textures: Vec<(TextureId, Arc<wgpu::TextureView>, Option<Arc<Sampler>>)>,
+    bindings: Arc<BindlessBindings>,
+}
+
+/// State of the render pass while `split_render` records the imgui draw commands.
+struct PassState<'r> {
+    render_data: &'r RenderData,
+    pipelines: &'r PipelineSet,
+    clip_off: [f32; 2],
+    clip_scale: [f32; 2],
+    // State set by previous draw commands, used to skip redundant state changes.
+    pipeline: Option<&'r RenderPipeline>,
+    texture_slot: Option<u32>,
+    bind_group: Option<&'r BindGroup>,
+    scissor: Option<ScissorRect>,
+    /// Where invalid draw commands are recorded instead of failing, for
+    /// [`Renderer::render_lossy`].
+    report: Option<&'r mut FrameReport>,
+    /// The index of the draw list being recorded.
+    draw_list_index: usize,
+}
+
+pub struct Renderer {
+    pipeline_source: PipelineSource,
+    pipelines: PipelineSet,
+    target_pipelines: Mutex<HashMap<RenderTarget, Arc<PipelineSet>>>,
+    /// The pipelines of [`Self::set_texture_shader`] and the kind of texture they were
+    /// created for.
+    texture_pipelines: HashMap<TextureId, (TextureKind, RenderPipeline)>,
+    wireframe: bool,
+    bindless: Option<BindlessPipeline>,
+    uniform_layout: BindGroupLayout,
+    view_transforms: [[[f32; 4]; 4]; MAX_VIEWS],
     /// Textures of the font atlas and all images.
-    pub textures: Textures<Texture>,
-    texture_layout: BindGroupLayout,
+    pub textures: TextureRegistry,
+    resources: Arc<TextureResources>,
+    shared_textures: Option<Arc<RwLock<TextureRegistry>>>,
     render_data: Option<RenderData>,
     config: RendererConfig<'static>,
+    callbacks: HashMap<RenderCallbackId, RenderCallback>,
+    next_callback_id: usize,
+    /// Textures removed again after the current frame, see [`Renderer::insert_frame_texture`].
+    frame_textures: Vec<TextureId>,
+    /// The font textures by the address of their font atlas, so contexts with their own
+    /// atlases can share a renderer, and contexts sharing an atlas share its texture.
+    font_textures: HashMap<usize, TextureId>,
+    /// The scale factors of the font atlases, see [`Renderer::handle_scale_factor_change`].
+    font_scales: HashMap<usize, f32>,
+    memory_budget: Option<(u64, MemoryBudgetCallback)>,
+    dropped_textures: DroppedTextures,
+    eviction: TextureEviction,
+    uploads: UploadQueue,
+    /// Drawn instead of missing textures if [`RendererConfig::missing_texture_placeholder`]
+    /// is set.
+    placeholder: Option<Texture>,
+    missing_textures: Mutex<Vec<TextureId>>,
 }
 
 impl Renderer {
     /// Create an entirely new imgui wgpu renderer.
+    ///
+    /// Fails with [`RendererError::InvalidConfig`] for configs that
+    /// [`RendererConfigBuilder::build`] rejects, with [`RendererError::MissingFeatures`] if
+    /// the config requires features the device doesn't have, and with the error of
+    /// [`Self::reload_font_texture`] if the font atlas can't be uploaded.
     pub fn new(
         imgui: &mut Context,
         device: &Device,
         queue: &Queue,
         config: RendererConfig,
-    ) -> Self {
+    ) -> RendererResult<Self> {
+        let resources = Arc::new(TextureResources::new(device));
+        Self::with_resources(imgui, device, queue, config, resources, None)
+    }
+
+    /// Create a renderer that draws the textures of `shared` in addition to its own, e.g.
+    /// one of several renderers for the windows of an application.
+    ///
+    /// Fails like [`Self::new`].
+    pub fn new_shared(
+        imgui: &mut Context,
+        device: &Device,
+        queue: &Queue,
+        config: RendererConfig,
+        shared: &SharedRendererResources,
+    ) -> RendererResult<Self> {
+        Self::with_resources(
+            imgui,
+            device,
+            queue,
+            config,
+            shared.resources.clone(),
+            Some(shared.textures.clone()),
+        )
+    }
+
+    fn with_resources(
+        imgui: &mut Context,
+        device: &Device,
+        queue: &Queue,
+        config: RendererConfig,
+        resources: Arc<TextureResources>,
+        shared_textures: Option<Arc<RwLock<TextureRegistry>>>,
+    ) -> RendererResult<Self> {
+        builder::validate(&config).map_err(RendererError::InvalidConfig)?;
         let RendererConfig {
             texture_format,
             depth_format,
@@ -366,48 +2282,1660 @@ impl Renderer {
             shader,
             vertex_shader_entry_point,
             fragment_shader_entry_point,
+            buffer_shrink_policy,
+            linearize_vertex_colors,
+            bindless,
+            extra_bind_group_layouts,
+            bind_group_hook,
+            vertex_layout,
+            draw_classifier,
+            additional_color_targets,
+            dual_source_blending,
+            multiview,
+            front_face,
+            cull_mode,
+            unclipped_depth,
+            conservative,
+            scissor_rounding,
+            draw_cmd_hook,
+            sampler_defaults,
+            missing_texture_placeholder,
+            alpha8_font_atlas,
+            font_distance_field,
+            subpixel_text,
+            gamma_correct_text,
+            keep_font_atlas_data,
+            font_atlas_format,
         } = config;
 
-        // Load shaders.
-        let shader_module = device.create_shader_module(shader.unwrap());
+        if dual_source_blending && !device.features().contains(Features::DUAL_SOURCE_BLENDING) {
+            return Err(RendererError::MissingFeatures(
+                Features::DUAL_SOURCE_BLENDING,
+            ));
+        }
+        if multiview.is_some() && !device.features().contains(Features::MULTIVIEW) {
+            return Err(RendererError::MissingFeatures(Features::MULTIVIEW));
+        }
+
+        // Load shaders.
+        let shader_module =
+            device.create_shader_module(shader.expect("RendererConfig::shader is required"));
+
+        // Create the uniform matrix buffer bind group layout.
+        let uniform_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        // Create the render pipeline layouts for each kind of texture.
+        let pipeline_layouts = resources
+            .layouts
+            .iter()
+            .map(|texture_layout| {
+                let bind_group_layouts: Vec<_> = [&uniform_layout, texture_layout]
+                    .into_iter()
+                    .chain(extra_bind_group_layouts.iter().copied())
+                    .collect();
+                device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                    label: Some("imgui-wgpu pipeline layout"),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[],
+                })
+            })
+            .collect();
+
+        // Only pass the override constant when needed, so custom shaders don't have to declare it.
+        let mut constants = HashMap::new();
+        if linearize_vertex_colors {
+            constants.insert(LINEARIZE_VERTEX_COLORS_CONSTANT.to_owned(), 1.0);
+        }
+
+        // Create the bindless layouts if requested and supported by the device. The
+        // bindless shader doesn't apply per-view transforms, so it can't do multiview,
+        // and only writes the main color target.
+        let bindless =
+            bindless.filter(|_| multiview.is_none() && additional_color_targets.is_empty());
+        let bindless = bindless.and_then(|bindless| {
+            BindlessPipeline::new(
+                device,
+                &uniform_layout,
+                &extra_bind_group_layouts,
+                bindless,
+                fragment_shader_entry_point == Some(FS_ENTRY_POINT_SRGB),
+            )
+        });
+
+        let config = RendererConfig {
+            texture_format,
+            depth_format,
+            sample_count,
+            shader: None,
+            vertex_shader_entry_point: None,
+            fragment_shader_entry_point: None,
+            buffer_shrink_policy,
+            linearize_vertex_colors,
+            bindless: None,
+            extra_bind_group_layouts: Vec::new(),
+            bind_group_hook,
+            vertex_layout,
+            draw_classifier,
+            additional_color_targets,
+            dual_source_blending,
+            multiview,
+            front_face,
+            cull_mode,
+            unclipped_depth,
+            conservative,
+            scissor_rounding,
+            draw_cmd_hook,
+            sampler_defaults,
+            missing_texture_placeholder,
+            alpha8_font_atlas,
+            font_distance_field,
+            subpixel_text,
+            gamma_correct_text,
+            keep_font_atlas_data,
+            font_atlas_format,
+        };
+
+        // Create the pipelines for the configured render target, pipelines for other
+        // targets are created on demand.
+        let pipeline_source = PipelineSource {
+            layouts: pipeline_layouts,
+            shader_module,
+            vertex_entry_point: vertex_shader_entry_point.map(str::to_owned),
+            fragment_entry_point: fragment_shader_entry_point.map(str::to_owned),
+            constants,
+        };
+        let pipelines = pipeline_source.create_pipelines(
+            device,
+            &config,
+            bindless.as_ref(),
+            RenderTarget {
+                texture_format,
+                sample_count,
+                depth_format,
+            },
+        );
+
+        let mut renderer = Self {
+            pipeline_source,
+            pipelines,
+            target_pipelines: Mutex::new(HashMap::new()),
+            texture_pipelines: HashMap::new(),
+            wireframe: false,
+            bindless,
+            uniform_layout,
+            view_transforms: [IDENTITY_MATRIX; MAX_VIEWS],
+            textures: TextureRegistry::new(),
+            resources,
+            shared_textures,
+            render_data: None,
+            callbacks: HashMap::new(),
+            next_callback_id: 0,
+            frame_textures: Vec::new(),
+            font_textures: HashMap::new(),
+            font_scales: HashMap::new(),
+            memory_budget: None,
+            dropped_textures: DroppedTextures::default(),
+            eviction: TextureEviction::default(),
+            uploads: UploadQueue::default(),
+            placeholder: None,
+            missing_textures: Mutex::new(Vec::new()),
+            config,
+        };
+        if renderer.config.missing_texture_placeholder {
+            renderer.placeholder = Some(placeholder::create_placeholder(device, queue, &renderer));
+        }
+
+        // Immediately load the font texture to the GPU.
+        renderer.reload_font_texture(imgui, device, queue)?;
+
+        Ok(renderer)
+    }
+
+    /// Start a new frame, before its draw data is prepared.
+    ///
+    /// This uploads the data of [`Self::enqueue_upload`] within the budget of the frame,
+    /// regenerates stale mipmaps, evicts textures that weren't drawn for too long, see
+    /// [`Self::set_texture_eviction`], and checks [`Self::set_memory_budget`].
+    ///
+    /// [`Self::render`] calls it for every frame. Applications using [`Self::prepare`] and
+    /// [`Self::split_render`], e.g. to draw several contexts with one renderer, call it
+    /// once per frame.
+    pub fn begin_frame(&mut self, queue: &Queue, device: &Device) {
+        self.process_uploads(queue);
+        self.regenerate_mipmaps(queue, device);
+        self.evict_unused_textures();
+        self.check_memory_budget();
+    }
+
+    /// Prepares buffers for the current imgui frame.  This must be
+    /// called before `Renderer::split_render`, and its output must
+    /// be passed to the render call.
+    pub fn prepare(
+        &self,
+        draw_data: &DrawData,
+        render_data: Option<RenderData>,
+        queue: &Queue,
+        device: &Device,
+    ) -> RenderData {
+        profile_scope!("imgui-wgpu prepare");
+
+        self.record_drawn_textures(draw_data);
+        self.missing_textures.lock().unwrap().clear();
+        let fb_width = draw_data.display_size[0] * draw_data.framebuffer_scale[0];
+        let fb_height = draw_data.display_size[1] * draw_data.framebuffer_scale[1];
+
+        let mut render_data = render_data.unwrap_or_else(|| {
+            let (uniform_buffer, uniform_bind_group) = self.create_uniform_buffer(device);
+            RenderData {
+                fb_size: [fb_width, fb_height],
+                last_size: [0.0, 0.0],
+                last_pos: [0.0, 0.0],
+                vertex_buffer: None,
+                vertex_buffer_size: 0,
+                vertex_buffer_underused_frames: 0,
+                index_buffer: None,
+                index_buffer_size: 0,
+                index_buffer_underused_frames: 0,
+                draw_list_offsets: SmallVec::<[_; 4]>::new(),
+                uniform_buffer,
+                uniform_bind_group,
+                bindless: None,
+                render: false,
+            }
+        });
+
+        // If the render area is <= 0, exit here and now.
+        if fb_width <= 0.0 || fb_height <= 0.0 {
+            render_data.render = false;
+            return render_data;
+        } else {
+            render_data.render = true;
+        }
+
+        // Only update matrices if the size or position changes
+        if (render_data.last_size[0] - draw_data.display_size[0]).abs() > f32::EPSILON
+            || (render_data.last_size[1] - draw_data.display_size[1]).abs() > f32::EPSILON
+            || (render_data.last_pos[0] - draw_data.display_pos[0]).abs() > f32::EPSILON
+            || (render_data.last_pos[1] - draw_data.display_pos[1]).abs() > f32::EPSILON
+        {
+            render_data.fb_size = [fb_width, fb_height];
+            render_data.last_size = draw_data.display_size;
+            render_data.last_pos = draw_data.display_pos;
+
+            let width = draw_data.display_size[0];
+            let height = draw_data.display_size[1];
+
+            let offset_x = draw_data.display_pos[0] / width;
+            let offset_y = draw_data.display_pos[1] / height;
+
+            // Create and update the transform matrix for the current frame.
+            // This is required to adapt to vulkan coordinates.
+            let matrix = [
+                [2.0 / width, 0.0, 0.0, 0.0],
+                [0.0, 2.0 / -height, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [-1.0 - offset_x * 2.0, 1.0 + offset_y * 2.0, 0.0, 1.0],
+            ];
+            queue.write_buffer(&render_data.uniform_buffer, 0, bytemuck::bytes_of(&matrix));
+        }
+        if self.config.multiview.is_some() {
+            let transforms = bytemuck::cast_slice(&self.view_transforms[..]);
+            queue.write_buffer(&render_data.uniform_buffer, 64, transforms);
+        }
+
+        render_data.draw_list_offsets.clear();
+
+        let mut vertex_count = 0;
+        let mut index_count = 0;
+        for draw_list in draw_data.draw_lists() {
+            render_data
+                .draw_list_offsets
+                .push((vertex_count as i32, index_count as u32));
+            vertex_count += draw_list.vtx_buffer().len();
+            index_count += draw_list.idx_buffer().len();
+        }
+
+        let mut vertices = Vec::with_capacity(vertex_count * std::mem::size_of::<DrawVertPod>());
+        let mut indices = Vec::with_capacity(index_count * std::mem::size_of::<DrawIdx>());
+
+        let vertex_stride = self.config.vertex_layout.array_stride as usize;
+        for draw_list in draw_data.draw_lists() {
+            if vertex_stride == size_of::<DrawVert>() {
+                // Safety: DrawVertPod is #[repr(transparent)] over DrawVert and DrawVert _should_ be Pod.
+                let vertices_pod: &[DrawVertPod] = unsafe { draw_list.transmute_vtx_buffer() };
+                vertices.extend_from_slice(bytemuck::cast_slice(vertices_pod));
+            } else {
+                // Safety: DrawList is #[repr(transparent)] over ImDrawList, whose vertex buffer
+                // holds `Size` vertices of the patched ImDrawVert described by the vertex layout.
+                let raw_vertices = unsafe {
+                    let raw = &*(draw_list as *const DrawList as *const imgui::sys::ImDrawList);
+                    std::slice::from_raw_parts(
+                        raw.VtxBuffer.Data as *const u8,
+                        raw.VtxBuffer.Size as usize * vertex_stride,
+                    )
+                };
+                vertices.extend_from_slice(raw_vertices);
+            }
+            indices.extend_from_slice(bytemuck::cast_slice(draw_list.idx_buffer()));
+        }
+
+        // Patched vertices don't necessarily keep the 4 byte alignment required for copies
+        vertices.resize(
+            vertices
+                .len()
+                .next_multiple_of(COPY_BUFFER_ALIGNMENT as usize),
+            0,
+        );
+
+        // Copies in wgpu must be padded to 4 byte alignment
+        indices.resize(
+            indices.len() + COPY_BUFFER_ALIGNMENT as usize
+                - indices.len() % COPY_BUFFER_ALIGNMENT as usize,
+            0,
+        );
+
+        // Check whether the buffers have been mostly empty for long enough to shrink them
+        let (shrink_index_buffer, shrink_vertex_buffer) = match self.config.buffer_shrink_policy {
+            Some(policy) => (
+                policy.should_shrink(
+                    render_data.index_buffer_size,
+                    indices.len(),
+                    &mut render_data.index_buffer_underused_frames,
+                ),
+                policy.should_shrink(
+                    render_data.vertex_buffer_size,
+                    vertices.len(),
+                    &mut render_data.vertex_buffer_underused_frames,
+                ),
+            ),
+            None => (false, false),
+        };
+
+        // If the buffer is not created, is too small for the new indices or should be shrunk, create a new buffer
+        if render_data.index_buffer.is_none()
+            || render_data.index_buffer_size < indices.len()
+            || shrink_index_buffer
+        {
+            let buffer = device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("imgui-wgpu index buffer"),
+                contents: &indices,
+                usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
+            });
+            render_data.index_buffer = Some(buffer);
+            render_data.index_buffer_size = indices.len();
+        } else if let Some(buffer) = render_data.index_buffer.as_ref() {
+            // The buffer is large enough for the new indices, so reuse it
+            queue.write_buffer(buffer, 0, &indices);
+        } else {
+            unreachable!()
+        }
+
+        // If the buffer is not created, is too small for the new vertices or should be shrunk, create a new buffer
+        if render_data.vertex_buffer.is_none()
+            || render_data.vertex_buffer_size < vertices.len()
+            || shrink_vertex_buffer
+        {
+            let buffer = device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("imgui-wgpu vertex buffer"),
+                contents: &vertices,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            });
+            render_data.vertex_buffer = Some(buffer);
+            render_data.vertex_buffer_size = vertices.len();
+        } else if let Some(buffer) = render_data.vertex_buffer.as_ref() {
+            // The buffer is large enough for the new vertices, so reuse it
+            queue.write_buffer(buffer, 0, &vertices);
+        } else {
+            unreachable!()
+        }
+
+        self.create_texture_pipelines(device, &self.pipelines, draw_data);
+
+        // The wireframe and texture pipelines bind textures individually.
+        render_data.bindless = self
+            .bindless
+            .as_ref()
+            .filter(|_| !self.wireframe && self.texture_pipelines.is_empty())
+            .and_then(|bindless| bindless.bind(self, draw_data, device));
+
+        render_data
+    }
+
+    /// Render the current imgui frame.  `Renderer::prepare` must be
+    /// called first.
+    ///
+    /// wgpu keeps the buffers and bind groups used by the render pass alive until it is
+    /// finished, so neither the renderer nor the render data stay borrowed, and passes
+    /// created with `RenderPass::forget_lifetime` work as well.
+    pub fn split_render(
+        &self,
+        draw_data: &DrawData,
+        render_data: &RenderData,
+        rpass: &mut RenderPass<'_>,
+    ) -> RendererResult<()> {
+        self.render_with_pipelines(
+            draw_data,
+            render_data,
+            rpass,
+            &self.pipelines,
+            &mut |_, _| true,
+            None,
+        )
+    }
+
+    /// Render only the draw commands of the current imgui frame for which `filter`
+    /// returns `true`, like [`Self::split_render`] otherwise.
+    ///
+    /// The filter is called with the draw list and parameters of every draw command and
+    /// render callback. This allows splitting the UI into several passes, e.g. rendering
+    /// some windows as background and the rest as overlay at different points of a frame.
+    pub fn split_render_filtered(
+        &self,
+        draw_data: &DrawData,
+        render_data: &RenderData,
+        rpass: &mut RenderPass<'_>,
+        mut filter: impl FnMut(&DrawList, &DrawCmdParams) -> bool,
+    ) -> RendererResult<()> {
+        self.render_with_pipelines(
+            draw_data,
+            render_data,
+            rpass,
+            &self.pipelines,
+            &mut filter,
+            None,
+        )
+    }
+
+    /// Render the current imgui frame like [`Self::split_render`], but into a render
+    /// pass whose attachments differ from the ones in the [`RendererConfig`].
+    ///
+    /// The pipelines for each distinct `target` are created on first use and cached, so
+    /// the same frame can be rendered e.g. into both the swapchain and an offscreen
+    /// recording target. Shaders of [`Self::set_texture_shader`] are only used for the
+    /// target in the [`RendererConfig`], other targets draw these textures with the
+    /// default shaders.
+    pub fn split_render_to(
+        &self,
+        draw_data: &DrawData,
+        render_data: &RenderData,
+        rpass: &mut RenderPass<'_>,
+        device: &Device,
+        target: RenderTarget,
+    ) -> RendererResult<()> {
+        if target == self.default_target() {
+            return self.render_with_pipelines(
+                draw_data,
+                render_data,
+                rpass,
+                &self.pipelines,
+                &mut |_, _| true,
+                None,
+            );
+        }
+        let pipelines = self
+            .target_pipelines
+            .lock()
+            .unwrap()
+            .entry(target)
+            .or_insert_with(|| {
+                Arc::new(self.pipeline_source.create_pipelines(
+                    device,
+                    &self.config,
+                    self.bindless.as_ref(),
+                    target,
+                ))
+            })
+            .clone();
+        self.create_texture_pipelines(device, &pipelines, draw_data);
+        self.render_with_pipelines(
+            draw_data,
+            render_data,
+            rpass,
+            &pipelines,
+            &mut |_, _| true,
+            None,
+        )
+    }
+
+    /// Create the pipelines for kinds of textures used by `draw_data` that `pipelines`
+    /// doesn't have yet.
+    fn create_texture_pipelines(
+        &self,
+        device: &Device,
+        pipelines: &PipelineSet,
+        draw_data: &DrawData,
+    ) {
+        let shared_textures = self.read_shared_textures();
+        for draw_list in draw_data.draw_lists() {
+            for cmd in draw_list.commands() {
+                if let Elements { cmd_params, .. } = cmd {
+                    if let Ok(texture) = self
+                        .texture_or_placeholder(shared_textures.as_deref(), cmd_params.texture_id)
+                    {
+                        if self.wireframe {
+                            self.pipeline_source.wireframe_pipeline(
+                                device,
+                                &self.config,
+                                pipelines,
+                                texture.kind,
+                            );
+                        } else {
+                            self.pipeline_source.texture_pipelines(
+                                device,
+                                &self.config,
+                                pipelines,
+                                texture.kind,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The render target described by the [`RendererConfig`].
+    fn default_target(&self) -> RenderTarget {
+        RenderTarget {
+            texture_format: self.config.texture_format,
+            sample_count: self.config.sample_count,
+            depth_format: self.config.depth_format,
+        }
+    }
+
+    /// Record the draw commands of a prepared frame selected by `filter` using `pipelines`.
+    ///
+    /// Invalid draw commands are recorded in `report` and skipped if it is set, otherwise
+    /// they fail the frame.
+    fn render_with_pipelines(
+        &self,
+        draw_data: &DrawData,
+        render_data: &RenderData,
+        rpass: &mut RenderPass<'_>,
+        pipelines: &PipelineSet,
+        filter: &mut dyn FnMut(&DrawList, &DrawCmdParams) -> bool,
+        report: Option<&mut FrameReport>,
+    ) -> RendererResult<()> {
+        if !render_data.render {
+            return Ok(());
+        }
+
+        // Execute all the imgui render work.
+        let mut state = PassState {
+            render_data,
+            pipelines,
+            clip_off: draw_data.display_pos,
+            clip_scale: draw_data.framebuffer_scale,
+            pipeline: None,
+            texture_slot: None,
+            bind_group: None,
+            scissor: None,
+            report,
+            draw_list_index: 0,
+        };
+        let shared_textures = self.read_shared_textures();
+        self.setup_render_state(rpass, &mut state);
+        for (index, (draw_list, bases)) in draw_data
+            .draw_lists()
+            .zip(render_data.draw_list_offsets.iter())
+            .enumerate()
+        {
+            state.draw_list_index = index;
+            self.render_draw_list(
+                rpass,
+                &mut state,
+                shared_textures.as_deref(),
+                draw_list,
+                *bases,
+                filter,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the pipeline, bind groups and buffers used for drawing imgui.
+    fn setup_render_state<'r>(&'r self, rpass: &mut RenderPass<'_>, state: &mut PassState<'r>) {
+        let render_data = state.render_data;
+        let pipeline = self
+            .pipeline_for(
+                state.pipelines,
+                render_data.bindless.is_some(),
+                DrawMode::Blended,
+                TextureKind::Filterable,
+            )
+            .expect("pipelines for filterable textures are created up front");
+        rpass.set_pipeline(pipeline);
+        if let Some(bindings) = &render_data.bindless {
+            rpass.set_bind_group(1, &bindings.bind_group, &[]);
+        }
+        rpass.set_bind_group(0, &render_data.uniform_bind_group, &[]);
+        if let Some(hook) = &self.config.bind_group_hook {
+            hook(rpass);
+        }
+        rpass.set_vertex_buffer(0, render_data.vertex_buffer.as_ref().unwrap().slice(..));
+        rpass.set_index_buffer(
+            render_data.index_buffer.as_ref().unwrap().slice(..),
+            IndexFormat::Uint16,
+        );
+
+        state.pipeline = Some(pipeline);
+        state.texture_slot = None;
+        state.bind_group = None;
+        state.scissor = None;
+    }
+
+    /// The pipeline of `pipelines` drawing commands with `mode` and a texture of `kind`,
+    /// from the texture array if `bindless`.
+    ///
+    /// Returns `None` if the pipelines for `kind` weren't created when preparing the frame.
+    fn pipeline_for<'p>(
+        &self,
+        pipelines: &'p PipelineSet,
+        bindless: bool,
+        mode: DrawMode,
+        kind: TextureKind,
+    ) -> Option<&'p RenderPipeline> {
+        let texture_pipelines = match &pipelines.bindless {
+            Some(pipelines) if bindless => pipelines,
+            _ => pipelines.textures[kind as usize].get()?,
+        };
+        if self.wireframe && !bindless {
+            if let Some(wireframe) = texture_pipelines.wireframe_pipeline.get() {
+                return Some(wireframe);
+            }
+        }
+        let blended = &texture_pipelines.pipeline;
+        Some(match mode {
+            DrawMode::Blended => blended,
+            DrawMode::Opaque => texture_pipelines
+                .opaque_pipeline
+                .as_ref()
+                .unwrap_or(blended),
+        })
+    }
+
+    /// Render the current imgui frame, starting it with [`Self::begin_frame`].
+    ///
+    /// The renderer is only borrowed for the duration of the call, so passes created with
+    /// `RenderPass::forget_lifetime`, e.g. ones owned by a render graph, work as well.
+    pub fn render(
+        &mut self,
+        draw_data: &DrawData,
+        queue: &Queue,
+        device: &Device,
+        rpass: &mut RenderPass<'_>,
+    ) -> RendererResult<()> {
+        self.begin_frame(queue, device);
+        let render_data = self.render_data.take();
+        self.render_data = Some(self.prepare(draw_data, render_data, queue, device));
+        let result = self.split_render(draw_data, self.render_data.as_ref().unwrap(), rpass);
+        self.end_frame();
+        result
+    }
+
+    /// The sampler of a texture of `kind` configured with `desc`, applying
+    /// [`RendererConfig::sampler_defaults`] if `desc` is the default of [`TextureConfig`].
+    fn texture_sampler(
+        &self,
+        device: &Device,
+        kind: TextureKind,
+        desc: &SamplerDescriptor,
+    ) -> Arc<Sampler> {
+        let desc = if *desc == TextureConfig::default().sampler_desc {
+            self.config.sampler_defaults.apply(desc)
+        } else {
+            desc.clone()
+        };
+        self.resources
+            .samplers
+            .get(device, &kind.sampler_desc(&desc))
+    }
+
+    /// Lock the textures shared with other renderers for reading, if there are any.
+    fn read_shared_textures(&self) -> Option<RwLockReadGuard<'_, TextureRegistry>> {
+        let shared_textures = self.shared_textures.as_ref()?;
+        Some(shared_textures.read().unwrap())
+    }
+
+    /// Release the resources that are no longer needed after rendering `draw_data`.
+    fn end_frame(&mut self) {
+        self.clear_frame_textures();
+        self.remove_dropped_textures();
+    }
+
+    /// Render a given `DrawList` from imgui onto a wgpu frame.
+    fn render_draw_list<'render>(
+        &'render self,
+        rpass: &mut RenderPass<'_>,
+        state: &mut PassState<'render>,
+        shared_textures: Option<&'render TextureRegistry>,
+        draw_list: &DrawList,
+        (vertex_base, index_base): (i32, u32),
+        filter: &mut dyn FnMut(&DrawList, &DrawCmdParams) -> bool,
+    ) -> RendererResult<()> {
+        profile_scope!("imgui-wgpu render_draw_list");
+
+        let mut start = index_base;
+
+        for cmd in draw_list.commands() {
+            match cmd {
+                Elements { count, cmd_params } => {
+                    let end = start.saturating_add(count as u32);
+                    if !filter(draw_list, &cmd_params) {
+                        start = end;
+                        continue;
+                    }
+
+                    if let Some(report) = state.report.as_deref_mut() {
+                        let first_index = (start - index_base) as usize;
+                        if let Some(reason) =
+                            report::validate_command(draw_list, &cmd_params, first_index, count)
+                        {
+                            report.skip(state.draw_list_index, &cmd_params, reason);
+                            start = end;
+                            continue;
+                        }
+                    }
+
+                    // Look up the texture of the current command.
+                    let texture_id = cmd_params.texture_id;
+                    let tex = match self.texture_or_placeholder(shared_textures, texture_id) {
+                        Ok(tex) => tex,
+                        Err(error) => {
+                            let report = state.report.as_deref_mut().ok_or(error.clone())?;
+                            report.skip(
+                                state.draw_list_index,
+                                &cmd_params,
+                                SkipReason::Texture(error),
+                            );
+                            start = end;
+                            continue;
+                        }
+                    };
+
+                    // Set scissors on the renderpass.
+                    if let Some(scissor) = ScissorRect::from_clip_rect(
+                        cmd_params.clip_rect,
+                        state.clip_off,
+                        state.clip_scale,
+                        state.render_data.fb_size,
+                        self.config.scissor_rounding,
+                    ) {
+                        // Only change the pipeline, texture bind group and scissors if they
+                        // differ from what the previous command used.
+                        let mode = self
+                            .config
+                            .draw_classifier
+                            .as_ref()
+                            .map_or(DrawMode::Blended, |classify| {
+                                classify(draw_list, &cmd_params)
+                            });
+                        // Textures inserted after `prepare` aren't in the texture array and
+                        // are bound individually instead.
+                        let bindless = state.render_data.bindless.as_deref().and_then(|bindless| {
+                            Some((bindless, *bindless.slots.get(&texture_id)?))
+                        });
+                        let texture_pipeline = self
+                            .texture_pipelines
+                            .get(&texture_id)
+                            .filter(|&&(kind, _)| {
+                                kind == tex.kind && std::ptr::eq(state.pipelines, &self.pipelines)
+                            })
+                            .map(|(_, pipeline)| pipeline);
+                        let pipeline = match texture_pipeline {
+                            Some(pipeline) if mode == DrawMode::Blended => Some(pipeline),
+                            _ => self.pipeline_for(
+                                state.pipelines,
+                                bindless.is_some(),
+                                mode,
+                                tex.kind,
+                            ),
+                        };
+                        let Some(pipeline) = pipeline else {
+                            let error = RendererError::BadTexture(texture_id);
+                            let report = state.report.as_deref_mut().ok_or(error.clone())?;
+                            report.skip(
+                                state.draw_list_index,
+                                &cmd_params,
+                                SkipReason::Texture(error),
+                            );
+                            start = end;
+                            continue;
+                        };
+                        if !state
+                            .pipeline
+                            .is_some_and(|current| std::ptr::eq(current, pipeline))
+                        {
+                            rpass.set_pipeline(pipeline);
+                            state.pipeline = Some(pipeline);
+                        }
+                        if let Some((bindless, slot)) = bindless {
+                            // Bind the array again if a texture was bound individually.
+                            if state.bind_group.take().is_some() {
+                                rpass.set_bind_group(1, &bindless.bind_group, &[]);
+                            }
+                            if state.texture_slot != Some(slot) {
+                                rpass.set_push_constants(
+                                    ShaderStages::FRAGMENT,
+                                    0,
+                                    bytemuck::bytes_of(&slot),
+                                );
+                                state.texture_slot = Some(slot);
+                            }
+                        } else {
+                            state.texture_slot = None;
+                            let bind_group = tex.bind_group.as_ref();
+                            if !state
+                                .bind_group
+                                .is_some_and(|current| std::ptr::eq(current, bind_group))
+                            {
+                                rpass.set_bind_group(1, Some(bind_group), &[]);
+                                state.bind_group = Some(bind_group);
+                            }
+                        }
+                        if state.scissor != Some(scissor) {
+                            scissor.apply(rpass);
+                            state.scissor = Some(scissor);
+                        }
+
+                        if let Some(hook) = &self.config.draw_cmd_hook {
+                            hook(rpass, &cmd_params, &scissor, tex);
+                        }
+
+                        // Draw the current batch of vertices with the renderpass.
+                        rpass.draw_indexed(start..end, vertex_base, 0..1);
+                    }
+
+                    // Increment the index regardless of whether or not this batch
+                    // of vertices was drawn.
+                    start = end;
+                }
+                DrawCmd::ResetRenderState => self.setup_render_state(rpass, state),
+                DrawCmd::RawCallback { callback, raw_cmd } => {
+                    // Other callbacks are owned by the application, only handle our own.
+                    if !std::ptr::fn_addr_eq(
+                        callback,
+                        render_callback_marker as unsafe extern "C" fn(_, _),
+                    ) {
+                        continue;
+                    }
+
+                    // Safety: `raw_cmd` points to the current command of `draw_list`.
+                    let raw_cmd = unsafe { &*raw_cmd };
+                    let id = RenderCallbackId(raw_cmd.UserCallbackData as usize);
+                    let Some(render_callback) = self.callbacks.get(&id) else {
+                        continue;
+                    };
+                    let clip_rect = raw_cmd.ClipRect;
+                    let cmd_params = DrawCmdParams {
+                        clip_rect: [clip_rect.x, clip_rect.y, clip_rect.z, clip_rect.w],
+                        texture_id: TextureId::new(raw_cmd.TextureId as usize),
+                        vtx_offset: raw_cmd.VtxOffset as usize,
+                        idx_offset: raw_cmd.IdxOffset as usize,
+                    };
+                    if !filter(draw_list, &cmd_params) {
+                        continue;
+                    }
+                    if let Some(scissor) = ScissorRect::from_clip_rect(
+                        cmd_params.clip_rect,
+                        state.clip_off,
+                        state.clip_scale,
+                        state.render_data.fb_size,
+                        self.config.scissor_rounding,
+                    ) {
+                        scissor.apply(rpass);
+                        render_callback(rpass, &mut ClipStack::new(scissor));
+                        self.setup_render_state(rpass, state);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a custom render callback.
+    ///
+    /// Use [`RenderCallbackId::add_to_window_draw_list`] to schedule it while building the UI.
+    pub fn register_callback(
+        &mut self,
+        callback: impl Fn(&mut RenderPass<'_>, &mut ClipStack) + Send + Sync + 'static,
+    ) -> RenderCallbackId {
+        let id = RenderCallbackId(self.next_callback_id);
+        self.next_callback_id += 1;
+        self.callbacks.insert(id, Box::new(callback));
+        id
+    }
+
+    /// Remove a custom render callback, draw lists still referencing it skip it.
+    pub fn unregister_callback(&mut self, id: RenderCallbackId) -> Option<RenderCallback> {
+        self.callbacks.remove(&id)
+    }
+
+    /// Draw the commands using `texture_id` with a custom shader, e.g. to display a data
+    /// texture with a false-color map, while the rest of the UI uses the default shaders.
+    ///
+    /// The shader must have the same interface as the renderer's shaders, see
+    /// [`RendererConfig::with_shaders`], with the bind group layout of the kind of the
+    /// texture, see [`Self::texture_layout`]. The pipeline is created for the kind of the
+    /// texture inserted with `texture_id` at the time of the call, or
+    /// [`TextureKind::Filterable`] if there is none, and the default shaders are used
+    /// while the texture has another kind.
+    ///
+    /// Texture shaders are only used when rendering to the target in the
+    /// [`RendererConfig`] with blending, [`Self::split_render_to`] ignores them for other
+    /// targets. The bindless mode is disabled while any are set.
+    pub fn set_texture_shader(
+        &mut self,
+        device: &Device,
+        texture_id: TextureId,
+        shader: ShaderModuleDescriptor<'_>,
+        vertex_entry_point: Option<&str>,
+        fragment_entry_point: Option<&str>,
+    ) {
+        let kind = self
+            .textures
+            .get_with_shared(self.read_shared_textures().as_deref(), texture_id)
+            .map_or(TextureKind::default(), |texture| texture.kind);
+        let shader_module = device.create_shader_module(shader);
+        let pipeline = create_pipeline(
+            device,
+            &PipelineDescriptor {
+                label: "imgui-wgpu texture pipeline",
+                shader_module: &shader_module,
+                vertex_entry_point,
+                fragment_entry_point,
+                ..self
+                    .pipeline_source
+                    .descriptor(&self.config, self.default_target(), kind)
+            },
+        );
+        self.texture_pipelines.insert(texture_id, (kind, pipeline));
+    }
+
+    /// Draw the commands using `texture_id` with the default shaders again.
+    ///
+    /// Returns `false` if no custom shader was set for the texture.
+    pub fn remove_texture_shader(&mut self, texture_id: TextureId) -> bool {
+        self.texture_pipelines.remove(&texture_id).is_some()
+    }
+
+    /// The layout of the bind groups of textures of `kind`, to create bind groups for
+    /// [`Texture::from_raw_parts`] or pipelines of custom shaders.
+    ///
+    /// Besides the bindings of `kind`, the layout has the [`TextureStyle`] uniform buffer
+    /// at binding 4.
+    pub fn texture_layout(&self, kind: TextureKind) -> &BindGroupLayout {
+        &self.resources.layouts[kind as usize]
+    }
+
+    /// Iterate all textures of [`Self::textures`] with their ids, in no particular order,
+    /// e.g. to list them in a debug window.
+    pub fn textures_iter(&self) -> impl Iterator<Item = (TextureId, &Texture)> {
+        self.textures.iter()
+    }
+
+    /// The id of a texture whose label is `label`, e.g. to display an image created
+    /// elsewhere. If several textures have this label any of them is returned.
+    pub fn find_texture_by_label(&self, label: &str) -> Option<TextureId> {
+        self.textures
+            .iter()
+            .find(|(_, texture)| texture.label() == Some(label))
+            .map(|(id, _)| id)
+    }
+
+    /// Resize the texture `texture_id` of [`Self::textures`] in place, see
+    /// [`Texture::resize`]. The texture keeps its id.
+    ///
+    /// Returns `false` if there is no texture with this id or it doesn't own a
+    /// `wgpu::Texture`, see [`Texture::try_texture`].
+    pub fn resize_texture(
+        &mut self,
+        device: &Device,
+        texture_id: TextureId,
+        size: Extent3d,
+    ) -> bool {
+        // Removing the texture would make its id stale, resize a clone instead.
+        let Some(mut texture) = self.textures.get(texture_id).cloned() else {
+            return false;
+        };
+        if texture.try_texture().is_none() {
+            return false;
+        }
+        texture.resize(device, self, size);
+        self.textures.replace(texture_id, texture);
+        true
+    }
+
+    /// Insert a texture that is only valid for the current frame, e.g. a view of the
+    /// swapchain or a transient resource of a render graph created with
+    /// [`Texture::from_view`].
+    ///
+    /// The texture is removed again after [`Self::render`] or when
+    /// [`Self::clear_frame_textures`] is called. A stale id kept until a later frame fails
+    /// with [`RendererError::StaleTexture`] instead of showing another texture.
+    pub fn insert_frame_texture(&mut self, texture: Texture) -> TextureId {
+        let id = self.textures.insert(texture);
+        self.frame_textures.push(id);
+        id
+    }
+
+    /// Remove all textures inserted with [`Self::insert_frame_texture`].
+    ///
+    /// This is done by [`Self::render`], but has to be called after rendering when the
+    /// frame is rendered with [`Self::prepare`] and [`Self::split_render`].
+    pub fn clear_frame_textures(&mut self) {
+        for id in self.frame_textures.drain(..) {
+            self.textures.remove(id);
+            self.texture_pipelines.remove(&id);
+        }
+    }
+
+    /// Create the uniform buffer holding the transform matrix of a frame, followed by
+    /// the per-view transforms with multiview, and its bind group.
+    fn create_uniform_buffer(&self, device: &Device) -> (Buffer, BindGroup) {
+        // The multiview shader always declares `MAX_VIEWS` transforms.
+        let views = if self.config.multiview.is_some() {
+            MAX_VIEWS
+        } else {
+            0
+        };
+        let uniform_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("imgui-wgpu uniform buffer"),
+            size: 64 * (1 + views as u64),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("imgui-wgpu bind group"),
+            layout: &self.uniform_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        (uniform_buffer, uniform_bind_group)
+    }
+
+    /// Draw the outlines of all triangles instead of filling them, to visualize imgui's
+    /// tessellation. Takes effect with the next prepared frame, which creates the
+    /// wireframe pipelines on first use.
+    ///
+    /// This has no effect unless the device has `Features::POLYGON_MODE_LINE`.
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
+    /// Set the transforms applied to each view in multiview rendering, after the
+    /// projection to clip space. `transforms` holds up to [`MAX_VIEWS`] column-major
+    /// matrices, starting with view 0, any further ones are ignored. Takes effect with the
+    /// next prepared frame.
+    pub fn set_view_transforms(&mut self, transforms: &[[[f32; 4]; 4]]) {
+        let count = transforms.len().min(MAX_VIEWS);
+        self.view_transforms[..count].copy_from_slice(&transforms[..count]);
+    }
+
+    /// Destroy all GPU resources of the renderer right away.
+    ///
+    /// Engines with a strict shutdown order can call this before destroying the device,
+    /// so no buffer or texture outlives it. `RenderData` returned by [`Self::prepare`]
+    /// has to be destroyed separately with [`RenderData::destroy`].
+    ///
+    /// The default texture style buffer is kept while other renderers share it, see
+    /// [`Self::new_shared`], or textures of this renderer are still referenced.
+    pub fn destroy(mut self) -> TeardownReport {
+        let mut report = TeardownReport::default();
+        // The textures of dropped handles are still in the registry and destroyed with it.
+        self.dropped_textures.lock().unwrap().clear();
+        for (id, texture) in self.textures.drain() {
+            if !texture.destroy() {
+                report.referenced_textures.push(id);
+            }
+        }
+        if let Some(placeholder) = self.placeholder.take() {
+            placeholder.destroy();
+        }
+        if let Some(render_data) = self.render_data.take() {
+            render_data.destroy();
+        }
+        if report.referenced_textures.is_empty() && Arc::strong_count(&self.resources) == 1 {
+            self.resources.default_style.destroy();
+        }
+        report
+    }
+
+    /// Reload the font texture if the font atlas changed since it was uploaded, e.g. because
+    /// fonts were added or rebuilt for a new DPI, and return whether it did.
+    ///
+    /// Call it every frame before `Context::new_frame`, like the new frame functions of
+    /// the Dear ImGui backends, so font changes never require [`Self::reload_font_texture`].
+    /// It has to run before the frame starts, since Dear ImGui requires a built atlas
+    /// for a new frame and draws the glyphs of the new atlas from then on.
+    ///
+    /// Returns the error of [`Self::reload_font_texture`] if the atlas couldn't be
+    /// uploaded.
+    pub fn update_font_texture(
+        &mut self,
+        imgui: &mut Context,
+        device: &Device,
+        queue: &Queue,
+    ) -> RendererResult<bool> {
+        let fonts = imgui.fonts();
+        let current = fonts.is_built()
+            && self.font_textures.get(&atlas_key(fonts)) == Some(&fonts.tex_id)
+            && self.textures.get(fonts.tex_id).is_some();
+        if !current {
+            self.reload_font_texture(imgui, device, queue)?;
+        }
+        Ok(!current)
+    }
+
+    /// Updates the texture on the GPU corresponding to the current imgui font atlas.
+    ///
+    /// This has to be called after loading a font, unless [`Self::update_font_texture`]
+    /// is called every frame. Each font atlas gets its own texture, so a renderer can
+    /// draw several contexts, and contexts sharing an atlas only need it uploaded once.
+    ///
+    /// Returns [`RendererError::FontAtlasTooLarge`] if the atlas exceeds the maximum
+    /// texture size of the device, e.g. with many large CJK fonts. The size is only known
+    /// once the atlas is built, so if the fonts changed since the previous font texture
+    /// was uploaded, that texture stays bound but no longer matches the glyphs. Rebuild
+    /// the fonts to fit, e.g. with fewer glyph ranges, and reload the texture again.
+    pub fn reload_font_texture(
+        &mut self,
+        imgui: &mut Context,
+        device: &Device,
+        queue: &Queue,
+    ) -> RendererResult<()> {
+        profile_scope!("imgui-wgpu reload_font_texture");
+
+        let fonts = imgui.fonts();
+        let atlas = atlas_key(fonts);
+
+        // Create font texture and upload it.
+        let format = self.font_atlas_format(device);
+        let alpha8 = matches!(format, TextureFormat::R8Unorm | TextureFormat::Bc4RUnorm);
+        let (handle, bytes_per_pixel) = if alpha8 {
+            (fonts.build_alpha8_texture(), 1)
+        } else {
+            (fonts.build_rgba32_texture(), 4)
+        };
+        // Fall back to uncompressed if the atlas isn't made of whole blocks.
+        let format = match format {
+            TextureFormat::Bc4RUnorm
+                if !handle.width.is_multiple_of(4) || !handle.height.is_multiple_of(4) =>
+            {
+                TextureFormat::R8Unorm
+            }
+            format => format,
+        };
+        let max = device.limits().max_texture_dimension_2d;
+        if handle.width > max || handle.height > max {
+            return Err(RendererError::FontAtlasTooLarge {
+                width: handle.width,
+                height: handle.height,
+                max,
+            });
+        }
+        let font_texture_cnfig = TextureConfig {
+            label: Some("imgui-wgpu font atlas"),
+            size: Extent3d {
+                width: handle.width,
+                height: handle.height,
+                ..Default::default()
+            },
+            format: Some(format),
+            ..Default::default()
+        };
+
+        let mut font_texture = Texture::new(device, self, font_texture_cnfig);
+        let distance_field = self.config.font_distance_field;
+        let subpixel = self.config.subpixel_text;
+        let coverage_gamma = if self.config.gamma_correct_text {
+            TEXT_COVERAGE_GAMMA
+        } else {
+            1.0
+        };
+        if alpha8 || distance_field.is_some() || subpixel || coverage_gamma != 1.0 {
+            let style = TextureStyle {
+                alpha_mask: alpha8,
+                distance_field,
+                subpixel,
+                coverage_gamma,
+                ..Default::default()
+            };
+            font_texture.set_style(device, queue, self, style);
+        }
+
+        // Upload the atlas in bands of rows, as very large atlases can exceed the
+        // per-upload limits of some (mostly WebGPU) implementations. Size the bands by
+        // the padded row pitch, since that is what the staging copy actually uses.
+        // Compressed atlases are an eighth of the size and uploaded at once.
+        let bytes_per_row = handle.width * bytes_per_pixel;
+        let padded_bytes_per_row = bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+        let max_band_size = device.limits().max_buffer_size.min(FONT_UPLOAD_BAND_SIZE);
+        let rows_per_band = (max_band_size / padded_bytes_per_row.max(1) as u64)
+            .clamp(1, handle.height.max(1) as u64) as u32;
+        #[cfg(feature = "compress")]
+        if format == TextureFormat::Bc4RUnorm {
+            let compressed = compress::compress_bc4(handle.data, handle.width);
+            font_texture.write(queue, &compressed, handle.width, handle.height);
+        }
+        let uncompressed_rows = if format.is_compressed() {
+            0
+        } else {
+            handle.height
+        };
+        for first_row in (0..uncompressed_rows).step_by(rows_per_band as usize) {
+            let rows = rows_per_band.min(handle.height - first_row);
+            let start = (first_row * bytes_per_row) as usize;
+            let end = start + (rows * bytes_per_row) as usize;
+            font_texture.write_rows(
+                queue,
+                0,
+                &handle.data[start..end],
+                handle.width,
+                first_row,
+                rows,
+            );
+        }
+
+        // Remove the previous texture of this atlas.
+        if let Some(previous) = self.font_textures.remove(&atlas) {
+            self.textures.remove(previous);
+        }
+        fonts.tex_id = self.textures.insert(font_texture);
+        self.font_textures.insert(atlas, fonts.tex_id);
+        // Clear imgui texture data to save memory.
+        if !self.config.keep_font_atlas_data {
+            fonts.clear_tex_data();
+        }
+        Ok(())
+    }
+
+    /// The format of font atlases, see [`RendererConfig::font_atlas_format`].
+    fn font_atlas_format(&self, device: &Device) -> TextureFormat {
+        match self.config.font_atlas_format {
+            Some(TextureFormat::Bc4RUnorm)
+                if !cfg!(feature = "compress")
+                    || !device.features().contains(Features::TEXTURE_COMPRESSION_BC) =>
+            {
+                TextureFormat::R8Unorm
+            }
+            Some(format) => format,
+            None if self.config.alpha8_font_atlas => TextureFormat::R8Unorm,
+            None => TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    /// Remove the texture of the font atlas of `imgui`, e.g. before dropping a context
+    /// drawn by this renderer whose atlas isn't shared. Returns `false` if the atlas had
+    /// no texture.
+    pub fn remove_font_texture(&mut self, imgui: &mut Context) -> bool {
+        let atlas = atlas_key(imgui.fonts());
+        self.font_scales.remove(&atlas);
+        let Some(id) = self.font_textures.remove(&atlas) else {
+            return false;
+        };
+        self.textures.remove(id);
+        true
+    }
+
+    /// Whether `id` is the texture of a font atlas.
+    pub(crate) fn is_font_texture(&self, id: TextureId) -> bool {
+        self.font_textures.values().any(|&font| font == id)
+    }
+}
+
+/// The key of `fonts` in `Renderer::font_textures`.
+fn atlas_key(fonts: &FontAtlas) -> usize {
+    fonts as *const FontAtlas as usize
+}
+
+/// The shader and layout of the renderer's pipelines, kept to create pipelines for
+/// further render targets.
+struct PipelineSource {
+    /// The pipeline layouts by [`TextureKind`].
+    layouts: Vec<PipelineLayout>,
+    shader_module: ShaderModule,
+    vertex_entry_point: Option<String>,
+    fragment_entry_point: Option<String>,
+    constants: HashMap<String, f64>,
+}
+
+impl PipelineSource {
+    /// Describe the default pipeline drawing textures of `kind` to `target`.
+    fn descriptor<'a>(
+        &'a self,
+        config: &'a RendererConfig,
+        target: RenderTarget,
+        kind: TextureKind,
+    ) -> PipelineDescriptor<'a> {
+        PipelineDescriptor {
+            label: "imgui-wgpu pipeline",
+            layout: &self.layouts[kind as usize],
+            shader_module: &self.shader_module,
+            vertex_entry_point: self.vertex_entry_point.as_deref(),
+            fragment_entry_point: self.fragment_entry_point.as_deref(),
+            constants: &self.constants,
+            vertex_layout: &config.vertex_layout,
+            texture_format: target.texture_format,
+            depth_format: target.depth_format,
+            sample_count: target.sample_count,
+            additional_color_targets: &config.additional_color_targets,
+            writes_additional_targets: true,
+            dual_source_blending: config.dual_source_blending,
+            multiview: config.multiview,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: config.front_face,
+                cull_mode: config.cull_mode,
+                polygon_mode: PolygonMode::Fill,
+                unclipped_depth: config.unclipped_depth,
+                conservative: config.conservative,
+            },
+            draw_mode: DrawMode::Blended,
+        }
+    }
+
+    /// Create the pipelines needed by `config` for drawing to `target`. Only the pipelines
+    /// for filterable textures are created, see [`Self::texture_pipelines`].
+    fn create_pipelines(
+        &self,
+        device: &Device,
+        config: &RendererConfig,
+        bindless: Option<&BindlessPipeline>,
+        target: RenderTarget,
+    ) -> PipelineSet {
+        let desc = self.descriptor(config, target, TextureKind::Filterable);
+        let textures: [OnceLock<_>; TextureKind::ALL.len()] = Default::default();
+        let _ = textures[TextureKind::Filterable as usize]
+            .set(TexturePipelines::new(device, config, &desc, true));
+
+        let bindless = bindless.map(|bindless| {
+            TexturePipelines::new(
+                device,
+                config,
+                &PipelineDescriptor {
+                    label: "imgui-wgpu bindless pipeline",
+                    layout: &bindless.layout,
+                    shader_module: &bindless.shader_module,
+                    vertex_entry_point: Some(VS_ENTRY_POINT),
+                    fragment_entry_point: Some(bindless.fragment_entry_point),
+                    // The bindless shader only has a single output.
+                    dual_source_blending: false,
+                    ..desc
+                },
+                // Wireframe rendering binds textures individually.
+                false,
+            )
+        });
+
+        PipelineSet {
+            target,
+            textures,
+            bindless,
+        }
+    }
+
+    /// The pipelines of `pipelines` drawing textures of `kind`, created if necessary.
+    fn texture_pipelines<'p>(
+        &self,
+        device: &Device,
+        config: &RendererConfig,
+        pipelines: &'p PipelineSet,
+        kind: TextureKind,
+    ) -> &'p TexturePipelines {
+        pipelines.textures[kind as usize].get_or_init(|| {
+            // Other kinds of textures are drawn with the built-in shaders reading them.
+            let builtin_shader = kind
+                .builtin_shader()
+                .map(|shader| device.create_shader_module(shader));
+            let desc = self.texture_descriptor(config, pipelines.target, kind, &builtin_shader);
+            let mut texture_pipelines = TexturePipelines::new(device, config, &desc, true);
+            texture_pipelines.builtin_shader = builtin_shader;
+            texture_pipelines
+        })
+    }
+
+    /// The wireframe pipeline of `pipelines` drawing textures of `kind`, created on first
+    /// use. Returns `None` if the device doesn't support wireframes.
+    fn wireframe_pipeline<'p>(
+        &self,
+        device: &Device,
+        config: &RendererConfig,
+        pipelines: &'p PipelineSet,
+        kind: TextureKind,
+    ) -> Option<&'p RenderPipeline> {
+        let texture_pipelines = self.texture_pipelines(device, config, pipelines, kind);
+        if !texture_pipelines.wireframe {
+            return None;
+        }
+        Some(texture_pipelines.wireframe_pipeline.get_or_init(|| {
+            let desc = self.texture_descriptor(
+                config,
+                pipelines.target,
+                kind,
+                &texture_pipelines.builtin_shader,
+            );
+            create_pipeline(
+                device,
+                &PipelineDescriptor {
+                    label: "imgui-wgpu wireframe pipeline",
+                    primitive: PrimitiveState {
+                        polygon_mode: PolygonMode::Line,
+                        ..desc.primitive
+                    },
+                    ..desc
+                },
+            )
+        }))
+    }
+
+    /// Describe the pipeline drawing textures of `kind` to `target`, with the
+    /// `builtin_shader` of the kind if it has one.
+    fn texture_descriptor<'a>(
+        &'a self,
+        config: &'a RendererConfig,
+        target: RenderTarget,
+        kind: TextureKind,
+        builtin_shader: &'a Option<ShaderModule>,
+    ) -> PipelineDescriptor<'a> {
+        let desc = self.descriptor(config, target, kind);
+        let Some(shader_module) = builtin_shader else {
+            return desc;
+        };
+        let srgb = self.fragment_entry_point.as_deref() == Some(FS_ENTRY_POINT_SRGB);
+        PipelineDescriptor {
+            label: "imgui-wgpu builtin texture pipeline",
+            shader_module,
+            vertex_entry_point: Some(VS_ENTRY_POINT),
+            fragment_entry_point: Some(if srgb {
+                FS_ENTRY_POINT_SRGB
+            } else {
+                FS_ENTRY_POINT_LINEAR
+            }),
+            // The built-in texture shaders only have a single output.
+            writes_additional_targets: false,
+            dual_source_blending: false,
+            ..desc
+        }
+    }
+}
+
+/// The pipelines drawing to one render target.
+struct PipelineSet {
+    target: RenderTarget,
+    /// The pipelines binding individual textures by [`TextureKind`].
+    textures: [OnceLock<TexturePipelines>; TextureKind::ALL.len()],
+    bindless: Option<TexturePipelines>,
+}
+
+/// The pipelines drawing one kind of texture.
+struct TexturePipelines {
+    pipeline: RenderPipeline,
+    opaque_pipeline: Option<RenderPipeline>,
+    /// Whether the device supports the wireframe pipeline, which is only created once
+    /// wireframes are enabled with [`Renderer::set_wireframe`].
+    wireframe: bool,
+    wireframe_pipeline: OnceLock<RenderPipeline>,
+    /// The built-in shader of the kind, kept for creating the wireframe pipeline.
+    builtin_shader: Option<ShaderModule>,
+}
+
+impl TexturePipelines {
+    /// Create the pipeline described by `desc` and the variants needed by `config`.
+    fn new(
+        device: &Device,
+        config: &RendererConfig,
+        desc: &PipelineDescriptor,
+        wireframe: bool,
+    ) -> Self {
+        let pipeline = create_pipeline(device, desc);
+
+        // The opaque pipeline is only needed if commands can be classified as opaque.
+        let opaque_pipeline = config.draw_classifier.is_some().then(|| {
+            create_pipeline(
+                device,
+                &PipelineDescriptor {
+                    label: "imgui-wgpu opaque pipeline",
+                    draw_mode: DrawMode::Opaque,
+                    ..*desc
+                },
+            )
+        });
+
+        // The wireframe pipeline can be toggled at runtime if the device supports it.
+        let wireframe = wireframe && device.features().contains(Features::POLYGON_MODE_LINE);
+
+        Self {
+            pipeline,
+            opaque_pipeline,
+            wireframe,
+            wireframe_pipeline: OnceLock::new(),
+            builtin_shader: None,
+        }
+    }
+}
+
+/// Everything needed to create one of the renderer's pipelines.
+struct PipelineDescriptor<'a> {
+    label: &'a str,
+    layout: &'a PipelineLayout,
+    shader_module: &'a ShaderModule,
+    vertex_entry_point: Option<&'a str>,
+    fragment_entry_point: Option<&'a str>,
+    constants: &'a HashMap<String, f64>,
+    vertex_layout: &'a VertexLayout,
+    texture_format: TextureFormat,
+    depth_format: Option<TextureFormat>,
+    sample_count: u32,
+    additional_color_targets: &'a [Option<ColorTargetState>],
+    /// Whether the shader writes the additional color targets, otherwise their write
+    /// mask is cleared.
+    writes_additional_targets: bool,
+    dual_source_blending: bool,
+    multiview: Option<NonZeroU32>,
+    primitive: PrimitiveState,
+    draw_mode: DrawMode,
+}
 
-        // Create the uniform matrix buffer.
-        let size = 64;
-        let uniform_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("imgui-wgpu uniform buffer"),
-            size,
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+/// Create a render pipeline drawing imgui vertices.
+fn create_pipeline(device: &Device, desc: &PipelineDescriptor) -> RenderPipeline {
+    let compilation_options = PipelineCompilationOptions {
+        constants: desc.constants,
+        ..Default::default()
+    };
 
-        // Create the uniform matrix buffer bind group layout.
-        let uniform_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            label: None,
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+    let main_target = ColorTargetState {
+        format: desc.texture_format,
+        blend: match desc.draw_mode {
+            DrawMode::Blended if desc.dual_source_blending => Some(BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::OneMinusSrc1,
+                    operation: BlendOperation::Add,
                 },
-                count: None,
-            }],
-        });
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::OneMinusSrc1Alpha,
+                    operation: BlendOperation::Add,
+                },
+            }),
+            DrawMode::Blended => Some(BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::OneMinusDstAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            }),
+            DrawMode::Opaque => None,
+        },
+        write_mask: ColorWrites::ALL,
+    };
+    let targets: Vec<_> = std::iter::once(Some(main_target))
+        .chain(desc.additional_color_targets.iter().map(|target| {
+            target.clone().map(|target| ColorTargetState {
+                write_mask: if desc.writes_additional_targets {
+                    target.write_mask
+                } else {
+                    ColorWrites::empty()
+                },
+                ..target
+            })
+        }))
+        .collect();
 
-        // Create the uniform matrix buffer bind group.
-        let uniform_bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: Some("imgui-wgpu bind group"),
-            layout: &uniform_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some(desc.label),
+        layout: Some(desc.layout),
+        vertex: VertexState {
+            module: desc.shader_module,
+            entry_point: desc.vertex_entry_point,
+            compilation_options: compilation_options.clone(),
+            buffers: &[VertexBufferLayout {
+                array_stride: desc.vertex_layout.array_stride,
+                step_mode: VertexStepMode::Vertex,
+                attributes: &desc.vertex_layout.attributes,
             }],
-        });
+        },
+        primitive: desc.primitive,
+        depth_stencil: desc.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: DepthBiasState::default(),
+        }),
+        multisample: MultisampleState {
+            count: desc.sample_count,
+            ..Default::default()
+        },
+        fragment: Some(FragmentState {
+            module: desc.shader_module,
+            entry_point: desc.fragment_entry_point,
+            compilation_options,
+            targets: &targets,
+        }),
+        multiview: desc.multiview,
+        cache: None,
+    })
+}
+
+/// The layouts and shader used by the bindless texture-array mode.
+struct BindlessPipeline {
+    layout: PipelineLayout,
+    shader_module: ShaderModule,
+    fragment_entry_point: &'static str,
+    texture_layout: BindGroupLayout,
+    default_sampler: Sampler,
+    max_textures: u32,
+    /// The bindings of the last frame, see [`Self::bind`].
+    cache: Mutex<Option<BindlessCache>>,
+}
+
+impl BindlessPipeline {
+    /// Create the bindless layouts, or return `None` if the device doesn't support them.
+    fn new(
+        device: &Device,
+        uniform_layout: &BindGroupLayout,
+        extra_layouts: &[&BindGroupLayout],
+        config: BindlessConfig,
+        srgb: bool,
+    ) -> Option<Self> {
+        let required = Features::TEXTURE_BINDING_ARRAY | Features::PUSH_CONSTANTS;
+        let limits = device.limits();
+        if !device.features().contains(required) || limits.max_push_constant_size < 4 {
+            log::warn!("imgui-wgpu: bindless mode is not supported by the device, disabling it");
+            return None;
+        }
+
+        let max_textures = config
+            .max_textures
+            .min(limits.max_sampled_textures_per_shader_stage)
+            .min(limits.max_samplers_per_shader_stage)
+            .max(1);
+        let count = NonZeroU32::new(max_textures);
 
-        // Create the texture layout for further usage.
         let texture_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            label: Some("imgui-wgpu bind group layout"),
+            label: Some("imgui-wgpu bindless bind group layout"),
             entries: &[
                 BindGroupLayoutEntry {
                     binding: 0,
@@ -417,381 +3945,289 @@ impl Renderer {
                         sample_type: TextureSampleType::Float { filterable: true },
                         view_dimension: TextureViewDimension::D2,
                     },
-                    count: None,
+                    count,
                 },
                 BindGroupLayoutEntry {
                     binding: 1,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
+                    count,
                 },
             ],
         });
 
-        // Create the render pipeline layout.
-        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
-            label: Some("imgui-wgpu pipeline layout"),
-            bind_group_layouts: &[&uniform_layout, &texture_layout],
-            push_constant_ranges: &[],
-        });
-
-        // Create the render pipeline.
-        // Create the render pipeline.
-        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: Some("imgui-wgpu pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: VertexState {
-                module: &shader_module,
-                entry_point: vertex_shader_entry_point,
-                compilation_options: Default::default(),
-                buffers: &[VertexBufferLayout {
-                    array_stride: size_of::<DrawVert>() as BufferAddress,
-                    step_mode: VertexStepMode::Vertex,
-                    attributes: &vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Unorm8x4],
-                }],
-            },
-            primitive: PrimitiveState {
-                topology: PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: FrontFace::Cw,
-                cull_mode: None,
-                polygon_mode: PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
-                format,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: wgpu::StencilState::default(),
-                bias: DepthBiasState::default(),
-            }),
-            multisample: MultisampleState {
-                count: sample_count,
-                ..Default::default()
-            },
-            fragment: Some(FragmentState {
-                module: &shader_module,
-                entry_point: fragment_shader_entry_point,
-                compilation_options: Default::default(),
-                targets: &[Some(ColorTargetState {
-                    format: texture_format,
-                    blend: Some(BlendState {
-                        color: BlendComponent {
-                            src_factor: BlendFactor::SrcAlpha,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                        alpha: BlendComponent {
-                            src_factor: BlendFactor::OneMinusDstAlpha,
-                            dst_factor: BlendFactor::One,
-                            operation: BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-            cache: None,
+        let bind_group_layouts: Vec<_> = [uniform_layout, &texture_layout]
+            .into_iter()
+            .chain(extra_layouts.iter().copied())
+            .collect();
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("imgui-wgpu bindless pipeline layout"),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[PushConstantRange {
+                stages: ShaderStages::FRAGMENT,
+                range: 0..4,
+            }],
         });
-
-        let mut renderer = Self {
-            pipeline,
-            uniform_buffer,
-            uniform_bind_group,
-            textures: Textures::new(),
-            texture_layout,
-            render_data: None,
-            config: RendererConfig {
-                texture_format,
-                depth_format,
-                sample_count,
-                shader: None,
-                vertex_shader_entry_point: None,
-                fragment_shader_entry_point: None,
-            },
+        let shader_module = device.create_shader_module(shaders::BINDLESS);
+        let fragment_entry_point = if srgb {
+            FS_ENTRY_POINT_SRGB
+        } else {
+            FS_ENTRY_POINT_LINEAR
         };
 
-        // Immediately load the font texture to the GPU.
-        renderer.reload_font_texture(imgui, device, queue);
+        let default_sampler = device.create_sampler(&TextureConfig::default().sampler_desc);
 
-        renderer
+        Some(Self {
+            layout,
+            shader_module,
+            fragment_entry_point,
+            texture_layout,
+            default_sampler,
+            max_textures,
+            cache: Mutex::new(None),
+        })
     }
 
-    /// Prepares buffers for the current imgui frame.  This must be
-    /// called before `Renderer::split_render`, and its output must
-    /// be passed to the render call.
-    pub fn prepare(
+    /// Bind all textures used by `draw_data` into a single bind group, reusing the one of
+    /// the previous frame if it used the same textures.
+    ///
+    /// Returns `None` if the frame uses more textures than fit into the array, in which
+    /// case the frame is rendered with regular bind groups.
+    fn bind(
         &self,
+        renderer: &Renderer,
         draw_data: &DrawData,
-        render_data: Option<RenderData>,
-        queue: &Queue,
         device: &Device,
-    ) -> RenderData {
-        let fb_width = draw_data.display_size[0] * draw_data.framebuffer_scale[0];
-        let fb_height = draw_data.display_size[1] * draw_data.framebuffer_scale[1];
-
-        let mut render_data = render_data.unwrap_or_else(|| RenderData {
-            fb_size: [fb_width, fb_height],
-            last_size: [0.0, 0.0],
-            last_pos: [0.0, 0.0],
-            vertex_buffer: None,
-            vertex_buffer_size: 0,
-            index_buffer: None,
-            index_buffer_size: 0,
-            draw_list_offsets: SmallVec::<[_; 4]>::new(),
-            render: false,
-        });
-
-        // If the render area is <= 0, exit here and now.
-        if fb_width <= 0.0 || fb_height <= 0.0 {
-            render_data.render = false;
-            return render_data;
-        } else {
-            render_data.render = true;
-        }
-
-        // Only update matrices if the size or position changes
-        if (render_data.last_size[0] - draw_data.display_size[0]).abs() > f32::EPSILON
-            || (render_data.last_size[1] - draw_data.display_size[1]).abs() > f32::EPSILON
-            || (render_data.last_pos[0] - draw_data.display_pos[0]).abs() > f32::EPSILON
-            || (render_data.last_pos[1] - draw_data.display_pos[1]).abs() > f32::EPSILON
-        {
-            render_data.fb_size = [fb_width, fb_height];
-            render_data.last_size = draw_data.display_size;
-            render_data.last_pos = draw_data.display_pos;
-
-            let width = draw_data.display_size[0];
-            let height = draw_data.display_size[1];
-
-            let offset_x = draw_data.display_pos[0] / width;
-            let offset_y = draw_data.display_pos[1] / height;
-
-            // Create and update the transform matrix for the current frame.
-            // This is required to adapt to vulkan coordinates.
-            let matrix = [
-                [2.0 / width, 0.0, 0.0, 0.0],
-                [0.0, 2.0 / -height, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [-1.0 - offset_x * 2.0, 1.0 + offset_y * 2.0, 0.0, 1.0],
-            ];
-            self.update_uniform_buffer(queue, &matrix);
-        }
-
-        render_data.draw_list_offsets.clear();
-
-        let mut vertex_count = 0;
-        let mut index_count = 0;
-        for draw_list in draw_data.draw_lists() {
-            render_data
-                .draw_list_offsets
-                .push((vertex_count as i32, index_count as u32));
-            vertex_count += draw_list.vtx_buffer().len();
-            index_count += draw_list.idx_buffer().len();
-        }
-
-        let mut vertices = Vec::with_capacity(vertex_count * std::mem::size_of::<DrawVertPod>());
-        let mut indices = Vec::with_capacity(index_count * std::mem::size_of::<DrawIdx>());
-
+    ) -> Option<Arc<BindlessBindings>> {
+        let shared_textures = renderer.read_shared_textures();
+        let mut slots = HashMap::new();
+        let mut textures = Vec::new();
         for draw_list in draw_data.draw_lists() {
-            // Safety: DrawVertPod is #[repr(transparent)] over DrawVert and DrawVert _should_ be Pod.
-            let vertices_pod: &[DrawVertPod] = unsafe { draw_list.transmute_vtx_buffer() };
-            vertices.extend_from_slice(bytemuck::cast_slice(vertices_pod));
-            indices.extend_from_slice(bytemuck::cast_slice(draw_list.idx_buffer()));
-        }
-
-        // Copies in wgpu must be padded to 4 byte alignment
-        indices.resize(
-            indices.len() + COPY_BUFFER_ALIGNMENT as usize
-                - indices.len() % COPY_BUFFER_ALIGNMENT as usize,
-            0,
-        );
-
-        // If the buffer is not created or is too small for the new indices, create a new buffer
-        if render_data.index_buffer.is_none() || render_data.index_buffer_size < indices.len() {
-            let buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: Some("imgui-wgpu index buffer"),
-                contents: &indices,
-                usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
-            });
-            render_data.index_buffer = Some(buffer);
-            render_data.index_buffer_size = indices.len();
-        } else if let Some(buffer) = render_data.index_buffer.as_ref() {
-            // The buffer is large enough for the new indices, so reuse it
-            queue.write_buffer(buffer, 0, &indices);
-        } else {
-            unreachable!()
+            for cmd in draw_list.commands() {
+                if let Elements { cmd_params, .. } = cmd {
+                    let texture_id = cmd_params.texture_id;
+                    if slots.contains_key(&texture_id) {
+                        continue;
+                    }
+                    // Unknown textures are reported by `split_render`.
+                    let Ok(texture) =
+                        renderer.texture_or_placeholder(shared_textures.as_deref(), texture_id)
+                    else {
+                        continue;
+                    };
+                    // The texture array only holds filterable textures without style.
+                    if texture.kind != TextureKind::Filterable || texture.style.is_some() {
+                        return None;
+                    }
+                    // Textures created from a view have no view to put into the array.
+                    let view = texture.view.clone()?;
+                    if textures.len() as u32 == self.max_textures {
+                        return None;
+                    }
+                    slots.insert(texture_id, textures.len() as u32);
+                    textures.push((texture_id, view, texture.sampler.clone()));
+                }
+            }
         }
-
-        // If the buffer is not created or is too small for the new vertices, create a new buffer
-        if render_data.vertex_buffer.is_none() || render_data.vertex_buffer_size < vertices.len() {
-            let buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: Some("imgui-wgpu vertex buffer"),
-                contents: &vertices,
-                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
-            });
-            render_data.vertex_buffer = Some(buffer);
-            render_data.vertex_buffer_size = vertices.len();
-        } else if let Some(buffer) = render_data.vertex_buffer.as_ref() {
-            // The buffer is large enough for the new vertices, so reuse it
-            queue.write_buffer(buffer, 0, &vertices);
-        } else {
-            unreachable!()
+        if textures.is_empty() {
+            return None;
         }
 
-        render_data
-    }
-
-    /// Render the current imgui frame.  `Renderer::prepare` must be
-    /// called first, and the output render data must be kept for the
-    /// lifetime of the renderpass.
-    pub fn split_render<'r>(
-        &'r self,
-        draw_data: &DrawData,
-        render_data: &'r RenderData,
-        rpass: &mut RenderPass<'r>,
-    ) -> RendererResult<()> {
-        if !render_data.render {
-            return Ok(());
+        // The cache holds on to the views and samplers, so equal pointers are the same ones.
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cache) = cache.as_ref() {
+            let unchanged = cache.textures.len() == textures.len()
+                && cache
+                    .textures
+                    .iter()
+                    .zip(&textures)
+                    .all(|(cached, texture)| {
+                        cached.0 == texture.0
+                            && Arc::ptr_eq(&cached.1, &texture.1)
+                            && match (&cached.2, &texture.2) {
+                                (Some(cached), Some(sampler)) => Arc::ptr_eq(cached, sampler),
+                                (cached, sampler) => cached.is_none() && sampler.is_none(),
+                            }
+                    });
+            if unchanged {
+                return Some(cache.bindings.clone());
+            }
         }
 
-        rpass.set_pipeline(&self.pipeline);
-        rpass.set_bind_group(0, &self.uniform_bind_group, &[]);
-        rpass.set_vertex_buffer(0, render_data.vertex_buffer.as_ref().unwrap().slice(..));
-        rpass.set_index_buffer(
-            render_data.index_buffer.as_ref().unwrap().slice(..),
-            IndexFormat::Uint16,
-        );
+        // Every element of the array has to be bound, so fill the rest with the first texture.
+        let mut views: Vec<&wgpu::TextureView> =
+            textures.iter().map(|(_, view, _)| &**view).collect();
+        let mut samplers: Vec<&Sampler> = textures
+            .iter()
+            .map(|(_, _, sampler)| sampler.as_deref().unwrap_or(&self.default_sampler))
+            .collect();
+        views.resize(self.max_textures as usize, views[0]);
+        samplers.resize(self.max_textures as usize, samplers[0]);
 
-        // Execute all the imgui render work.
-        for (draw_list, bases) in draw_data
-            .draw_lists()
-            .zip(render_data.draw_list_offsets.iter())
-        {
-            self.render_draw_list(
-                rpass,
-                draw_list,
-                render_data.fb_size,
-                draw_data.display_pos,
-                draw_data.framebuffer_scale,
-                *bases,
-            )?;
-        }
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("imgui-wgpu bindless bind group"),
+            layout: &self.texture_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureViewArray(&views),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::SamplerArray(&samplers),
+                },
+            ],
+        });
 
-        Ok(())
+        let bindings = Arc::new(BindlessBindings { bind_group, slots });
+        *cache = Some(BindlessCache {
+            textures,
+            bindings: bindings.clone(),
+        });
+        Some(bindings)
     }
+}
 
-    /// Render the current imgui frame.
-    pub fn render<'r>(
-        &'r mut self,
-        draw_data: &DrawData,
-        queue: &Queue,
-        device: &Device,
-        rpass: &mut RenderPass<'r>,
-    ) -> RendererResult<()> {
-        let render_data = self.render_data.take();
-        self.render_data = Some(self.prepare(draw_data, render_data, queue, device));
-        self.split_render(draw_data, self.render_data.as_ref().unwrap(), rpass)
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naga::back::pipeline_constants::process_overrides;
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+    use naga::{Expression, Function, Handle, Literal, Module, Statement};
 
-    /// Render a given `DrawList` from imgui onto a wgpu frame.
-    fn render_draw_list<'render>(
-        &'render self,
-        rpass: &mut RenderPass<'render>,
-        draw_list: &DrawList,
-        fb_size: [f32; 2],
-        clip_off: [f32; 2],
-        clip_scale: [f32; 2],
-        (vertex_base, index_base): (i32, u32),
-    ) -> RendererResult<()> {
-        let mut start = index_base;
+    /// The number of times `srgb_to_linear` is applied to the vertex colors by `vs_main`
+    /// and `fragment_entry_point` of the default shaders, including the functions they call.
+    fn vertex_color_conversions(linearize: bool, fragment_entry_point: &str) -> usize {
+        let module = naga::front::wgsl::parse_str(shaders::source(&shaders::DEFAULT)).unwrap();
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .unwrap();
+        let constants = [(
+            LINEARIZE_VERTEX_COLORS_CONSTANT.to_owned(),
+            f64::from(u8::from(linearize)),
+        )]
+        .into();
+        let (module, _) = process_overrides(&module, &info, &constants).unwrap();
+        let srgb_to_linear = module
+            .functions
+            .fetch_if(|function| function.name.as_deref() == Some("srgb_to_linear"))
+            .unwrap();
 
-        for cmd in draw_list.commands() {
-            if let Elements { count, cmd_params } = cmd {
-                let clip_rect = [
-                    (cmd_params.clip_rect[0] - clip_off[0]) * clip_scale[0],
-                    (cmd_params.clip_rect[1] - clip_off[1]) * clip_scale[1],
-                    (cmd_params.clip_rect[2] - clip_off[0]) * clip_scale[0],
-                    (cmd_params.clip_rect[3] - clip_off[1]) * clip_scale[1],
-                ];
-
-                // Set the current texture bind group on the renderpass.
-                let texture_id = cmd_params.texture_id;
-                let tex = self
-                    .textures
-                    .get(texture_id)
-                    .ok_or(RendererError::BadTexture(texture_id))?;
-                rpass.set_bind_group(1, Some(tex.bind_group.as_ref()), &[]);
-
-                // Set scissors on the renderpass.
-                let end = start + count as u32;
-                if clip_rect[0] < fb_size[0]
-                    && clip_rect[1] < fb_size[1]
-                    && clip_rect[2] >= 0.0
-                    && clip_rect[3] >= 0.0
-                {
-                    let scissors = (
-                        clip_rect[0].max(0.0).floor() as u32,
-                        clip_rect[1].max(0.0).floor() as u32,
-                        (clip_rect[2].min(fb_size[0]) - clip_rect[0].max(0.0))
-                            .abs()
-                            .ceil() as u32,
-                        (clip_rect[3].min(fb_size[1]) - clip_rect[1].max(0.0))
-                            .abs()
-                            .ceil() as u32,
-                    );
-
-                    // XXX: Work-around for wgpu issue [1] by only issuing draw
-                    // calls if the scissor rect is valid (by wgpu's flawed
-                    // logic). Regardless, a zero-width or zero-height scissor
-                    // is essentially a no-op render anyway, so just skip it.
-                    // [1]: https://github.com/gfx-rs/wgpu/issues/1750
-                    if scissors.2 > 0 && scissors.3 > 0 {
-                        rpass.set_scissor_rect(scissors.0, scissors.1, scissors.2, scissors.3);
+        [VS_ENTRY_POINT, fragment_entry_point]
+            .iter()
+            .map(|&name| {
+                let function = &module
+                    .entry_points
+                    .iter()
+                    .find(|entry_point| entry_point.name == name)
+                    .unwrap()
+                    .function;
+                selected_conversions(&module, function, srgb_to_linear)
+            })
+            .sum()
+    }
 
-                        // Draw the current batch of vertices with the renderpass.
-                        rpass.draw_indexed(start..end, vertex_base, 0..1);
-                    }
+    /// The number of selects choosing the result of `srgb_to_linear` in `function` and
+    /// the functions it calls.
+    fn selected_conversions(
+        module: &Module,
+        function: &Function,
+        srgb_to_linear: Handle<Function>,
+    ) -> usize {
+        let converts = |expression| {
+            matches!(
+                function.expressions[expression],
+                Expression::CallResult(called) if called == srgb_to_linear
+            )
+        };
+        let selected = function
+            .expressions
+            .iter()
+            .filter_map(|(_, expression)| match *expression {
+                Expression::Select {
+                    condition,
+                    accept,
+                    reject,
+                } if converts(accept) || converts(reject) => {
+                    let chosen = if constant_bool(module, &function.expressions[condition]) {
+                        accept
+                    } else {
+                        reject
+                    };
+                    Some(chosen)
                 }
+                _ => None,
+            })
+            .filter(|&chosen| converts(chosen))
+            .count();
+        let called: usize = function
+            .body
+            .iter()
+            .filter_map(|statement| match *statement {
+                Statement::Call { function, .. } => Some(function),
+                _ => None,
+            })
+            .map(|called| selected_conversions(module, &module.functions[called], srgb_to_linear))
+            .sum();
+        selected + called
+    }
 
-                // Increment the index regardless of whether or not this batch
-                // of vertices was drawn.
-                start = end;
-            }
+    /// The value of a boolean constant expression of a function after the overrides were
+    /// processed.
+    fn constant_bool(module: &Module, expression: &Expression) -> bool {
+        match *expression {
+            Expression::Literal(Literal::Bool(value)) => value,
+            Expression::Constant(constant) => constant_bool(
+                module,
+                &module.global_expressions[module.constants[constant].init],
+            ),
+            ref expression => panic!("{expression:?} isn't a constant boolean"),
         }
-        Ok(())
     }
 
-    /// Updates the current uniform buffer containing the transform matrix.
-    fn update_uniform_buffer(&self, queue: &Queue, matrix: &[[f32; 4]; 4]) {
-        let data = bytemuck::bytes_of(matrix);
-        queue.write_buffer(&self.uniform_buffer, 0, data);
-    }
+    #[test]
+    fn shrinks_buffers_after_enough_underused_frames() {
+        let policy = BufferShrinkPolicy::default();
+        let mut underused_frames = 0;
+        for _ in 1..policy.frames {
+            assert!(!policy.should_shrink(1000, 249, &mut underused_frames));
+        }
+        assert!(policy.should_shrink(1000, 249, &mut underused_frames));
+        assert_eq!(underused_frames, 0);
 
-    /// Updates the texture on the GPU corresponding to the current imgui font atlas.
-    ///
-    /// This has to be called after loading a font.
-    pub fn reload_font_texture(&mut self, imgui: &mut Context, device: &Device, queue: &Queue) {
-        let fonts = imgui.fonts();
-        // Remove possible font atlas texture.
-        self.textures.remove(fonts.tex_id);
+        // A single frame using enough of the buffer starts the count again.
+        for _ in 1..policy.frames {
+            assert!(!policy.should_shrink(1000, 0, &mut underused_frames));
+        }
+        assert!(!policy.should_shrink(1000, 250, &mut underused_frames));
+        assert!(!policy.should_shrink(1000, 0, &mut underused_frames));
+        assert_eq!(underused_frames, 1);
+    }
 
-        // Create font texture and upload it.
-        let handle = fonts.build_rgba32_texture();
-        let font_texture_cnfig = TextureConfig {
-            label: Some("imgui-wgpu font atlas"),
-            size: Extent3d {
-                width: handle.width,
-                height: handle.height,
-                ..Default::default()
-            },
+    #[test]
+    fn shrinks_buffers_without_delay() {
+        let policy = BufferShrinkPolicy {
+            frames: 0,
             ..Default::default()
         };
+        let mut underused_frames = 0;
+        assert!(!policy.should_shrink(1000, 1000, &mut underused_frames));
+        assert!(policy.should_shrink(1000, 10, &mut underused_frames));
+    }
 
-        let font_texture = Texture::new(device, self, font_texture_cnfig);
-        font_texture.write(queue, handle.data, handle.width, handle.height);
-        fonts.tex_id = self.textures.insert(font_texture);
-        // Clear imgui texture data to save memory.
-        fonts.clear_tex_data();
+    #[test]
+    fn linear_output_converts_vertex_colors_once() {
+        for linearize in [false, true] {
+            assert_eq!(
+                vertex_color_conversions(linearize, FS_ENTRY_POINT_LINEAR),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn srgb_output_converts_vertex_colors_if_linearized() {
+        assert_eq!(vertex_color_conversions(false, FS_ENTRY_POINT_SRGB), 0);
+        assert_eq!(vertex_color_conversions(true, FS_ENTRY_POINT_SRGB), 1);
     }
 }
@@ -0,0 +1,299 @@
+//! Creating textures from texture container files.
+
+use crate::{Renderer, Texture, TextureConfig, TextureKind};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use wgpu::*;
+
+/// An error loading a texture from a file.
+#[derive(Clone, Debug)]
+pub enum TextureLoadError {
+    /// The data is not a valid file of the expected type.
+    Invalid(&'static str),
+    /// The file uses a format or feature that can't be loaded.
+    Unsupported(String),
+    /// The texture format requires device features that are not enabled.
+    MissingFeatures(Features),
+}
+
+impl fmt::Display for TextureLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureLoadError::Invalid(reason) => {
+                write!(f, "imgui texture load error: invalid file, {reason}")
+            }
+            TextureLoadError::Unsupported(what) => {
+                write!(f, "imgui texture load error: unsupported {what}")
+            }
+            TextureLoadError::MissingFeatures(features) => {
+                write!(f, "imgui texture load error: requires {features:?}")
+            }
+        }
+    }
+}
+
+impl Error for TextureLoadError {}
+
+/// The layout and mip levels of a texture parsed from a file, before it is uploaded.
+pub(crate) struct TextureData<'a> {
+    pub format: TextureFormat,
+    /// The size of the first mip level, with the layers of all faces in
+    /// `depth_or_array_layers` for 2D textures.
+    pub size: Extent3d,
+    pub dimension: TextureDimension,
+    /// Whether the layers are the six faces of a cube.
+    pub cube: bool,
+    /// The data of each mip level, with all layers of a level one after another.
+    pub levels: Vec<Cow<'a, [u8]>>,
+}
+
+impl TextureData<'_> {
+    /// The kind of texture displaying this data.
+    fn kind(&self) -> Result<TextureKind, TextureLoadError> {
+        let layers = self.size.depth_or_array_layers;
+        let sample_type = self.format.sample_type(None, None);
+        Ok(match sample_type {
+            Some(TextureSampleType::Uint) if layers == 1 => TextureKind::Uint,
+            Some(TextureSampleType::Sint) if layers == 1 => TextureKind::Sint,
+            Some(TextureSampleType::Float { filterable: false }) if layers == 1 => {
+                TextureKind::NonFilterable
+            }
+            Some(TextureSampleType::Float { filterable: true }) => {
+                match (self.dimension, self.cube, layers) {
+                    (TextureDimension::D3, _, _) => TextureKind::Volume,
+                    (_, true, 6) => TextureKind::Cube,
+                    (_, true, _) => return Err(unsupported("cube array")),
+                    (_, false, 1) => TextureKind::Filterable,
+                    (_, false, _) => TextureKind::Array,
+                }
+            }
+            _ => return Err(unsupported(format!("texture format {:?}", self.format))),
+        })
+    }
+
+    /// Check that a texture of this size can be created with `limits`, since an invalid
+    /// `create_texture` panics with the default error handler.
+    fn check_limits(&self, limits: &Limits) -> Result<(), TextureLoadError> {
+        let size = self.size;
+        let (max_size, max_layers) = match self.dimension {
+            TextureDimension::D3 => (
+                limits.max_texture_dimension_3d,
+                limits.max_texture_dimension_3d,
+            ),
+            _ => (
+                limits.max_texture_dimension_2d,
+                limits.max_texture_array_layers,
+            ),
+        };
+        if size.width > max_size
+            || size.height > max_size
+            || size.depth_or_array_layers > max_layers
+        {
+            return Err(unsupported(format!(
+                "size {}x{}x{} on this device",
+                size.width, size.height, size.depth_or_array_layers
+            )));
+        }
+        check_mip_levels(size, self.dimension, self.levels.len())?;
+        if self.cube && size.width != size.height {
+            return Err(TextureLoadError::Invalid("cube faces aren't square"));
+        }
+        Ok(())
+    }
+
+    /// Create a texture and upload all mip levels.
+    pub fn upload(
+        self,
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        label: Option<&str>,
+    ) -> Result<Texture, TextureLoadError> {
+        let missing_features = self.format.required_features() - device.features();
+        if !missing_features.is_empty() {
+            return Err(TextureLoadError::MissingFeatures(missing_features));
+        }
+        self.check_limits(&device.limits())?;
+        let kind = self.kind()?;
+
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: self.size.physical_size(self.format),
+                label,
+                format: Some(self.format),
+                mip_level_count: self.levels.len() as u32,
+                dimension: self.dimension,
+                kind,
+                ..Default::default()
+            },
+        );
+
+        for (level, data) in self.levels.iter().enumerate() {
+            let level = level as u32;
+            let (extent, bytes_per_row, rows) =
+                level_layout(self.format, self.size, self.dimension, level)?;
+            let expected =
+                bytes_per_row as usize * rows as usize * extent.depth_or_array_layers as usize;
+            if data.len() < expected {
+                return Err(TextureLoadError::Invalid("mip level data is truncated"));
+            }
+
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: texture.texture(),
+                    mip_level: level,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &data[..expected],
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(rows),
+                },
+                extent,
+            );
+        }
+
+        Ok(texture)
+    }
+}
+
+/// The copy size of mip `level` of a texture whose first level has `size`, and the bytes
+/// per row and number of rows of each of its layers or depth slices.
+pub(crate) fn level_layout(
+    format: TextureFormat,
+    size: Extent3d,
+    dimension: TextureDimension,
+    level: u32,
+) -> Result<(Extent3d, u32, u32), TextureLoadError> {
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format
+        .block_copy_size(None)
+        .ok_or_else(|| unsupported(format!("texture format {format:?}")))?;
+    let extent = size.mip_level_size(level, dimension);
+    let blocks_wide = extent.width.div_ceil(block_width);
+    let blocks_high = extent.height.div_ceil(block_height);
+    let bytes_per_row = blocks_wide.checked_mul(block_size).ok_or(TOO_LARGE)?;
+    let extent = Extent3d {
+        width: blocks_wide.checked_mul(block_width).ok_or(TOO_LARGE)?,
+        height: blocks_high.checked_mul(block_height).ok_or(TOO_LARGE)?,
+        ..extent
+    };
+    Ok((extent, bytes_per_row, blocks_high))
+}
+
+/// The size in bytes of mip `level` with all its layers or depth slices, see
+/// [`level_layout`].
+pub(crate) fn level_size(
+    format: TextureFormat,
+    size: Extent3d,
+    dimension: TextureDimension,
+    level: u32,
+) -> Result<usize, TextureLoadError> {
+    let (extent, bytes_per_row, rows) = level_layout(format, size, dimension, level)?;
+    (bytes_per_row as usize)
+        .checked_mul(rows as usize)
+        .and_then(|size| size.checked_mul(extent.depth_or_array_layers as usize))
+        .ok_or(TOO_LARGE)
+}
+
+/// Reject more mip levels than a texture of `size` has, before their sizes are computed.
+pub(crate) fn check_mip_levels(
+    size: Extent3d,
+    dimension: TextureDimension,
+    level_count: usize,
+) -> Result<(), TextureLoadError> {
+    if level_count > size.max_mips(dimension) as usize {
+        return Err(TextureLoadError::Invalid(
+            "more mip levels than the size allows",
+        ));
+    }
+    Ok(())
+}
+
+/// The error for sizes that overflow when computing the size of the data.
+const TOO_LARGE: TextureLoadError = TextureLoadError::Invalid("texture is too large");
+
+/// An [`TextureLoadError::Unsupported`] error.
+pub(crate) fn unsupported(what: impl Into<String>) -> TextureLoadError {
+    TextureLoadError::Unsupported(what.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(
+        size: Extent3d,
+        dimension: TextureDimension,
+        level_count: usize,
+    ) -> TextureData<'static> {
+        TextureData {
+            format: TextureFormat::Rgba8Unorm,
+            size,
+            dimension,
+            cube: false,
+            levels: vec![Cow::Borrowed(&[]); level_count],
+        }
+    }
+
+    fn size(width: u32, height: u32, depth_or_array_layers: u32) -> Extent3d {
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers,
+        }
+    }
+
+    #[test]
+    fn computes_level_sizes_in_whole_blocks() {
+        let size = size(10, 6, 2);
+        let bytes = |format, level| level_size(format, size, TextureDimension::D2, level);
+        assert_eq!(bytes(TextureFormat::Rgba8Unorm, 0).unwrap(), 10 * 6 * 4 * 2);
+        assert_eq!(bytes(TextureFormat::Rgba8Unorm, 1).unwrap(), 5 * 3 * 4 * 2);
+        // 3x2 blocks of 8 bytes, then a single block for the 5x3 level.
+        assert_eq!(
+            bytes(TextureFormat::Bc1RgbaUnorm, 0).unwrap(),
+            3 * 2 * 8 * 2
+        );
+        assert_eq!(bytes(TextureFormat::Bc1RgbaUnorm, 1).unwrap(), 2 * 8 * 2);
+        assert!(bytes(TextureFormat::Depth24Plus, 0).is_err());
+
+        let volume = level_size(TextureFormat::R8Unorm, size, TextureDimension::D3, 1);
+        assert_eq!(volume.unwrap(), 5 * 3);
+    }
+
+    #[test]
+    fn checks_the_limits_of_the_device() {
+        let limits = Limits::downlevel_defaults();
+        let max = limits.max_texture_dimension_2d;
+        let check = |size, dimension, levels| data(size, dimension, levels).check_limits(&limits);
+        assert!(check(size(max, max, 1), TextureDimension::D2, 1).is_ok());
+        assert!(matches!(
+            check(size(max + 1, 1, 1), TextureDimension::D2, 1),
+            Err(TextureLoadError::Unsupported(_))
+        ));
+        assert!(check(
+            size(1, 1, limits.max_texture_array_layers + 1),
+            TextureDimension::D2,
+            1
+        )
+        .is_err());
+        let max_3d = limits.max_texture_dimension_3d;
+        assert!(check(size(max_3d, max_3d, max_3d), TextureDimension::D3, 1).is_ok());
+        assert!(check(size(max_3d, max_3d, max_3d + 1), TextureDimension::D3, 1).is_err());
+
+        assert!(check(size(4, 4, 1), TextureDimension::D2, 3).is_ok());
+        assert!(matches!(
+            check(size(4, 4, 1), TextureDimension::D2, 4),
+            Err(TextureLoadError::Invalid(_))
+        ));
+        let mut cube = data(size(4, 2, 6), TextureDimension::D2, 1);
+        cube.cube = true;
+        assert!(cube.check_limits(&limits).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Accounting for the GPU memory of the renderer.
+
+use crate::{Renderer, Texture, TextureRegistry};
+use wgpu::*;
+
+/// Called when the memory used by the renderer exceeds its budget, see
+/// [`Renderer::set_memory_budget`]. It can evict textures from the registry.
+pub type MemoryBudgetCallback = Box<dyn FnMut(&MemoryReport, &mut TextureRegistry) + Send + Sync>;
+
+/// The GPU memory allocated by the renderer in bytes, see [`Renderer::memory_usage`].
+///
+/// The sizes are computed from the formats and sizes of the resources, the driver may
+/// allocate more for alignment or metadata.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// All textures except the font atlas.
+    pub textures: u64,
+    /// The font atlas texture.
+    pub font_atlas: u64,
+    /// The vertex buffer of [`Renderer::render`].
+    pub vertex_buffers: u64,
+    /// The index buffer of [`Renderer::render`].
+    pub index_buffers: u64,
+}
+
+impl MemoryReport {
+    /// The memory of all resources.
+    pub fn total(&self) -> u64 {
+        self.textures + self.font_atlas + self.vertex_buffers + self.index_buffers
+    }
+}
+
+impl Texture {
+    /// The memory of all mip levels and samples of the underlying `wgpu::Texture` in
+    /// bytes, or 0 if the texture doesn't own one.
+    ///
+    /// Only the luma plane of [`VideoTexture`](crate::VideoTexture)s is counted.
+    pub fn memory_size(&self) -> u64 {
+        let Some(texture) = &self.texture else {
+            return 0;
+        };
+        let format = texture.format();
+        // Combined depth-stencil formats have no copy size of their own.
+        let block_size = format.block_copy_size(None).unwrap_or_else(|| {
+            [TextureAspect::DepthOnly, TextureAspect::StencilOnly]
+                .into_iter()
+                .filter_map(|aspect| format.block_copy_size(Some(aspect)))
+                .sum()
+        });
+        let (block_width, block_height) = format.block_dimensions();
+        let level_sizes: u64 = (0..texture.mip_level_count())
+            .map(|level| {
+                let size = texture.size().mip_level_size(level, texture.dimension());
+                size.width.div_ceil(block_width) as u64
+                    * size.height.div_ceil(block_height) as u64
+                    * size.depth_or_array_layers as u64
+            })
+            .sum();
+        level_sizes * block_size as u64 * texture.sample_count() as u64
+    }
+}
+
+impl Renderer {
+    /// The GPU memory currently allocated by the renderer.
+    ///
+    /// Buffers of [`RenderData`](crate::RenderData) created by the application with
+    /// [`Self::prepare`] are not included.
+    pub fn memory_usage(&self) -> MemoryReport {
+        let mut report = MemoryReport::default();
+        for (id, texture) in self.textures.iter() {
+            if self.is_font_texture(id) {
+                report.font_atlas += texture.memory_size();
+            } else {
+                report.textures += texture.memory_size();
+            }
+        }
+        if let Some(render_data) = &self.render_data {
+            report.vertex_buffers = render_data.vertex_buffer.as_ref().map_or(0, Buffer::size);
+            report.index_buffers = render_data.index_buffer.as_ref().map_or(0, Buffer::size);
+        }
+        report
+    }
+
+    /// Call `on_exceeded` from [`Self::begin_frame`] whenever [`Self::memory_usage`]
+    /// exceeds `budget` bytes, e.g. to evict cached textures or report an error. Pass
+    /// `None` to remove the budget.
+    ///
+    /// This happens before the frame is prepared, textures it removes that are drawn by
+    /// the frame are handled like other missing textures.
+    pub fn set_memory_budget(&mut self, budget: Option<(u64, MemoryBudgetCallback)>) {
+        self.memory_budget = budget;
+    }
+
+    /// Call the budget callback if the memory usage exceeds the budget.
+    pub(crate) fn check_memory_budget(&mut self) {
+        let Some((budget, _)) = &self.memory_budget else {
+            return;
+        };
+        let report = self.memory_usage();
+        if report.total() > *budget {
+            if let Some((_, on_exceeded)) = &mut self.memory_budget {
+                on_exceeded(&report, &mut self.textures);
+            }
+        }
+    }
+}
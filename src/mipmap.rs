@@ -0,0 +1,215 @@
+//! Mipmap generation for textures uploaded at full resolution.
+
+use crate::Renderer;
+use std::borrow::Cow;
+use std::sync::atomic::Ordering;
+use wgpu::*;
+
+impl Renderer {
+    /// Regenerate the mip levels of the textures created with
+    /// [`TextureConfig::auto_mipmaps`](crate::TextureConfig::auto_mipmaps) whose first
+    /// level was written since the last frame.
+    pub(crate) fn regenerate_mipmaps(&self, queue: &Queue, device: &Device) {
+        let mut encoder = None;
+        for (_, texture) in self.textures_iter() {
+            let Some(stale) = &texture.mipmaps_stale else {
+                continue;
+            };
+            if stale.swap(false, Ordering::Relaxed) {
+                let encoder = encoder.get_or_insert_with(|| {
+                    device.create_command_encoder(&CommandEncoderDescriptor {
+                        label: Some("imgui-wgpu mipmap encoder"),
+                    })
+                });
+                texture.generate_mipmaps(device, encoder);
+            }
+        }
+        // Submitted right away, so it runs after the pending writes and before the frame.
+        if let Some(encoder) = encoder {
+            queue.submit(Some(encoder.finish()));
+        }
+    }
+}
+
+/// The WGSL name of `format` if textures of it can be written as storage textures.
+pub(crate) fn storage_format(device: &Device, format: TextureFormat) -> Option<&'static str> {
+    match format {
+        TextureFormat::Rgba8Unorm => Some("rgba8unorm"),
+        TextureFormat::Rgba8Snorm => Some("rgba8snorm"),
+        TextureFormat::Rgba16Float => Some("rgba16float"),
+        TextureFormat::Rgba32Float => Some("rgba32float"),
+        TextureFormat::R32Float => Some("r32float"),
+        TextureFormat::Rg32Float => Some("rg32float"),
+        TextureFormat::Bgra8Unorm if device.features().contains(Features::BGRA8UNORM_STORAGE) => {
+            Some("bgra8unorm")
+        }
+        _ => None,
+    }
+}
+
+/// A view of a single mip level of a single layer of `texture`.
+fn mip_view(texture: &wgpu::Texture, layer: u32, level: u32) -> TextureView {
+    texture.create_view(&TextureViewDescriptor {
+        label: Some("imgui-wgpu mipmap view"),
+        dimension: Some(TextureViewDimension::D2),
+        base_mip_level: level,
+        mip_level_count: Some(1),
+        base_array_layer: layer,
+        array_layer_count: Some(1),
+        ..Default::default()
+    })
+}
+
+/// A pipeline drawing a texture bound with a sampler to the whole target of `format`.
+pub(crate) fn blit_pipeline(device: &Device, format: TextureFormat) -> RenderPipeline {
+    let shader_module = device.create_shader_module(include_wgsl!("mipmap.wgsl"));
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("imgui-wgpu blit pipeline"),
+        layout: None,
+        vertex: VertexState {
+            module: &shader_module,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[],
+        },
+        fragment: Some(FragmentState {
+            module: &shader_module,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(format.into())],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// A linearly filtering sampler for [`blit_pipeline`].
+pub(crate) fn blit_sampler(device: &Device) -> Sampler {
+    device.create_sampler(&SamplerDescriptor {
+        label: Some("imgui-wgpu blit sampler"),
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        ..Default::default()
+    })
+}
+
+/// Draw `source` to the whole `target` with `pipeline` from [`blit_pipeline`].
+pub(crate) fn blit_view(
+    device: &Device,
+    encoder: &mut CommandEncoder,
+    pipeline: &RenderPipeline,
+    sampler: &Sampler,
+    source: &TextureView,
+    target: &TextureView,
+) {
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("imgui-wgpu blit bind group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(source),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+        ],
+    });
+
+    let mut rpass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("imgui-wgpu blit pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::TRANSPARENT),
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    rpass.set_pipeline(pipeline);
+    rpass.set_bind_group(0, &bind_group, &[]);
+    rpass.draw(0..3, 0..1);
+}
+
+/// Render each mip level of every layer of `texture` from the previous level with a
+/// linearly filtered blit.
+pub(crate) fn blit(device: &Device, encoder: &mut CommandEncoder, texture: &wgpu::Texture) {
+    let pipeline = blit_pipeline(device, texture.format());
+    let sampler = blit_sampler(device);
+
+    for layer in 0..texture.depth_or_array_layers() {
+        for level in 1..texture.mip_level_count() {
+            let src_view = mip_view(texture, layer, level - 1);
+            let dst_view = mip_view(texture, layer, level);
+            blit_view(device, encoder, &pipeline, &sampler, &src_view, &dst_view);
+        }
+    }
+}
+
+/// Compute each mip level of every layer of `texture` from the previous level in a single
+/// compute pass, with one dispatch per level. `storage_format` is the WGSL name of the
+/// format of `texture`, see [`storage_format`].
+pub(crate) fn compute(
+    device: &Device,
+    encoder: &mut CommandEncoder,
+    texture: &wgpu::Texture,
+    storage_format: &str,
+) {
+    let source = include_str!("mipmap_compute.wgsl").replace("rgba8unorm", storage_format);
+    let shader_module = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("imgui-wgpu mipmap compute shader"),
+        source: ShaderSource::Wgsl(Cow::Owned(source)),
+    });
+    let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+        label: Some("imgui-wgpu mipmap compute pipeline"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("cs_main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+    let bind_group_layout = pipeline.get_bind_group_layout(0);
+
+    // Create all bind groups up front, since the pass borrows them.
+    let mut bind_groups = Vec::new();
+    for layer in 0..texture.depth_or_array_layers() {
+        for level in 1..texture.mip_level_count() {
+            let src_view = mip_view(texture, layer, level - 1);
+            let dst_view = mip_view(texture, layer, level);
+            let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                label: Some("imgui-wgpu mipmap bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&src_view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&dst_view),
+                    },
+                ],
+            });
+            let size = texture.size().mip_level_size(level, texture.dimension());
+            bind_groups.push((bind_group, size));
+        }
+    }
+
+    let mut cpass = encoder.begin_compute_pass(&ComputePassDescriptor {
+        label: Some("imgui-wgpu mipmap pass"),
+        timestamp_writes: None,
+    });
+    cpass.set_pipeline(&pipeline);
+    for (bind_group, size) in &bind_groups {
+        cpass.set_bind_group(0, bind_group, &[]);
+        cpass.dispatch_workgroups(size.width.div_ceil(8), size.height.div_ceil(8), 1);
+    }
+}
@@ -0,0 +1,26 @@
+struct VertexOutput {
+    @location(0) v_UV: vec2<f32>,
+    @builtin(position) v_Position: vec4<f32>,
+};
+
+// A triangle covering the whole target, with UVs from 0 to 1 on the visible part.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    out.v_UV = uv;
+    out.v_Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+@group(0) @binding(0)
+var u_Texture: texture_2d<f32>;
+@group(0) @binding(1)
+var u_Sampler: sampler;
+
+// Each target texel is halfway between four source texels, so linear filtering
+// averages them.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(u_Texture, u_Sampler, in.v_UV);
+}
@@ -0,0 +1,22 @@
+@group(0) @binding(0)
+var u_Source: texture_2d<f32>;
+// The storage format is replaced with the format of the texture.
+@group(0) @binding(1)
+var u_Target: texture_storage_2d<rgba8unorm, write>;
+
+// Average the 2x2 source texels of each target texel. The last row and column of
+// sources with odd sizes are clamped.
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(u_Target);
+    if any(id.xy >= size) {
+        return;
+    }
+    let max_texel = textureDimensions(u_Source) - 1u;
+    let texel = id.xy * 2u;
+    let sum = textureLoad(u_Source, texel, 0)
+        + textureLoad(u_Source, min(texel + vec2<u32>(1u, 0u), max_texel), 0)
+        + textureLoad(u_Source, min(texel + vec2<u32>(0u, 1u), max_texel), 0)
+        + textureLoad(u_Source, min(texel + vec2<u32>(1u, 1u), max_texel), 0);
+    textureStore(u_Target, id.xy, sum * 0.25);
+}
@@ -0,0 +1,63 @@
+//! Drawing a placeholder for textures that don't exist.
+
+use crate::{Renderer, RendererResult, Texture, TextureConfig, TextureRegistry};
+use imgui::TextureId;
+use wgpu::*;
+
+/// The size of the placeholder in pixels.
+const SIZE: u32 = 8;
+
+/// The colors of the squares of the placeholder.
+const COLORS: [[u8; 4]; 2] = [[255, 0, 255, 255], [0, 0, 0, 255]];
+
+/// Create the magenta and black checkerboard drawn instead of missing textures.
+pub(crate) fn create_placeholder(device: &Device, queue: &Queue, renderer: &Renderer) -> Texture {
+    let texture = Texture::new(
+        device,
+        renderer,
+        TextureConfig {
+            label: Some("imgui-wgpu missing texture"),
+            format: Some(TextureFormat::Rgba8Unorm),
+            ..TextureConfig::pixel_art(Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            })
+        },
+    );
+    let data: Vec<u8> = (0..SIZE * SIZE)
+        .flat_map(|i| COLORS[((i / SIZE / 2 + i % SIZE / 2) % 2) as usize])
+        .collect();
+    texture.write(queue, &data, SIZE, SIZE);
+    texture
+}
+
+impl Renderer {
+    /// The ids of the missing textures that were drawn as a placeholder by the frame
+    /// prepared last, e.g. to log them.
+    ///
+    /// See [`RendererConfig::missing_texture_placeholder`].
+    ///
+    /// [`RendererConfig::missing_texture_placeholder`]: crate::RendererConfig::missing_texture_placeholder
+    pub fn missing_textures(&self) -> Vec<TextureId> {
+        self.missing_textures.lock().unwrap().clone()
+    }
+
+    /// The texture of `id`, or the placeholder if it doesn't exist and one was created.
+    pub(crate) fn texture_or_placeholder<'a>(
+        &'a self,
+        shared: Option<&'a TextureRegistry>,
+        id: TextureId,
+    ) -> RendererResult<&'a Texture> {
+        let error = match self.textures.get_with_shared(shared, id) {
+            Ok(texture) => return Ok(texture),
+            Err(error) => error,
+        };
+        let placeholder = self.placeholder.as_ref().ok_or(error)?;
+        let mut missing = self.missing_textures.lock().unwrap();
+        if !missing.contains(&id) {
+            missing.push(id);
+        }
+        Ok(placeholder)
+    }
+}
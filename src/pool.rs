@@ -0,0 +1,165 @@
+//! Reusing textures instead of creating new ones, e.g. for resized render targets.
+
+use crate::{Renderer, Texture, TextureConfig, TextureKind};
+use imgui::TextureId;
+use std::collections::VecDeque;
+use wgpu::*;
+
+/// The properties a released texture has to match to be reused.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct PoolKey {
+    size: Extent3d,
+    format: TextureFormat,
+    usage: TextureUsages,
+    mip_level_count: u32,
+    sample_count: u32,
+    dimension: TextureDimension,
+    kind: TextureKind,
+}
+
+impl PoolKey {
+    /// The key of `texture`, or `None` if it doesn't own a `wgpu::Texture` or is a video
+    /// frame, whose chroma plane isn't tracked.
+    fn of(texture: &Texture) -> Option<Self> {
+        if texture.kind == TextureKind::Nv12 {
+            return None;
+        }
+        let wgpu_texture = texture.texture.as_ref()?;
+        Some(Self {
+            size: wgpu_texture.size(),
+            format: wgpu_texture.format(),
+            usage: wgpu_texture.usage(),
+            mip_level_count: wgpu_texture.mip_level_count(),
+            sample_count: wgpu_texture.sample_count(),
+            dimension: wgpu_texture.dimension(),
+            kind: texture.kind,
+        })
+    }
+}
+
+/// Textures released after use, handed out again by [`Self::acquire`] for the same size,
+/// format, usage and kind instead of allocating new ones.
+///
+/// Released textures are returned as they were, including their contents, sampler,
+/// view and style. The oldest ones are dropped when more than the capacity are kept.
+pub struct TexturePool {
+    free: VecDeque<(PoolKey, Texture)>,
+    capacity: usize,
+}
+
+impl TexturePool {
+    /// Create an empty pool keeping at most `capacity` released textures.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            free: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Take a released texture matching `config`, or create a new one.
+    pub fn acquire(
+        &mut self,
+        device: &Device,
+        renderer: &Renderer,
+        config: TextureConfig,
+    ) -> Texture {
+        let key = PoolKey {
+            size: config.size,
+            format: config.format.unwrap_or(renderer.config.texture_format),
+            usage: config.usage,
+            mip_level_count: config.mip_level_count,
+            sample_count: config.sample_count,
+            dimension: config.dimension,
+            kind: config.kind,
+        };
+        self.take(key)
+            .unwrap_or_else(|| Texture::new(device, renderer, config))
+    }
+
+    /// Keep `texture` to be reused by a later [`Self::acquire`].
+    ///
+    /// Textures created with [`Texture::from_view`], aliases inserted with
+    /// [`Renderer::insert_region`] and video frames are dropped instead.
+    pub fn release(&mut self, texture: Texture) {
+        let Some(key) = PoolKey::of(&texture) else {
+            return;
+        };
+        self.free.push_back((key, texture));
+        while self.free.len() > self.capacity {
+            self.free.pop_front();
+        }
+    }
+
+    /// Replace the texture `texture_id` of `renderer` with one of `size`, keeping its id,
+    /// and release the previous one, e.g. for a render target displayed in a window that
+    /// is resized.
+    ///
+    /// The new texture has the format, usage, kind, label and sampler of the previous
+    /// one, but neither its contents nor its style. Returns `false` if there is no
+    /// texture with this id or it can't be released, see [`Self::release`].
+    pub fn resize(
+        &mut self,
+        device: &Device,
+        renderer: &mut Renderer,
+        texture_id: TextureId,
+        size: Extent3d,
+    ) -> bool {
+        let Some(previous) = renderer.textures.get(texture_id) else {
+            return false;
+        };
+        let Some(key) = PoolKey::of(previous) else {
+            return false;
+        };
+        if key.size == size {
+            return true;
+        }
+
+        let key = PoolKey { size, ..key };
+        let mut texture = self.take(key).unwrap_or_else(|| {
+            let config = TextureConfig {
+                size,
+                label: previous.label(),
+                format: Some(key.format),
+                usage: key.usage,
+                mip_level_count: key.mip_level_count,
+                sample_count: key.sample_count,
+                dimension: key.dimension,
+                kind: key.kind,
+                view_desc: previous.view_desc.clone(),
+                view_formats: &previous.view_formats,
+                ..Default::default()
+            };
+            Texture::new(device, renderer, config)
+        });
+        texture.label = previous.label.clone();
+        texture.sampler = previous.sampler.clone();
+        texture.style = None;
+        texture.rebuild_bind_group(device, renderer);
+
+        if let Some(previous) = renderer.textures.replace(texture_id, texture) {
+            self.release(previous);
+        }
+        true
+    }
+
+    /// The number of released textures kept for reuse.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Whether no released textures are kept.
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// Drop all released textures.
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+
+    /// Take the most recently released texture matching `key`.
+    fn take(&mut self, key: PoolKey) -> Option<Texture> {
+        let index = self.free.iter().rposition(|(free, _)| *free == key)?;
+        self.free.remove(index).map(|(_, texture)| texture)
+    }
+}
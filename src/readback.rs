@@ -0,0 +1,119 @@
+//! Reading textures back to the CPU.
+
+use crate::Texture;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use wgpu::*;
+
+impl Texture {
+    /// Copy the first mip level of the texture to the CPU, e.g. to save it to a file.
+    ///
+    /// The returned data is tightly packed in the format of the texture, with all layers
+    /// one after another. The texture needs `TextureUsages::COPY_SRC`. The copy is
+    /// submitted to `queue` right away, but on native backends the future only completes
+    /// once the device is polled, e.g. with `device.poll(Maintain::Wait)`.
+    pub fn read(&self, device: &Device, queue: &Queue) -> impl Future<Output = Vec<u8>> {
+        let (extent, bytes_per_row, rows) = self.copy_layout(self.size.width, self.size.height);
+        let layers = self.size.depth_or_array_layers;
+        let padded_bytes_per_row = bytes_per_row.next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("imgui-wgpu readback buffer"),
+            size: padded_bytes_per_row as u64 * rows as u64 * layers as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("imgui-wgpu readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture: self.texture(),
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(rows),
+                },
+            },
+            Extent3d {
+                depth_or_array_layers: layers,
+                ..extent
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let mapped = MapFuture::default();
+        let state = mapped.state.clone();
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            let mut state = state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        async move {
+            mapped.await.expect("failed to map the readback buffer");
+
+            // Strip the padding of each row.
+            let padded = buffer.slice(..).get_mapped_range();
+            let data = padded
+                .chunks(padded_bytes_per_row as usize)
+                .flat_map(|row| &row[..bytes_per_row as usize])
+                .copied()
+                .collect();
+            drop(padded);
+            buffer.unmap();
+            data
+        }
+    }
+}
+
+/// The shared state of a [`MapFuture`] and its `map_async` callback.
+#[derive(Default)]
+struct MapState {
+    result: Option<Result<(), BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Completes when the `map_async` callback sharing its state was called.
+#[derive(Default)]
+struct MapFuture {
+    state: Arc<Mutex<MapState>>,
+}
+
+impl Future for MapFuture {
+    type Output = Result<(), BufferAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Wait for `future`, which is completed by polling `device`, e.g. the one of
+/// [`Texture::read`]. Only supported on native backends.
+pub(crate) fn block_on<T>(device: &Device, future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        device.poll(Maintain::Wait);
+        if let Poll::Ready(value) = future.as_mut().poll(&mut context) {
+            return value;
+        }
+    }
+}
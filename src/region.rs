@@ -0,0 +1,89 @@
+//! Sub-rectangles of textures, like the sprites of a sprite sheet.
+
+use crate::{Renderer, Texture};
+use imgui::TextureId;
+
+/// A sub-rectangle of a texture, e.g. a sprite of a sprite sheet or an image of a
+/// [`TextureAtlas`](crate::TextureAtlas).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureRegion {
+    /// The texture to draw.
+    pub texture_id: TextureId,
+    /// The UV coordinates of the top left corner.
+    pub uv0: [f32; 2],
+    /// The UV coordinates of the bottom right corner.
+    pub uv1: [f32; 2],
+}
+
+impl TextureRegion {
+    /// The region of `texture_id` at `position` with `size`, in pixels of a texture of
+    /// `texture_size`.
+    pub fn from_pixels(
+        texture_id: TextureId,
+        texture_size: [u32; 2],
+        position: [u32; 2],
+        size: [u32; 2],
+    ) -> Self {
+        let [width, height] = texture_size.map(|size| size as f32);
+        let [x, y] = position;
+        Self {
+            texture_id,
+            uv0: [x as f32 / width, y as f32 / height],
+            uv1: [(x + size[0]) as f32 / width, (y + size[1]) as f32 / height],
+        }
+    }
+
+    /// An image widget showing the region with `size`.
+    pub fn image(&self, size: [f32; 2]) -> imgui::Image {
+        imgui::Image::new(self.texture_id, size)
+            .uv0(self.uv0)
+            .uv1(self.uv1)
+    }
+}
+
+impl Renderer {
+    /// Insert a texture id for `region`, so it can be passed around like the id of any
+    /// other texture. Draw its images with [`Self::region`].
+    ///
+    /// The new texture shares the view and bind group of the texture of the region, so
+    /// drawing several regions of the same texture doesn't switch bind groups. The texture
+    /// of the region is pinned, since only its regions may be drawn. Returns `None` if
+    /// there is no texture with the id of the region.
+    pub fn insert_region(&mut self, region: TextureRegion) -> Option<TextureId> {
+        let texture = self.textures.get(region.texture_id)?;
+        let alias = Texture {
+            texture: None,
+            view: texture.view.clone(),
+            sampler: texture.sampler.clone(),
+            bind_group: texture.bind_group.clone(),
+            kind: texture.kind,
+            params: None,
+            size: texture.size,
+            view_desc: None,
+            style: None,
+            uv_rect: Some([region.uv0, region.uv1]),
+            label: texture.label.clone(),
+            mipmaps_stale: None,
+            view_formats: Vec::new(),
+        };
+        self.pin_texture(region.texture_id);
+        Some(self.textures.insert(alias))
+    }
+
+    /// The region of the texture `texture_id` to draw, which covers the whole texture
+    /// unless it was inserted with [`Self::insert_region`].
+    ///
+    /// Returns `None` if there is no texture with this id.
+    pub fn region(&self, texture_id: TextureId) -> Option<TextureRegion> {
+        let [uv0, uv1] = self
+            .textures
+            .get(texture_id)?
+            .uv_rect
+            .unwrap_or([[0.0, 0.0], [1.0, 1.0]]);
+        Some(TextureRegion {
+            texture_id,
+            uv0,
+            uv1,
+        })
+    }
+}
@@ -0,0 +1,270 @@
+//! The texture map of the renderer.
+
+use crate::{RendererError, Texture};
+use imgui::TextureId;
+use std::collections::HashMap;
+
+/// The low bits of a `TextureId` hold its slot, the high bits the generation of the slot,
+/// except for the highest bit, which is set for ids of shared textures.
+const SLOT_BITS: u32 = usize::BITS / 2;
+const SLOT_MASK: usize = (1 << SLOT_BITS) - 1;
+const SHARED_BIT: usize = 1 << (usize::BITS - 1);
+const GENERATION_MASK: usize = !SHARED_BIT >> SLOT_BITS;
+
+/// Split the id of a texture into its slot and generation.
+fn split(id: TextureId) -> (usize, usize) {
+    (id.id() & SLOT_MASK, (id.id() & !SHARED_BIT) >> SLOT_BITS)
+}
+
+/// The textures of a renderer, keyed by the `TextureId`s used in imgui draw commands.
+///
+/// Has the same API as `imgui::Textures`, but also allows iterating all textures.
+///
+/// The slots of removed textures are reused, but each id also holds the generation of
+/// its slot, which is incremented when a texture is removed. Ids kept after removing
+/// their texture therefore never refer to another texture, see [`Self::is_stale`].
+#[derive(Default)]
+pub struct TextureRegistry {
+    textures: HashMap<usize, Texture>,
+    slots: Slots,
+}
+
+/// The allocation of the ids of a [`TextureRegistry`].
+#[derive(Default)]
+struct Slots {
+    /// The number of times the texture of each slot was removed, whose low bits are the
+    /// current generation of the slot.
+    removals: Vec<u64>,
+    /// The slots of removed textures.
+    free: Vec<usize>,
+    /// [`SHARED_BIT`] for the textures of `SharedRendererResources`, otherwise 0.
+    tag: usize,
+}
+
+impl Slots {
+    /// Whether `id` was allocated by slots like these, shared or not.
+    fn owns(&self, id: TextureId) -> bool {
+        id.id() & SHARED_BIT == self.tag
+    }
+
+    /// The current generation of `slot`.
+    fn generation(&self, slot: usize) -> Option<usize> {
+        let removals = *self.removals.get(slot)?;
+        Some(removals as usize & GENERATION_MASK)
+    }
+
+    /// Allocate a new id, reusing the slot of a released one if possible.
+    fn allocate(&mut self) -> usize {
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.removals.push(0);
+            self.removals.len() - 1
+        });
+        assert!(slot <= SLOT_MASK, "too many textures");
+        self.tag | (self.generation(slot).unwrap() << SLOT_BITS) | slot
+    }
+
+    /// Release the slot of `id` for reuse by a later id of the next generation.
+    fn release(&mut self, id: TextureId) {
+        let (slot, generation) = split(id);
+        if self.owns(id) && self.generation(slot) == Some(generation) {
+            self.removals[slot] += 1;
+            self.free.push(slot);
+        }
+    }
+
+    /// Whether `id` was allocated and released, assuming it isn't in use.
+    fn is_released(&self, id: TextureId) -> bool {
+        let (slot, generation) = split(id);
+        let Some(&removals) = self.removals.get(slot) else {
+            return false;
+        };
+        // Once the generation wrapped around, every generation but the current one was
+        // allocated and released.
+        self.owns(id)
+            && generation != removals as usize & GENERATION_MASK
+            && (removals > GENERATION_MASK as u64 || (generation as u64) < removals)
+    }
+}
+
+impl TextureRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty registry for shared textures, whose ids don't collide with the
+    /// ids of other registries.
+    pub(crate) fn shared() -> Self {
+        Self {
+            slots: Slots {
+                tag: SHARED_BIT,
+                ..Slots::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Whether `id` belongs to a registry created with [`Self::shared`].
+    pub(crate) fn is_shared_id(id: TextureId) -> bool {
+        id.id() & SHARED_BIT != 0
+    }
+
+    /// Insert a texture and return its newly allocated id.
+    pub fn insert(&mut self, texture: Texture) -> TextureId {
+        let id = self.slots.allocate();
+        self.textures.insert(id, texture);
+        TextureId::new(id)
+    }
+
+    /// Replace the texture of `id`, returning the previous one.
+    ///
+    /// `id` should have been returned by [`Self::insert`], other ids may collide with
+    /// the ids of textures inserted later.
+    pub fn replace(&mut self, id: TextureId, texture: Texture) -> Option<Texture> {
+        self.textures.insert(id.id(), texture)
+    }
+
+    /// Remove the texture of `id`.
+    pub fn remove(&mut self, id: TextureId) -> Option<Texture> {
+        let texture = self.textures.remove(&id.id())?;
+        self.slots.release(id);
+        Some(texture)
+    }
+
+    /// The texture of `id`.
+    pub fn get(&self, id: TextureId) -> Option<&Texture> {
+        self.textures.get(&id.id())
+    }
+
+    /// The texture of `id`, mutably.
+    pub fn get_mut(&mut self, id: TextureId) -> Option<&mut Texture> {
+        self.textures.get_mut(&id.id())
+    }
+
+    /// Whether `id` refers to a texture that was removed, as opposed to an id that was
+    /// never returned by [`Self::insert`].
+    pub fn is_stale(&self, id: TextureId) -> bool {
+        !self.textures.contains_key(&id.id()) && self.slots.is_released(id)
+    }
+
+    /// The error for drawing `id` if there is no texture with this id.
+    pub(crate) fn missing(&self, id: TextureId) -> RendererError {
+        if self.is_stale(id) {
+            RendererError::StaleTexture(id)
+        } else {
+            RendererError::BadTexture(id)
+        }
+    }
+
+    /// The number of registered textures.
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Whether no textures are registered.
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// Iterate all textures with their ids, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (TextureId, &Texture)> {
+        self.textures
+            .iter()
+            .map(|(&id, texture)| (TextureId::new(id), texture))
+    }
+
+    /// Remove all textures, returning them with their ids.
+    pub fn drain(&mut self) -> impl Iterator<Item = (TextureId, Texture)> + '_ {
+        self.textures
+            .drain()
+            .map(|(id, texture)| (TextureId::new(id), texture))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allocate(slots: &mut Slots) -> TextureId {
+        TextureId::new(slots.allocate())
+    }
+
+    #[test]
+    fn reuses_released_slots_with_the_next_generation() {
+        let mut slots = Slots::default();
+        let a = allocate(&mut slots);
+        let b = allocate(&mut slots);
+        assert_eq!(split(a), (0, 0));
+        assert_eq!(split(b), (1, 0));
+
+        slots.release(a);
+        let c = allocate(&mut slots);
+        assert_eq!(split(c), (0, 1));
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn releasing_twice_frees_the_slot_once() {
+        let mut slots = Slots::default();
+        let a = allocate(&mut slots);
+        slots.release(a);
+        slots.release(a);
+        allocate(&mut slots);
+        assert_eq!(split(allocate(&mut slots)), (1, 0));
+    }
+
+    #[test]
+    fn released_ids_are_stale() {
+        let mut slots = Slots::default();
+        let a = allocate(&mut slots);
+        assert!(!slots.is_released(a));
+
+        slots.release(a);
+        let b = allocate(&mut slots);
+        assert!(slots.is_released(a));
+        assert!(!slots.is_released(b));
+        // Ids that were never allocated aren't stale.
+        assert!(!slots.is_released(TextureId::new(1)));
+        assert!(!slots.is_released(TextureId::new(2 << SLOT_BITS)));
+    }
+
+    #[test]
+    fn ids_of_other_registries_are_not_stale() {
+        let mut slots = Slots::default();
+        let a = allocate(&mut slots);
+        slots.release(a);
+
+        let mut shared = Slots {
+            tag: SHARED_BIT,
+            ..Slots::default()
+        };
+        let b = allocate(&mut shared);
+        assert!(TextureRegistry::is_shared_id(b));
+        assert!(!TextureRegistry::is_shared_id(a));
+        assert!(!shared.is_released(a));
+        shared.release(a);
+        assert_eq!(split(allocate(&mut shared)), (1, 0));
+    }
+
+    #[test]
+    fn generations_wrap_around() {
+        let mut slots = Slots::default();
+        let first = allocate(&mut slots);
+        slots.removals[0] = GENERATION_MASK as u64;
+        slots.free.clear();
+        let last = TextureId::new(slots.generation(0).unwrap() << SLOT_BITS);
+        assert_eq!(split(last), (0, GENERATION_MASK));
+
+        slots.release(last);
+        let wrapped = allocate(&mut slots);
+        assert_eq!(split(wrapped), (0, 0));
+        assert_eq!(wrapped, first);
+        assert!(!slots.is_released(wrapped));
+        assert!(slots.is_released(last));
+        assert!(slots.is_released(TextureId::new(1 << SLOT_BITS)));
+
+        slots.release(wrapped);
+        assert!(slots.is_released(wrapped));
+        assert_eq!(split(allocate(&mut slots)), (0, 1));
+    }
+}
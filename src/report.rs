@@ -0,0 +1,105 @@
+//! Rendering frames with invalid draw commands, see [`Renderer::render_lossy`].
+
+use crate::{Renderer, RendererError};
+use imgui::{DrawCmdParams, DrawData, DrawList};
+use wgpu::*;
+
+/// Why [`Renderer::render_lossy`] skipped a draw command.
+#[derive(Clone, Debug)]
+pub enum SkipReason {
+    /// The texture of the command doesn't exist.
+    Texture(RendererError),
+    /// The clip rect isn't finite or has a negative size.
+    ClipRect,
+    /// The indices of the command go past the end of its draw list, or its vertex offset
+    /// past the end of the vertices.
+    IndexOverflow,
+}
+
+/// A draw command skipped by [`Renderer::render_lossy`].
+#[derive(Clone, Debug)]
+pub struct SkippedCommand {
+    /// The index of the draw list of the command in the [`DrawData`].
+    pub draw_list: usize,
+    /// The parameters of the command.
+    pub params: DrawCmdParams,
+    /// Why the command was skipped.
+    pub reason: SkipReason,
+}
+
+/// The draw commands skipped by [`Renderer::render_lossy`].
+#[derive(Clone, Debug, Default)]
+pub struct FrameReport {
+    /// The skipped commands in the order they were recorded.
+    pub skipped: Vec<SkippedCommand>,
+}
+
+impl FrameReport {
+    /// Whether all draw commands were rendered.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Record that the command with `params` of the draw list `draw_list` was skipped.
+    pub(crate) fn skip(&mut self, draw_list: usize, params: &DrawCmdParams, reason: SkipReason) {
+        self.skipped.push(SkippedCommand {
+            draw_list,
+            params: *params,
+            reason,
+        });
+    }
+}
+
+/// Why the command with `params` drawing the `count` indices starting at `first_index`
+/// of `draw_list` can't be drawn, if it can't.
+pub(crate) fn validate_command(
+    draw_list: &DrawList,
+    params: &DrawCmdParams,
+    first_index: usize,
+    count: usize,
+) -> Option<SkipReason> {
+    let index_end = first_index.checked_add(count);
+    if index_end.is_none_or(|end| end > draw_list.idx_buffer().len())
+        || params.vtx_offset > draw_list.vtx_buffer().len()
+    {
+        return Some(SkipReason::IndexOverflow);
+    }
+    let [left, top, right, bottom] = params.clip_rect;
+    if !params.clip_rect.iter().all(|value| value.is_finite()) || right < left || bottom < top {
+        return Some(SkipReason::ClipRect);
+    }
+    None
+}
+
+impl Renderer {
+    /// Render the current imgui frame like [`Self::render`], but skip the draw commands
+    /// that can't be drawn instead of failing the whole frame, e.g. so a plugin drawing a
+    /// removed texture doesn't take down an editor.
+    ///
+    /// Besides commands with missing textures, this skips commands with clip rects that
+    /// aren't finite or have a negative size and commands whose indices or vertex offset
+    /// go past the end of their draw list. The skipped commands are returned.
+    pub fn render_lossy(
+        &mut self,
+        draw_data: &DrawData,
+        queue: &Queue,
+        device: &Device,
+        rpass: &mut RenderPass<'_>,
+    ) -> FrameReport {
+        self.begin_frame(queue, device);
+        let render_data = self.render_data.take();
+        self.render_data = Some(self.prepare(draw_data, render_data, queue, device));
+        let mut report = FrameReport::default();
+        let result = self.render_with_pipelines(
+            draw_data,
+            self.render_data.as_ref().unwrap(),
+            rpass,
+            &self.pipelines,
+            &mut |_, _| true,
+            Some(&mut report),
+        );
+        debug_assert!(result.is_ok(), "lossy rendering reports errors instead");
+        self.end_frame();
+        report
+    }
+}
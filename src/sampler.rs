@@ -0,0 +1,126 @@
+//! Sharing samplers between textures, and their defaults.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use wgpu::*;
+
+/// The sampler settings of textures created without a sampler of their own, see
+/// [`RendererConfig::sampler_defaults`](crate::RendererConfig::sampler_defaults).
+///
+/// They apply to textures whose config keeps the default `sampler_desc` of
+/// [`TextureConfig`](crate::TextureConfig). wgpu has no mip LOD bias, the LOD clamps can
+/// restrict the mip levels used instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDefaults {
+    /// How coordinates outside of the texture are handled, in all directions.
+    pub address_mode: AddressMode,
+    /// The magnification, minification and mipmap filter.
+    pub filter: FilterMode,
+    /// The maximum anisotropy from 1 to 16, only used with linear filtering.
+    pub anisotropy_clamp: u16,
+    /// The lowest mip level used.
+    pub lod_min_clamp: f32,
+    /// The highest mip level used.
+    pub lod_max_clamp: f32,
+}
+
+impl Default for SamplerDefaults {
+    /// Linear filtering clamped to the edges, like the default of `TextureConfig`.
+    fn default() -> Self {
+        Self {
+            address_mode: AddressMode::ClampToEdge,
+            filter: FilterMode::Linear,
+            anisotropy_clamp: 1,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 100.0,
+        }
+    }
+}
+
+impl SamplerDefaults {
+    /// Linear filtering with up to `level` times anisotropic filtering, which keeps
+    /// images drawn at an angle or strongly scaled down sharp.
+    pub fn anisotropic(level: u16) -> Self {
+        Self {
+            anisotropy_clamp: level,
+            ..Default::default()
+        }
+    }
+
+    /// Nearest neighbor filtering, e.g. for pixel art.
+    pub fn nearest() -> Self {
+        Self {
+            filter: FilterMode::Nearest,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the defaults to `desc`.
+    pub(crate) fn apply<'a>(&self, desc: &SamplerDescriptor<'a>) -> SamplerDescriptor<'a> {
+        let anisotropy_clamp = match self.filter {
+            FilterMode::Linear => self.anisotropy_clamp.clamp(1, 16),
+            FilterMode::Nearest => 1,
+        };
+        SamplerDescriptor {
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.filter,
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            anisotropy_clamp,
+            ..desc.clone()
+        }
+    }
+}
+
+/// The fields of a `SamplerDescriptor` except its label, which isn't `Hash` because of
+/// the float LOD clamps.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    address_modes: [AddressMode; 3],
+    filters: [FilterMode; 3],
+    lod_clamps: [u32; 2],
+    compare: Option<CompareFunction>,
+    anisotropy_clamp: u16,
+    border_color: Option<SamplerBorderColor>,
+}
+
+impl From<&SamplerDescriptor<'_>> for SamplerKey {
+    fn from(desc: &SamplerDescriptor<'_>) -> Self {
+        Self {
+            address_modes: [
+                desc.address_mode_u,
+                desc.address_mode_v,
+                desc.address_mode_w,
+            ],
+            filters: [desc.mag_filter, desc.min_filter, desc.mipmap_filter],
+            lod_clamps: [desc.lod_min_clamp.to_bits(), desc.lod_max_clamp.to_bits()],
+            compare: desc.compare,
+            anisotropy_clamp: desc.anisotropy_clamp,
+            border_color: desc.border_color,
+        }
+    }
+}
+
+/// The samplers created by a renderer, so textures with the same sampler descriptor
+/// share a single sampler.
+#[derive(Default)]
+pub(crate) struct SamplerCache {
+    samplers: Mutex<HashMap<SamplerKey, Arc<Sampler>>>,
+}
+
+impl SamplerCache {
+    /// The sampler matching `desc`, which is created on first use. The label of the
+    /// descriptor used first is kept.
+    pub(crate) fn get(&self, device: &Device, desc: &SamplerDescriptor) -> Arc<Sampler> {
+        self.samplers
+            .lock()
+            .unwrap()
+            .entry(SamplerKey::from(desc))
+            .or_insert_with(|| Arc::new(device.create_sampler(desc)))
+            .clone()
+    }
+}
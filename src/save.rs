@@ -0,0 +1,124 @@
+//! Saving textures to image files.
+
+use crate::readback::block_on;
+use crate::{util, Texture};
+use image::{ColorType, ImageError, ImageFormat};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use wgpu::*;
+
+/// An error saving a texture to a file.
+#[derive(Debug)]
+pub enum TextureSaveError {
+    /// The texture format can't be converted to an image.
+    UnsupportedFormat(TextureFormat),
+    /// The texture doesn't own a `wgpu::Texture` that could be read, see
+    /// [`Texture::try_texture`].
+    BorrowedTexture,
+    /// The image couldn't be encoded or written.
+    Image(ImageError),
+}
+
+impl fmt::Display for TextureSaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureSaveError::UnsupportedFormat(format) => {
+                write!(f, "imgui texture save error: unsupported format {format:?}")
+            }
+            TextureSaveError::BorrowedTexture => {
+                write!(
+                    f,
+                    "imgui texture save error: the texture doesn't own a wgpu::Texture"
+                )
+            }
+            TextureSaveError::Image(error) => write!(f, "imgui texture save error: {error}"),
+        }
+    }
+}
+
+impl Error for TextureSaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextureSaveError::UnsupportedFormat(_) | TextureSaveError::BorrowedTexture => None,
+            TextureSaveError::Image(error) => Some(error),
+        }
+    }
+}
+
+impl From<ImageError> for TextureSaveError {
+    fn from(error: ImageError) -> Self {
+        TextureSaveError::Image(error)
+    }
+}
+
+impl Texture {
+    /// Read the first layer of the first mip level back and save it as a PNG file, e.g.
+    /// for an "export" button of a preview.
+    ///
+    /// The texture needs `TextureUsages::COPY_SRC` and one of the `Rgba8Unorm`,
+    /// `Bgra8Unorm` (both also sRGB), `R8Unorm`, `Rgba16Unorm`, `Rgba16Float` or
+    /// `Rgba32Float` formats. Float values are clamped to `0.0..=1.0` and saved with 8
+    /// bits per channel. This blocks until the GPU finished the copy, so it is only
+    /// supported on native backends, see [`Self::read`] otherwise.
+    pub fn save_png(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        path: impl AsRef<Path>,
+    ) -> Result<(), TextureSaveError> {
+        let format = self
+            .try_texture()
+            .ok_or(TextureSaveError::BorrowedTexture)?
+            .format();
+        let pixels = self.size.width as usize * self.size.height as usize;
+        let (color_type, bytes_per_pixel) = match format {
+            TextureFormat::R8Unorm => (ColorType::L8, 1),
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb => (ColorType::Rgba8, 4),
+            TextureFormat::Rgba16Unorm => (ColorType::Rgba16, 8),
+            TextureFormat::Rgba16Float => (ColorType::Rgba8, 8),
+            TextureFormat::Rgba32Float => (ColorType::Rgba8, 16),
+            _ => return Err(TextureSaveError::UnsupportedFormat(format)),
+        };
+
+        let mut data = block_on(device, self.read(device, queue));
+        data.truncate(pixels * bytes_per_pixel);
+
+        let unit = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let data = match format {
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+                data
+            }
+            // PNG encoders take 16-bit samples in native byte order.
+            TextureFormat::Rgba16Unorm => data
+                .chunks_exact(2)
+                .flat_map(|value| u16::from_le_bytes([value[0], value[1]]).to_ne_bytes())
+                .collect(),
+            TextureFormat::Rgba16Float => data
+                .chunks_exact(2)
+                .map(|value| unit(util::f16_to_f32(u16::from_le_bytes([value[0], value[1]]))))
+                .collect(),
+            TextureFormat::Rgba32Float => data
+                .chunks_exact(4)
+                .map(|value| unit(f32::from_le_bytes(value.try_into().unwrap())))
+                .collect(),
+            _ => data,
+        };
+
+        image::save_buffer_with_format(
+            path,
+            &data,
+            self.size.width,
+            self.size.height,
+            color_type,
+            ImageFormat::Png,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! Rebuilding the fonts for a new scale factor, e.g. when a window moves to a monitor with
+//! another DPI.
+
+use crate::{atlas_key, Renderer, RendererError, RendererResult};
+use imgui::internal::RawCast;
+use imgui::{Context, FontAtlas};
+use wgpu::{Device, Queue};
+
+impl Renderer {
+    /// Rebuild the fonts of `imgui` at their pixel sizes for the scale factor `new_scale`
+    /// and upload the atlas again, so text stays crisp on monitors with another DPI
+    /// instead of being scaled.
+    ///
+    /// The fonts are assumed to be added at their sizes for a scale factor of 1, or for
+    /// the scale of the previous call. Their sizes are multiplied by the change of the
+    /// scale and `Io::font_global_scale` is set to the inverse of `new_scale`, so text
+    /// keeps its size in logical points. Call it before `Context::new_frame`, like
+    /// [`Self::update_font_texture`]. The custom rects of the atlas are packed anew and
+    /// have to be filled again.
+    ///
+    /// Fails with [`RendererError::InvalidScaleFactor`] if `new_scale` isn't a finite
+    /// number greater than 0. If the atlas can't be uploaded, see
+    /// [`Self::reload_font_texture`], the fonts are rebuilt and uploaded at their previous
+    /// sizes. Should that upload fail as well, e.g. because fonts were added since the last
+    /// upload, its error is returned instead and the fonts are only half restored: they
+    /// are back at their previous sizes, but the font texture no longer matches the
+    /// rebuilt atlas until it is uploaded again with [`Self::reload_font_texture`].
+    pub fn handle_scale_factor_change(
+        &mut self,
+        imgui: &mut Context,
+        device: &Device,
+        queue: &Queue,
+        new_scale: f32,
+    ) -> RendererResult<()> {
+        if !new_scale.is_finite() || new_scale <= 0.0 {
+            return Err(RendererError::InvalidScaleFactor(new_scale));
+        }
+        let atlas = atlas_key(imgui.fonts());
+        let previous_scale = self.font_scales.get(&atlas).copied().unwrap_or(1.0);
+        let change = new_scale / previous_scale;
+
+        scale_fonts(imgui.fonts(), change);
+        // Discard the pixels, so the atlas is built again at the new sizes.
+        imgui.fonts().clear_tex_data();
+        if let Err(error) = self.reload_font_texture(imgui, device, queue) {
+            // The atlas was built at the new sizes, so build and upload it again at the
+            // previous ones to match the font texture.
+            scale_fonts(imgui.fonts(), change.recip());
+            imgui.fonts().clear_tex_data();
+            self.reload_font_texture(imgui, device, queue)?;
+            return Err(error);
+        }
+        self.font_scales.insert(atlas, new_scale);
+        imgui.io_mut().font_global_scale = new_scale.recip();
+        Ok(())
+    }
+}
+
+/// Multiply the pixel sizes of the fonts of `fonts` by `factor`, which takes effect when
+/// the atlas is built the next time.
+fn scale_fonts(fonts: &mut FontAtlas, factor: f32) {
+    // SAFETY: `FontAtlas` has the layout of `ImFontAtlas`, whose font configs are owned
+    // by the atlas. The atlas isn't locked, as it's only used outside of frames.
+    let configs = unsafe {
+        let configs = &fonts.raw_mut().ConfigData;
+        if configs.Size <= 0 {
+            return;
+        }
+        std::slice::from_raw_parts_mut(configs.Data, configs.Size as usize)
+    };
+    for config in configs {
+        config.SizePixels *= factor;
+        config.GlyphExtraSpacing.x *= factor;
+        config.GlyphExtraSpacing.y *= factor;
+        config.GlyphOffset.x *= factor;
+        config.GlyphOffset.y *= factor;
+        config.GlyphMinAdvanceX *= factor;
+        // The maximum advance is unlimited by default.
+        if config.GlyphMaxAdvanceX < f32::MAX {
+            config.GlyphMaxAdvanceX *= factor;
+        }
+    }
+}
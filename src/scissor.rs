@@ -0,0 +1,204 @@
+//! Converting imgui clip rects to scissor rects.
+
+use wgpu::RenderPass;
+
+/// How the fractional edges of imgui clip rects are rounded to whole pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScissorRounding {
+    /// Round all edges down, like the reference Dear ImGui backends.
+    Floor,
+    /// Round all edges to the nearest pixel, keeping pixels whose center is inside.
+    Round,
+    /// Round the left and top edges down and the width and height up, like previous
+    /// versions of this crate.
+    #[default]
+    Expand,
+}
+
+impl ScissorRounding {
+    /// Round the edges (left, top, right, bottom) of a rect.
+    fn round_edges(self, [left, top, right, bottom]: [f32; 4]) -> [f32; 4] {
+        match self {
+            ScissorRounding::Floor => [left.floor(), top.floor(), right.floor(), bottom.floor()],
+            ScissorRounding::Round => [left.round(), top.round(), right.round(), bottom.round()],
+            ScissorRounding::Expand => [
+                left.floor(),
+                top.floor(),
+                left.floor() + (right - left).ceil(),
+                top.floor() + (bottom - top).ceil(),
+            ],
+        }
+    }
+}
+
+/// A scissor rect in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScissorRect {
+    /// The left edge, counted from the left of the framebuffer.
+    pub x: u32,
+    /// The top edge, counted from the top of the framebuffer.
+    pub y: u32,
+    /// The width, which is 0 for empty rects.
+    pub width: u32,
+    /// The height, which is 0 for empty rects.
+    pub height: u32,
+}
+
+impl ScissorRect {
+    /// Convert an imgui clip rect (left, up, right, down) to a scissor rect in a
+    /// framebuffer of `fb_size` physical pixels.
+    ///
+    /// Returns `None` if no pixel of the clip rect is visible.
+    pub(crate) fn from_clip_rect(
+        clip_rect: [f32; 4],
+        clip_off: [f32; 2],
+        clip_scale: [f32; 2],
+        fb_size: [f32; 2],
+        rounding: ScissorRounding,
+    ) -> Option<Self> {
+        let clip_rect = [
+            (clip_rect[0] - clip_off[0]) * clip_scale[0],
+            (clip_rect[1] - clip_off[1]) * clip_scale[1],
+            (clip_rect[2] - clip_off[0]) * clip_scale[0],
+            (clip_rect[3] - clip_off[1]) * clip_scale[1],
+        ];
+
+        if clip_rect[0] >= fb_size[0]
+            || clip_rect[1] >= fb_size[1]
+            || clip_rect[2] < 0.0
+            || clip_rect[3] < 0.0
+        {
+            return None;
+        }
+
+        let [left, top, right, bottom] = rounding.round_edges([
+            clip_rect[0].max(0.0),
+            clip_rect[1].max(0.0),
+            clip_rect[2].min(fb_size[0]),
+            clip_rect[3].min(fb_size[1]),
+        ]);
+        let scissor = Self {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left).max(0.0) as u32,
+            height: (bottom - top).max(0.0) as u32,
+        };
+
+        // XXX: Work-around for wgpu issue [1] by only issuing draw
+        // calls if the scissor rect is valid (by wgpu's flawed
+        // logic). Regardless, a zero-width or zero-height scissor
+        // is essentially a no-op render anyway, so just skip it.
+        // [1]: https://github.com/gfx-rs/wgpu/issues/1750
+        (!scissor.is_empty()).then_some(scissor)
+    }
+
+    /// Whether the rect covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// The overlap of both rects, which is empty if they don't overlap.
+    pub fn intersect(&self, other: &ScissorRect) -> ScissorRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        ScissorRect {
+            x,
+            y,
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        }
+    }
+
+    /// Set the rect as the scissor rect of `rpass`.
+    pub(crate) fn apply(&self, rpass: &mut RenderPass<'_>) {
+        rpass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scissor(clip_rect: [f32; 4], rounding: ScissorRounding) -> Option<ScissorRect> {
+        ScissorRect::from_clip_rect(clip_rect, [0.0; 2], [1.0; 2], [100.0; 2], rounding)
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> ScissorRect {
+        ScissorRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn rounds_fractional_edges() {
+        let clip_rect = [10.5, 20.25, 30.5, 40.75];
+        assert_eq!(
+            scissor(clip_rect, ScissorRounding::Floor),
+            Some(rect(10, 20, 20, 20))
+        );
+        assert_eq!(
+            scissor(clip_rect, ScissorRounding::Round),
+            Some(rect(11, 20, 20, 21))
+        );
+        assert_eq!(
+            scissor(clip_rect, ScissorRounding::Expand),
+            Some(rect(10, 20, 20, 21))
+        );
+    }
+
+    #[test]
+    fn applies_offset_and_scale() {
+        let scissor = ScissorRect::from_clip_rect(
+            [10.0, 10.0, 20.0, 20.0],
+            [5.0, 5.0],
+            [2.0, 2.0],
+            [100.0; 2],
+            ScissorRounding::Floor,
+        );
+        assert_eq!(scissor, Some(rect(10, 10, 20, 20)));
+    }
+
+    #[test]
+    fn clamps_to_the_framebuffer() {
+        assert_eq!(
+            scissor([-5.0, -5.0, 200.0, 50.0], ScissorRounding::Expand),
+            Some(rect(0, 0, 100, 50))
+        );
+        assert_eq!(
+            scissor([99.5, 99.5, 120.0, 120.0], ScissorRounding::Expand),
+            Some(rect(99, 99, 1, 1))
+        );
+    }
+
+    #[test]
+    fn skips_invisible_rects() {
+        assert_eq!(
+            scissor([100.0, 0.0, 120.0, 10.0], ScissorRounding::Expand),
+            None
+        );
+        assert_eq!(
+            scissor([-20.0, 0.0, -10.0, 10.0], ScissorRounding::Expand),
+            None
+        );
+        assert_eq!(
+            scissor([10.0, 10.0, 10.25, 20.0], ScissorRounding::Floor),
+            None
+        );
+        assert_eq!(
+            scissor([10.0, 10.0, 10.25, 20.0], ScissorRounding::Expand),
+            Some(rect(10, 10, 1, 10))
+        );
+    }
+
+    #[test]
+    fn intersects_rects() {
+        let a = rect(0, 0, 50, 50);
+        assert_eq!(a.intersect(&rect(25, 10, 50, 10)), rect(25, 10, 25, 10));
+        assert!(a.intersect(&rect(60, 0, 10, 10)).is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! The built-in shaders, assembled from the parts they share.
+//!
+//! `imgui_common.wgsl` holds the vertex inputs and the handling of vertex colors,
+//! `imgui_vertex.wgsl` and `imgui_vertex_multiview.wgsl` the vertex stages,
+//! `imgui_output.wgsl` the single color target and `imgui_style.wgsl` the styling of
+//! textures set by `Texture::set_style`. The remaining files bind and sample a kind of
+//! texture in the fragment stage.
+
+use std::borrow::Cow;
+use wgpu::{ShaderModuleDescriptor, ShaderSource};
+
+/// The descriptor of a shader made of the WGSL files, labeled with the last one.
+macro_rules! shader {
+    ($($file:literal),+ => $fragment:literal) => {
+        ShaderModuleDescriptor {
+            label: Some($fragment),
+            source: ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                $(include_str!($file), "\n",)+
+                include_str!($fragment),
+            ))),
+        }
+    };
+}
+
+/// The descriptor of a shader drawing styled textures to a single color target.
+macro_rules! textured_shader {
+    ($fragment:literal) => {
+        shader!(
+            "imgui_common.wgsl",
+            "imgui_vertex.wgsl",
+            "imgui_output.wgsl",
+            "imgui_style.wgsl" => $fragment
+        )
+    };
+}
+
+/// The shader of `RendererConfig::new` and `RendererConfig::new_srgb`.
+pub(crate) const DEFAULT: ShaderModuleDescriptor<'static> = textured_shader!("imgui.wgsl");
+
+/// The shader of `RendererConfig::with_multiview`.
+pub(crate) const MULTIVIEW: ShaderModuleDescriptor<'static> = shader!(
+    "imgui_common.wgsl",
+    "imgui_vertex_multiview.wgsl",
+    "imgui_output.wgsl",
+    "imgui_style.wgsl" => "imgui.wgsl"
+);
+
+/// The shader of `RendererConfig::with_dual_source_blending`, which declares its own
+/// outputs.
+pub(crate) const DUAL_SOURCE: ShaderModuleDescriptor<'static> = shader!(
+    "imgui_common.wgsl",
+    "imgui_vertex.wgsl",
+    "imgui_style.wgsl" => "imgui_dual_source.wgsl"
+);
+
+/// The shader of the bindless pipeline, which doesn't style textures.
+pub(crate) const BINDLESS: ShaderModuleDescriptor<'static> = shader!(
+    "imgui_common.wgsl",
+    "imgui_vertex.wgsl",
+    "imgui_output.wgsl" => "imgui_bindless.wgsl"
+);
+
+// The shaders of `TextureKind::builtin_shader`.
+pub(crate) const UINT: ShaderModuleDescriptor<'static> = textured_shader!("imgui_uint.wgsl");
+pub(crate) const SINT: ShaderModuleDescriptor<'static> = textured_shader!("imgui_sint.wgsl");
+pub(crate) const ARRAY: ShaderModuleDescriptor<'static> = textured_shader!("imgui_array.wgsl");
+pub(crate) const CUBE: ShaderModuleDescriptor<'static> = textured_shader!("imgui_cube.wgsl");
+pub(crate) const VOLUME: ShaderModuleDescriptor<'static> = textured_shader!("imgui_volume.wgsl");
+pub(crate) const DEPTH: ShaderModuleDescriptor<'static> = textured_shader!("imgui_depth.wgsl");
+pub(crate) const NV12: ShaderModuleDescriptor<'static> = textured_shader!("imgui_nv12.wgsl");
+
+/// The WGSL source of a built-in shader.
+#[cfg(test)]
+pub(crate) fn source(shader: &ShaderModuleDescriptor<'static>) -> &'static str {
+    match shader.source {
+        ShaderSource::Wgsl(Cow::Borrowed(source)) => source,
+        _ => unreachable!("built-in shaders are borrowed WGSL"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FS_ENTRY_POINT_LINEAR, FS_ENTRY_POINT_SRGB, VS_ENTRY_POINT};
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    #[test]
+    fn assembled_shaders_are_valid() {
+        for shader in [
+            DEFAULT,
+            MULTIVIEW,
+            DUAL_SOURCE,
+            BINDLESS,
+            UINT,
+            SINT,
+            ARRAY,
+            CUBE,
+            VOLUME,
+            DEPTH,
+            NV12,
+        ] {
+            let label = shader.label.unwrap();
+            let module = naga::front::wgsl::parse_str(source(&shader))
+                .unwrap_or_else(|err| panic!("{label}: {err}"));
+            Validator::new(ValidationFlags::all(), Capabilities::all())
+                .validate(&module)
+                .unwrap_or_else(|err| panic!("{label}: {err:?}"));
+            for entry_point in [VS_ENTRY_POINT, FS_ENTRY_POINT_LINEAR, FS_ENTRY_POINT_SRGB] {
+                assert!(
+                    module.entry_points.iter().any(|ep| ep.name == entry_point),
+                    "{label} lacks {entry_point}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Resources shared by several renderers.
+
+use crate::{
+    create_texture_style, RendererError, Texture, TextureKind, TextureRegistry, TextureStyle,
+};
+use imgui::TextureId;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use wgpu::*;
+
+use crate::sampler::SamplerCache;
+
+/// The resources textures are created with, which have to be the same for all renderers
+/// drawing a texture.
+pub(crate) struct TextureResources {
+    /// The bind group layout of each [`TextureKind`].
+    pub(crate) layouts: Vec<BindGroupLayout>,
+    pub(crate) samplers: SamplerCache,
+    /// Bound by textures without their own [`TextureStyle`].
+    pub(crate) default_style: Buffer,
+}
+
+impl TextureResources {
+    pub(crate) fn new(device: &Device) -> Self {
+        Self {
+            layouts: TextureKind::ALL
+                .iter()
+                .map(|kind| kind.create_bind_group_layout(device))
+                .collect(),
+            samplers: SamplerCache::default(),
+            default_style: create_texture_style(device, TextureStyle::default()),
+        }
+    }
+}
+
+/// Textures and texture resources shared by several renderers, e.g. one per window or
+/// viewport, see [`Renderer::new_shared`](crate::Renderer::new_shared).
+///
+/// Textures inserted into [`Self::textures`] can be drawn by all renderers with the
+/// same `TextureId`, without creating their GPU resources once per renderer. Textures
+/// created with any of the renderers can be inserted. Each renderer still has its own
+/// [`Renderer::textures`](crate::Renderer::textures), e.g. for its font atlas, and its
+/// own pipelines, which depend on its config.
+#[derive(Clone)]
+pub struct SharedRendererResources {
+    pub(crate) resources: Arc<TextureResources>,
+    pub(crate) textures: Arc<RwLock<TextureRegistry>>,
+}
+
+impl SharedRendererResources {
+    /// Create the shared resources for renderers using `device`.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            resources: Arc::new(TextureResources::new(device)),
+            textures: Arc::new(RwLock::new(TextureRegistry::shared())),
+        }
+    }
+
+    /// The shared textures, locked for modification. Renderers block while drawing
+    /// until the lock is released.
+    pub fn textures(&self) -> RwLockWriteGuard<'_, TextureRegistry> {
+        self.textures.write().unwrap()
+    }
+}
+
+impl TextureRegistry {
+    /// The texture of `id`, looked up in `shared` if `id` belongs to shared textures.
+    pub(crate) fn get_with_shared<'a>(
+        &'a self,
+        shared: Option<&'a TextureRegistry>,
+        id: TextureId,
+    ) -> Result<&'a Texture, RendererError> {
+        let registry = if TextureRegistry::is_shared_id(id) {
+            shared.ok_or(RendererError::BadTexture(id))?
+        } else {
+            self
+        };
+        registry.get(id).ok_or_else(|| registry.missing(id))
+    }
+}
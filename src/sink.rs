@@ -0,0 +1,188 @@
+//! Displaying the frames of a video decoder or camera.
+
+use crate::{RawTextureConfig, Renderer, Texture, TextureConfig, TextureKind, VideoTexture};
+use imgui::TextureId;
+use std::sync::Arc;
+use wgpu::*;
+
+const LABEL: &str = "imgui-wgpu video sink";
+
+/// The pixels of a [`VideoFrame`].
+pub enum FramePlanes<'a> {
+    /// NV12 planes in CPU memory, see [`VideoTexture::write_frame`].
+    Nv12 {
+        /// One byte per pixel.
+        luma: &'a [u8],
+        /// Interleaved U and V bytes for each 2x2 block of pixels.
+        chroma: &'a [u8],
+    },
+    /// Tightly packed 8-bit RGBA pixels in CPU memory.
+    Rgba8(&'a [u8]),
+    /// A filterable texture already on the GPU, e.g. decoded by hardware, which is
+    /// displayed without copying it.
+    Texture(Arc<wgpu::Texture>),
+}
+
+/// A frame returned by [`FrameSource::next_frame`].
+pub struct VideoFrame<'a> {
+    /// The width of the frame in pixels.
+    pub width: u32,
+    /// The height of the frame in pixels.
+    pub height: u32,
+    /// The pixels of the frame, whose format can change from frame to frame.
+    pub planes: FramePlanes<'a>,
+}
+
+/// A decoder, camera or other producer of frames, displayed with a [`VideoTextureSink`].
+pub trait FrameSource {
+    /// The frame to display next, or `None` if the last one is still current.
+    fn next_frame(&mut self) -> Option<VideoFrame<'_>>;
+}
+
+/// The texture a [`VideoTextureSink`] uploads its frames to.
+enum SinkTarget {
+    Nv12(VideoTexture),
+    Rgba8 { width: u32, height: u32 },
+    External(Arc<wgpu::Texture>),
+}
+
+/// Pulls the frames of a [`FrameSource`] into a texture of the renderer.
+///
+/// Call [`Self::update`] once per frame and draw the returned texture. The texture is
+/// recreated under the same id when the size or format of the frames change.
+pub struct VideoTextureSink<S> {
+    source: S,
+    texture_id: Option<TextureId>,
+    target: Option<SinkTarget>,
+}
+
+impl<S: FrameSource> VideoTextureSink<S> {
+    /// Display the frames of `source`, starting with the next one.
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            texture_id: None,
+            target: None,
+        }
+    }
+
+    /// Upload the next frame of the source, if there is one, and return the texture
+    /// displaying the latest frame. Returns `None` until the source produced a frame.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        renderer: &mut Renderer,
+    ) -> Option<TextureId> {
+        let Self {
+            source,
+            texture_id,
+            target,
+        } = self;
+        let Some(frame) = source.next_frame() else {
+            return *texture_id;
+        };
+        let (width, height) = (frame.width, frame.height);
+
+        match frame.planes {
+            FramePlanes::Nv12 { luma, chroma } => {
+                let current = matches!(
+                    target,
+                    Some(SinkTarget::Nv12(video))
+                        if [video.width(), video.height()] == [width, height]
+                );
+                if !current {
+                    let video = VideoTexture::new(device, Some(LABEL), width, height);
+                    show(texture_id, renderer, video.create_texture(device, renderer));
+                    *target = Some(SinkTarget::Nv12(video));
+                }
+                if let Some(SinkTarget::Nv12(video)) = target {
+                    video.write_frame(queue, luma, chroma);
+                }
+            }
+            FramePlanes::Rgba8(data) => {
+                let current = matches!(
+                    target,
+                    Some(SinkTarget::Rgba8 { width: w, height: h }) if [*w, *h] == [width, height]
+                );
+                if !current {
+                    let config = TextureConfig {
+                        size: Extent3d {
+                            width,
+                            height,
+                            depth_or_array_layers: 1,
+                        },
+                        label: Some(LABEL),
+                        format: Some(TextureFormat::Rgba8Unorm),
+                        ..Default::default()
+                    };
+                    show(texture_id, renderer, Texture::new(device, renderer, config));
+                    *target = Some(SinkTarget::Rgba8 { width, height });
+                }
+                if let Some(texture) = texture_id.and_then(|id| renderer.textures.get(id)) {
+                    texture.write(queue, data, width, height);
+                }
+            }
+            FramePlanes::Texture(texture) => {
+                let current = matches!(
+                    target,
+                    Some(SinkTarget::External(current)) if Arc::ptr_eq(current, &texture)
+                );
+                if !current {
+                    let view = texture.create_view(&TextureViewDescriptor::default());
+                    let config = RawTextureConfig {
+                        label: Some(LABEL),
+                        sampler_desc: TextureConfig::default().sampler_desc,
+                        kind: TextureKind::Filterable,
+                    };
+                    let size = texture.size();
+                    let displayed = Texture::from_raw_parts(
+                        device,
+                        renderer,
+                        texture.clone(),
+                        Arc::new(view),
+                        None,
+                        Some(&config),
+                        size,
+                    );
+                    show(texture_id, renderer, displayed);
+                    *target = Some(SinkTarget::External(texture));
+                }
+            }
+        }
+        *texture_id
+    }
+
+    /// The texture displaying the latest frame, or `None` until the source produced one.
+    pub fn texture_id(&self) -> Option<TextureId> {
+        self.texture_id
+    }
+
+    /// The source of the frames.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// The source of the frames, mutably, e.g. to seek.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Remove the texture from the renderer and return the source.
+    pub fn remove(self, renderer: &mut Renderer) -> S {
+        if let Some(id) = self.texture_id {
+            renderer.textures.remove(id);
+        }
+        self.source
+    }
+}
+
+/// Display `texture` under `texture_id`, inserting it if there is no texture yet.
+fn show(texture_id: &mut Option<TextureId>, renderer: &mut Renderer, texture: Texture) {
+    match *texture_id {
+        Some(id) if renderer.textures.get(id).is_some() => {
+            renderer.textures.replace(id, texture);
+        }
+        _ => *texture_id = Some(renderer.textures.insert(texture)),
+    }
+}
@@ -0,0 +1,59 @@
+//! Rasterizing SVG images into textures.
+
+use crate::{Renderer, Texture, TextureConfig};
+use resvg::{tiny_skia, usvg};
+use wgpu::*;
+
+impl Texture {
+    /// Rasterize an SVG image to a texture of `target_size` pixels and upload it.
+    ///
+    /// The image is stretched to `target_size`, so pass a size with its aspect ratio
+    /// multiplied by the UI scale factor to keep icons crisp on HiDPI displays. Text is
+    /// not rendered, convert it to paths when exporting the image.
+    pub fn from_svg(
+        device: &Device,
+        queue: &Queue,
+        renderer: &Renderer,
+        bytes: &[u8],
+        target_size: [u32; 2],
+    ) -> Result<Texture, usvg::Error> {
+        let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())?;
+        let [width, height] = target_size.map(|size| size.max(1));
+
+        let mut pixmap = tiny_skia::Pixmap::new(width, height)
+            .expect("the target size is not zero and fits a pixmap");
+        let size = tree.size();
+        let transform = tiny_skia::Transform::from_scale(
+            width as f32 / size.width(),
+            height as f32 / size.height(),
+        );
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+        // imgui blends with straight alpha, but the pixmap is premultiplied.
+        let data: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+
+        let texture = Texture::new(
+            device,
+            renderer,
+            TextureConfig {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                label: Some("imgui-wgpu svg texture"),
+                format: Some(TextureFormat::Rgba8Unorm),
+                ..Default::default()
+            },
+        );
+        texture.write(queue, &data, width, height);
+        Ok(texture)
+    }
+}
@@ -0,0 +1,137 @@
+//! Spreading large texture uploads across frames.
+
+use crate::{Renderer, RendererError, RendererResult};
+use imgui::TextureId;
+use std::collections::VecDeque;
+use wgpu::*;
+
+/// The default of [`Renderer::set_upload_budget`].
+const DEFAULT_UPLOAD_BUDGET: u64 = 8 * 1024 * 1024;
+
+/// The state of [`Renderer::enqueue_upload`].
+pub(crate) struct UploadQueue {
+    /// The bytes uploaded by each frame.
+    budget: u64,
+    pending: VecDeque<PendingUpload>,
+}
+
+impl Default for UploadQueue {
+    fn default() -> Self {
+        Self {
+            budget: DEFAULT_UPLOAD_BUDGET,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// The data of a texture whose rows starting at `next_row` weren't uploaded yet.
+struct PendingUpload {
+    texture_id: TextureId,
+    data: Vec<u8>,
+    /// In rows of blocks for compressed formats.
+    next_row: u32,
+}
+
+impl Renderer {
+    /// Upload `data` to the texture `texture_id` over the next frames, within the budget
+    /// of [`Self::set_upload_budget`], instead of all at once like
+    /// [`Texture::write`](crate::Texture::write).
+    ///
+    /// `data` covers the whole first mip level like for `Texture::write`. The uploads are
+    /// done by [`Self::begin_frame`] in the order they were enqueued, the texture shows its
+    /// previous contents for the rows that weren't uploaded yet. Uploads to textures
+    /// removed or replaced by larger ones in the meantime are dropped.
+    ///
+    /// Fails if there is no texture `texture_id`, it doesn't own a `wgpu::Texture` or
+    /// `data` is too short for it.
+    pub fn enqueue_upload(&mut self, texture_id: TextureId, data: Vec<u8>) -> RendererResult<()> {
+        let texture = self
+            .textures
+            .get(texture_id)
+            .ok_or_else(|| self.textures.missing(texture_id))?;
+        let size = texture.size();
+        let (_, bytes_per_row, rows) = texture
+            .try_copy_layout(size.width, size.height)
+            .ok_or(RendererError::BorrowedTexture(texture_id))?;
+        let expected = bytes_per_row as usize * rows as usize;
+        if data.len() < expected {
+            return Err(RendererError::UploadTooShort {
+                texture_id,
+                expected,
+                actual: data.len(),
+            });
+        }
+        self.uploads.pending.push_back(PendingUpload {
+            texture_id,
+            data,
+            next_row: 0,
+        });
+        Ok(())
+    }
+
+    /// Set the number of bytes uploaded by each frame for [`Self::enqueue_upload`].
+    ///
+    /// At least one row of the oldest pending upload is uploaded every frame, even if it
+    /// is larger than the budget. The default is 8 MiB.
+    pub fn set_upload_budget(&mut self, bytes: u64) {
+        self.uploads.budget = bytes;
+    }
+
+    /// Whether parts of the data enqueued for `texture_id` weren't uploaded yet, e.g. to
+    /// show a placeholder instead.
+    pub fn is_upload_pending(&self, texture_id: TextureId) -> bool {
+        self.uploads
+            .pending
+            .iter()
+            .any(|upload| upload.texture_id == texture_id)
+    }
+
+    /// Upload the pending data within the budget of this frame.
+    pub(crate) fn process_uploads(&mut self, queue: &Queue) {
+        let mut budget = self.uploads.budget;
+        let mut progressed = false;
+        while let Some(upload) = self.uploads.pending.front_mut() {
+            let Some(texture) = self.textures.get(upload.texture_id) else {
+                self.uploads.pending.pop_front();
+                continue;
+            };
+            let size = texture.size();
+            // The texture may have been replaced by one that doesn't own a wgpu::Texture.
+            let Some((_, bytes_per_row, rows)) = texture.try_copy_layout(size.width, size.height)
+            else {
+                self.uploads.pending.pop_front();
+                continue;
+            };
+            if upload.data.len() < bytes_per_row as usize * rows as usize {
+                self.uploads.pending.pop_front();
+                continue;
+            }
+            let remaining = rows - upload.next_row;
+            let affordable = (budget / bytes_per_row.max(1) as u64).min(remaining as u64) as u32;
+            let count = match affordable {
+                0 if progressed => break,
+                0 => 1,
+                affordable => affordable,
+            };
+
+            let (_, block_height) = texture.texture().format().block_dimensions();
+            let start = upload.next_row as usize * bytes_per_row as usize;
+            let end = start + count as usize * bytes_per_row as usize;
+            texture.write_rows(
+                queue,
+                0,
+                &upload.data[start..end],
+                size.width,
+                upload.next_row * block_height,
+                count * block_height,
+            );
+            upload.next_row += count;
+            budget = budget.saturating_sub(count as u64 * bytes_per_row as u64);
+            progressed = true;
+
+            if upload.next_row >= rows {
+                self.uploads.pending.pop_front();
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Helpers for the wgpu plumbing around rendering imgui, like acquiring the frames of a
+//! surface.
+
+use crate::readback::block_on;
+use crate::Texture;
+use wgpu::{Device, Queue, Surface, SurfaceConfiguration, SurfaceError, SurfaceTexture};
+
+/// The result of [`acquire_frame`].
+#[derive(Debug)]
+pub enum FrameAcquire {
+    /// The frame is ready to be rendered to.
+    Ready(SurfaceTexture),
+    /// The frame can be rendered to, but the surface no longer matches the window
+    /// exactly and should be reconfigured when convenient.
+    Suboptimal(SurfaceTexture),
+    /// No frame is available right now, rendering should be skipped this time.
+    Skip,
+}
+
+impl FrameAcquire {
+    /// The acquired frame, if any.
+    pub fn into_frame(self) -> Option<SurfaceTexture> {
+        match self {
+            FrameAcquire::Ready(frame) | FrameAcquire::Suboptimal(frame) => Some(frame),
+            FrameAcquire::Skip => None,
+        }
+    }
+}
+
+/// Acquire the next frame of `surface`, handling the common swapchain edge cases.
+///
+/// - If the surface is outdated or lost, it is reconfigured with `config` and the
+///   acquisition is retried once.
+/// - If acquiring times out, [`FrameAcquire::Skip`] is returned.
+/// - If the frame is suboptimal, [`FrameAcquire::Suboptimal`] is returned.
+///
+/// Only running out of memory is reported as an error.
+pub fn acquire_frame(
+    surface: &Surface,
+    device: &Device,
+    config: &SurfaceConfiguration,
+) -> Result<FrameAcquire, SurfaceError> {
+    match surface.get_current_texture() {
+        Ok(frame) => Ok(ready_or_suboptimal(frame)),
+        Err(SurfaceError::Timeout) => Ok(FrameAcquire::Skip),
+        Err(SurfaceError::Outdated | SurfaceError::Lost) => {
+            surface.configure(device, config);
+            match surface.get_current_texture() {
+                Ok(frame) => Ok(ready_or_suboptimal(frame)),
+                Err(SurfaceError::OutOfMemory) => Err(SurfaceError::OutOfMemory),
+                Err(_) => Ok(FrameAcquire::Skip),
+            }
+        }
+        Err(SurfaceError::OutOfMemory) => Err(SurfaceError::OutOfMemory),
+    }
+}
+
+fn ready_or_suboptimal(frame: SurfaceTexture) -> FrameAcquire {
+    if frame.suboptimal {
+        FrameAcquire::Suboptimal(frame)
+    } else {
+        FrameAcquire::Ready(frame)
+    }
+}
+
+/// Convert `value` to the bits of the nearest half float, e.g. to fill textures with
+/// the `R16Float`, `Rg16Float` or `Rgba16Float` format.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = (bits >> 16 & 0x8000) as u16;
+    let exponent = (bits >> 23 & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    // Infinity and NaN, keeping NaNs quiet.
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // Values below the smallest normal half float become subnormal or zero.
+    let (half, shift) = if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let shift = (14 - exponent) as u32;
+        ((mantissa | 0x80_0000) >> shift, shift)
+    } else {
+        ((exponent as u32) << 10 | mantissa >> 13, 13)
+    };
+    // Round to nearest, ties to even. A carry into the exponent rounds up correctly,
+    // up to infinity.
+    let remainder = (mantissa | 0x80_0000) & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    let round_up = remainder > halfway || (remainder == halfway && half & 1 == 1);
+    sign | (half + round_up as u32) as u16
+}
+
+/// Convert the bits of a half float to the `f32` of the same value, e.g. to read back
+/// textures with a half float format.
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = (bits as u32 & 0x8000) << 16;
+    let exponent = (bits >> 10 & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+    let magnitude = match exponent {
+        // Subnormal half floats are normal as `f32`.
+        0 => {
+            let value = mantissa as f32 * 2f32.powi(-24);
+            return if sign != 0 { -value } else { value };
+        }
+        0x1f => 0x7f80_0000 | mantissa << 13,
+        _ => (exponent + 127 - 15) << 23 | mantissa << 13,
+    };
+    f32::from_bits(sign | magnitude)
+}
+
+/// The differences between two textures found by [`compare_textures`].
+#[derive(Clone, Debug)]
+pub struct DiffStats {
+    /// The width of the compared textures in pixels.
+    pub width: u32,
+    /// The height of the compared textures in pixels.
+    pub height: u32,
+    /// The largest difference of a channel of any pixel.
+    pub max_channel_delta: u8,
+    /// The number of pixels with any difference.
+    pub differing_pixels: usize,
+    /// The largest channel difference of each pixel, row by row.
+    pub pixel_deltas: Vec<u8>,
+}
+
+impl DiffStats {
+    /// Whether the textures are identical.
+    pub fn is_identical(&self) -> bool {
+        self.differing_pixels == 0
+    }
+
+    /// The number of pixels with a channel differing by more than `tolerance`, e.g. to
+    /// allow for rounding differences between GPUs.
+    pub fn pixels_above(&self, tolerance: u8) -> usize {
+        self.pixel_deltas
+            .iter()
+            .filter(|&&delta| delta > tolerance)
+            .count()
+    }
+
+    /// An `Rgba8Unorm` image of the differences, with identical pixels black and the
+    /// others red, brighter the larger the difference, e.g. to save next to a failed
+    /// golden image.
+    pub fn diff_image(&self) -> Vec<u8> {
+        self.pixel_deltas
+            .iter()
+            .flat_map(|&delta| match delta {
+                0 => [0, 0, 0, 255],
+                delta => [64 + (delta as u32 * 191 / 255) as u8, 0, 0, 255],
+            })
+            .collect()
+    }
+}
+
+/// Read both textures back and compare them, e.g. to test UIs rendered into textures
+/// against golden images.
+///
+/// The textures need the same size and format, `TextureUsages::COPY_SRC` and a format
+/// with 8 bits per channel, like `Rgba8Unorm`. Only the first layer of the first mip
+/// level is compared. This blocks until the GPU finished the copies, so it is only
+/// supported on native backends.
+pub fn compare_textures(device: &Device, queue: &Queue, a: &Texture, b: &Texture) -> DiffStats {
+    let format = a.texture().format();
+    assert!(
+        a.size() == b.size() && format == b.texture().format(),
+        "the textures must have the same size and format"
+    );
+    let channels = format.components() as usize;
+    assert!(
+        !format.is_compressed() && format.block_copy_size(None) == Some(channels as u32),
+        "only formats with 8 bits per channel can be compared"
+    );
+
+    let size = a.size();
+    let pixels = size.width as usize * size.height as usize;
+    let (data_a, data_b) = (a.read(device, queue), b.read(device, queue));
+    let (data_a, data_b) = (block_on(device, data_a), block_on(device, data_b));
+
+    let pixel_deltas: Vec<u8> = data_a
+        .chunks_exact(channels)
+        .zip(data_b.chunks_exact(channels))
+        .take(pixels)
+        .map(|(a, b)| {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| a.abs_diff(*b))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    DiffStats {
+        width: size.width,
+        height: size.height,
+        max_channel_delta: pixel_deltas.iter().copied().max().unwrap_or(0),
+        differing_pixels: pixel_deltas.iter().filter(|&&delta| delta > 0).count(),
+        pixel_deltas,
+    }
+}
@@ -0,0 +1,148 @@
+//! Video frames stored as separate luma and chroma planes.
+
+use crate::{Renderer, Texture, TextureConfig, TextureKind};
+use std::sync::Arc;
+use wgpu::*;
+
+/// An NV12 video frame, with a full resolution luma (Y) plane and a half resolution
+/// chroma (UV) plane with interleaved U and V samples.
+///
+/// The planes are converted from limited range BT.709 YUV to RGB while drawing, so
+/// decoded frames can be uploaded without converting them on the CPU.
+pub struct VideoTexture {
+    luma: Arc<wgpu::Texture>,
+    chroma: Arc<wgpu::Texture>,
+    size: Extent3d,
+}
+
+impl VideoTexture {
+    /// Create the planes of a `width` by `height` video frame.
+    pub fn new(device: &Device, label: Option<&str>, width: u32, height: u32) -> Self {
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let create_plane = |size, format| {
+            Arc::new(device.create_texture(&TextureDescriptor {
+                label,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[],
+            }))
+        };
+        let luma = create_plane(size, TextureFormat::R8Unorm);
+        let chroma = create_plane(Self::chroma_size(size), TextureFormat::Rg8Unorm);
+
+        Self { luma, chroma, size }
+    }
+
+    /// The size of the chroma plane of a frame of `size`, rounded up for odd sizes.
+    fn chroma_size(size: Extent3d) -> Extent3d {
+        Extent3d {
+            width: size.width.div_ceil(2),
+            height: size.height.div_ceil(2),
+            depth_or_array_layers: 1,
+        }
+    }
+
+    /// Create a [`Texture`] of kind [`TextureKind::Nv12`] displaying this frame, which
+    /// can be inserted into [`Renderer::textures`].
+    ///
+    /// The texture shows what was last written with [`Self::write_frame`].
+    pub fn create_texture(&self, device: &Device, renderer: &Renderer) -> Texture {
+        let kind = TextureKind::Nv12;
+        let luma_view = Arc::new(self.luma.create_view(&TextureViewDescriptor::default()));
+        let chroma_view = self.chroma.create_view(&TextureViewDescriptor::default());
+        let sampler =
+            renderer.texture_sampler(device, kind, &TextureConfig::default().sampler_desc);
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("imgui-wgpu video bind group"),
+            layout: &renderer.resources.layouts[kind as usize],
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&luma_view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::TextureView(&chroma_view),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: renderer.resources.default_style.as_entire_binding(),
+                },
+            ],
+        });
+
+        Texture {
+            texture: Some(self.luma.clone()),
+            view: Some(luma_view),
+            sampler: Some(sampler),
+            bind_group: Arc::new(bind_group),
+            kind,
+            params: None,
+            size: self.size,
+            view_desc: None,
+            style: None,
+            uv_rect: None,
+            label: None,
+            mipmaps_stale: None,
+            view_formats: Vec::new(),
+        }
+    }
+
+    /// Upload a frame.
+    ///
+    /// - `luma`: One byte per pixel, `width` bytes per row.
+    /// - `chroma`: Interleaved U and V bytes for each 2x2 block of pixels, rows of
+    ///   `width` bytes rounded up to even, for half as many rows rounded up.
+    pub fn write_frame(&self, queue: &Queue, luma: &[u8], chroma: &[u8]) {
+        Self::write_plane(queue, &self.luma, luma, self.size, 1);
+        Self::write_plane(queue, &self.chroma, chroma, Self::chroma_size(self.size), 2);
+    }
+
+    /// Write the tightly packed rows of `data` to `plane`.
+    fn write_plane(
+        queue: &Queue,
+        plane: &wgpu::Texture,
+        data: &[u8],
+        size: Extent3d,
+        bytes_per_pixel: u32,
+    ) {
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: plane,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            data,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width * bytes_per_pixel),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+    }
+
+    /// The width of the frame in pixels.
+    pub fn width(&self) -> u32 {
+        self.size.width
+    }
+
+    /// The height of the frame in pixels.
+    pub fn height(&self) -> u32 {
+        self.size.height
+    }
+}
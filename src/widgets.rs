@@ -0,0 +1,188 @@
+//! Small imgui widgets for displaying textures of the renderer.
+
+use imgui::{Image, MouseButton, TextureId, Ui};
+
+/// The size of an image of `texture_size` scaled to fit into `avail` while keeping its
+/// aspect ratio, and the offset centering it there.
+///
+/// Returns `(offset, size)`, both zero if either size is empty.
+pub fn fit_rect(texture_size: [f32; 2], avail: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+    if texture_size[0] <= 0.0 || texture_size[1] <= 0.0 || avail[0] <= 0.0 || avail[1] <= 0.0 {
+        return ([0.0; 2], [0.0; 2]);
+    }
+    let scale = (avail[0] / texture_size[0]).min(avail[1] / texture_size[1]);
+    let size = [texture_size[0] * scale, texture_size[1] * scale];
+    let offset = [(avail[0] - size[0]) / 2.0, (avail[1] - size[1]) / 2.0];
+    (offset, size)
+}
+
+/// Draw the texture `texture_id` of `texture_size` as large as fits into `avail` at the
+/// cursor, keeping its aspect ratio and centering it with empty bars at the sides.
+///
+/// Pass `ui.content_region_avail()` as `avail` to fill the rest of a window. The cursor
+/// is left below the whole `avail` area, and the size the image is drawn at is returned.
+pub fn image_fit(
+    ui: &Ui,
+    texture_id: TextureId,
+    texture_size: [f32; 2],
+    avail: [f32; 2],
+) -> [f32; 2] {
+    let (offset, size) = fit_rect(texture_size, avail);
+    let start = ui.cursor_pos();
+    ui.set_cursor_pos([start[0] + offset[0], start[1] + offset[1]]);
+    Image::new(texture_id, size).build(ui);
+    // Reserve the whole area, so following widgets don't move with the letterboxing.
+    ui.set_cursor_pos(start);
+    ui.dummy(avail);
+    size
+}
+
+/// The zoom factors [`ImageViewer`] is limited to, in screen pixels per texel.
+const ZOOM_RANGE: (f32, f32) = (1.0 / 64.0, 256.0);
+
+/// The state of an image viewer with mouse wheel zoom, drag panning and pixel
+/// inspection, drawn with [`Self::show`].
+///
+/// Double-clicking fits the image into the viewer again.
+pub struct ImageViewer {
+    texture_id: TextureId,
+    texture_size: [f32; 2],
+    nearest_texture_id: Option<TextureId>,
+    /// The screen pixels per texel, or `None` to fit the image on the next frame.
+    zoom: Option<f32>,
+    /// The texel at the center of the viewer.
+    center: [f32; 2],
+}
+
+impl ImageViewer {
+    /// A viewer of the texture `texture_id` of `texture_size`, fitted into the viewer.
+    pub fn new(texture_id: TextureId, texture_size: [f32; 2]) -> Self {
+        Self {
+            texture_id,
+            texture_size,
+            nearest_texture_id: None,
+            zoom: None,
+            center: [texture_size[0] / 2.0, texture_size[1] / 2.0],
+        }
+    }
+
+    /// Show the texture `texture_id` instead when it is magnified, e.g. the same image
+    /// inserted again with a nearest-neighbor sampler, see [`TextureConfig::pixel_art`],
+    /// so individual pixels stay sharp.
+    ///
+    /// [`TextureConfig::pixel_art`]: crate::TextureConfig::pixel_art
+    pub fn nearest_magnification(mut self, texture_id: TextureId) -> Self {
+        self.nearest_texture_id = Some(texture_id);
+        self
+    }
+
+    /// Display another texture of `texture_size`, keeping the zoom and position if the
+    /// size didn't change.
+    pub fn set_texture(&mut self, texture_id: TextureId, texture_size: [f32; 2]) {
+        if texture_size != self.texture_size {
+            self.texture_size = texture_size;
+            self.fit();
+        }
+        self.texture_id = texture_id;
+    }
+
+    /// The screen pixels per texel, `None` until the first [`Self::show`] after creating
+    /// the viewer or fitting the image.
+    pub fn zoom(&self) -> Option<f32> {
+        self.zoom
+    }
+
+    /// Fit the whole image into the viewer on the next [`Self::show`].
+    pub fn fit(&mut self) {
+        self.zoom = None;
+        self.center = [self.texture_size[0] / 2.0, self.texture_size[1] / 2.0];
+    }
+
+    /// Draw the viewer filling `size` at the cursor and handle its input. Returns the
+    /// texel under the mouse cursor, e.g. to show its value, which is also shown in a
+    /// tooltip.
+    pub fn show(&mut self, ui: &Ui, size: [f32; 2]) -> Option<[u32; 2]> {
+        let size = [size[0].max(1.0), size[1].max(1.0)];
+        let origin = ui.cursor_screen_pos();
+        ui.invisible_button("##image_viewer", size);
+        let hovered = ui.is_item_hovered();
+        let io = ui.io();
+
+        let zoom = *self.zoom.get_or_insert_with(|| {
+            let (_, fitted) = fit_rect(self.texture_size, size);
+            (fitted[0] / self.texture_size[0].max(1.0)).clamp(ZOOM_RANGE.0, ZOOM_RANGE.1)
+        });
+        let view_center = [origin[0] + size[0] / 2.0, origin[1] + size[1] / 2.0];
+        let to_texel = |pos: [f32; 2], zoom: f32, center: [f32; 2]| {
+            [
+                center[0] + (pos[0] - view_center[0]) / zoom,
+                center[1] + (pos[1] - view_center[1]) / zoom,
+            ]
+        };
+
+        if ui.is_item_active() {
+            self.center[0] -= io.mouse_delta[0] / zoom;
+            self.center[1] -= io.mouse_delta[1] / zoom;
+        }
+        if hovered && ui.is_mouse_double_clicked(MouseButton::Left) {
+            self.fit();
+        } else if hovered && io.mouse_wheel != 0.0 {
+            // Zoom around the mouse cursor, so the texel under it stays in place.
+            let texel = to_texel(io.mouse_pos, zoom, self.center);
+            let new_zoom = (zoom * 1.2f32.powf(io.mouse_wheel)).clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+            self.center = [
+                texel[0] - (io.mouse_pos[0] - view_center[0]) / new_zoom,
+                texel[1] - (io.mouse_pos[1] - view_center[1]) / new_zoom,
+            ];
+            self.zoom = Some(new_zoom);
+        }
+        let zoom = self.zoom.unwrap_or(zoom);
+
+        let image_min = [
+            view_center[0] - self.center[0] * zoom,
+            view_center[1] - self.center[1] * zoom,
+        ];
+        let image_max = [
+            image_min[0] + self.texture_size[0] * zoom,
+            image_min[1] + self.texture_size[1] * zoom,
+        ];
+        let texture_id = match self.nearest_texture_id {
+            Some(nearest) if zoom > 1.0 => nearest,
+            _ => self.texture_id,
+        };
+        let draw_list = ui.get_window_draw_list();
+        let view_max = [origin[0] + size[0], origin[1] + size[1]];
+        draw_list.with_clip_rect_intersect(origin, view_max, || {
+            draw_list
+                .add_image(texture_id, image_min, image_max)
+                .build();
+        });
+
+        if !hovered {
+            return None;
+        }
+        let texel = to_texel(io.mouse_pos, zoom, self.center);
+        if texel[0] < 0.0
+            || texel[1] < 0.0
+            || texel[0] >= self.texture_size[0]
+            || texel[1] >= self.texture_size[1]
+        {
+            return None;
+        }
+        let texel = [texel[0] as u32, texel[1] as u32];
+        // Outline the texel once it is large enough to be seen.
+        if zoom >= 8.0 {
+            let min = [
+                image_min[0] + texel[0] as f32 * zoom,
+                image_min[1] + texel[1] as f32 * zoom,
+            ];
+            draw_list.with_clip_rect_intersect(origin, view_max, || {
+                draw_list
+                    .add_rect(min, [min[0] + zoom, min[1] + zoom], [1.0, 1.0, 0.0, 1.0])
+                    .build();
+            });
+        }
+        ui.tooltip_text(format!("{}, {}", texel[0], texel[1]));
+        Some(texel)
+    }
+}
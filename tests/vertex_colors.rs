@@ -0,0 +1,207 @@
+//! Theme colors rendered to sRGB and linear targets come back unchanged.
+//!
+//! The tests need a GPU adapter and are ignored by default, run them with
+//! `cargo test -- --ignored`. The shader logic is also checked without a GPU by the unit
+//! tests of the crate.
+
+use imgui::{Context, ImColor32, StyleColor};
+use imgui_wgpu::{util, Renderer, RendererConfig, Texture, TextureConfig};
+use std::sync::Mutex;
+use wgpu::*;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 16;
+
+const THEME_COLORS: [StyleColor; 4] = [
+    StyleColor::WindowBg,
+    StyleColor::Button,
+    StyleColor::CheckMark,
+    StyleColor::PlotHistogram,
+];
+
+/// imgui allows one context at a time, but tests run in parallel.
+static IMGUI: Mutex<()> = Mutex::new(());
+
+fn device() -> (Device, Queue) {
+    let instance = Instance::default();
+    let adapter = pollster::block_on(instance.request_adapter(&RequestAdapterOptions::default()))
+        .expect("No adapter available");
+    pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None))
+        .expect("Failed to create the device")
+}
+
+/// Render one opaque stripe per theme color into a target of `config.texture_format`
+/// and return the colors along with the contents of the target.
+fn render_stripes(
+    device: &Device,
+    queue: &Queue,
+    config: RendererConfig,
+) -> (Vec<ImColor32>, Vec<u8>) {
+    let _guard = IMGUI.lock().unwrap_or_else(|err| err.into_inner());
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.io_mut().display_size = [WIDTH as f32, HEIGHT as f32];
+
+    let format = config.texture_format;
+    let mut renderer =
+        Renderer::new(&mut imgui, device, queue, config).expect("Failed to create the renderer");
+    let target = Texture::new(
+        device,
+        &renderer,
+        TextureConfig {
+            size: Extent3d {
+                width: WIDTH,
+                height: HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            format: Some(format),
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC,
+            ..Default::default()
+        },
+    );
+
+    let colors: Vec<_> = THEME_COLORS
+        .iter()
+        .map(|&color| {
+            let [r, g, b, _] = imgui.style()[color];
+            ImColor32::from([r, g, b, 1.0])
+        })
+        .collect();
+    let stripe = (WIDTH as usize / colors.len()) as f32;
+
+    let ui = imgui.new_frame();
+    let draw_list = ui.get_background_draw_list();
+    for (i, &color) in colors.iter().enumerate() {
+        draw_list
+            .add_rect(
+                [i as f32 * stripe, 0.0],
+                [(i + 1) as f32 * stripe, HEIGHT as f32],
+                color,
+            )
+            .filled(true)
+            .build();
+    }
+    drop(draw_list);
+    let draw_data = imgui.render();
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    let mut rpass = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: target.view(),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    renderer
+        .render(draw_data, queue, device, &mut rpass)
+        .expect("Rendering failed");
+    drop(rpass);
+    queue.submit(Some(encoder.finish()));
+
+    let data = target.read(device, queue);
+    device.poll(Maintain::Wait);
+    (colors, pollster::block_on(data))
+}
+
+/// The centers of the stripes as `[r, g, b]` in the range 0 to 1.
+fn stripe_centers(pixels: &[[f32; 4]], count: usize) -> Vec<[f32; 3]> {
+    let stripe = WIDTH as usize / count;
+    let row = HEIGHT as usize / 2 * WIDTH as usize;
+    (0..count)
+        .map(|i| {
+            let [r, g, b, _] = pixels[row + i * stripe + stripe / 2];
+            [r, g, b]
+        })
+        .collect()
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn expected_srgb(color: ImColor32) -> [f32; 3] {
+    [color.r, color.g, color.b].map(|channel| channel as f32 / 255.0)
+}
+
+fn assert_close(actual: &[[f32; 3]], expected: &[[f32; 3]], tolerance: f32) {
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() <= tolerance),
+            "rendered {actual:?}, expected {expected:?}"
+        );
+    }
+}
+
+fn check_srgb_target(linearize_vertex_colors: bool) {
+    let (device, queue) = device();
+    let config = RendererConfig {
+        texture_format: TextureFormat::Rgba8UnormSrgb,
+        linearize_vertex_colors,
+        ..RendererConfig::new()
+    };
+    let (colors, data) = render_stripes(&device, &queue, config);
+
+    let pixels: Vec<_> = data
+        .chunks_exact(4)
+        .map(|pixel| [0, 1, 2, 3].map(|i| pixel[i] as f32 / 255.0))
+        .collect();
+    let expected: Vec<_> = colors.into_iter().map(expected_srgb).collect();
+    assert_close(
+        &stripe_centers(&pixels, expected.len()),
+        &expected,
+        1.0 / 255.0,
+    );
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn srgb_target() {
+    check_srgb_target(false);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn srgb_target_with_linearized_vertex_colors() {
+    check_srgb_target(true);
+}
+
+#[test]
+#[ignore = "needs a GPU adapter"]
+fn linear_target_with_linearized_vertex_colors() {
+    let (device, queue) = device();
+    let config = RendererConfig {
+        texture_format: TextureFormat::Rgba16Float,
+        linearize_vertex_colors: true,
+        ..RendererConfig::new_srgb()
+    };
+    let (colors, data) = render_stripes(&device, &queue, config);
+
+    let pixels: Vec<_> = data
+        .chunks_exact(8)
+        .map(|pixel| {
+            [0, 1, 2, 3]
+                .map(|i| util::f16_to_f32(u16::from_le_bytes([pixel[2 * i], pixel[2 * i + 1]])))
+        })
+        .collect();
+    let expected: Vec<_> = colors
+        .into_iter()
+        .map(|color| expected_srgb(color).map(srgb_to_linear))
+        .collect();
+    assert_close(&stripe_centers(&pixels, expected.len()), &expected, 0.002);
+}